@@ -1,22 +1,31 @@
+use crate::control::ControlCommand;
+use crate::error::{HydraError, Result};
+use crate::plan;
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
 /// Automated Claude Code task runner
 ///
 /// Run with an optional implementation plan:
-///   hydra [PLAN] [OPTIONS]
+///   hydra [PLAN]... [OPTIONS]
 ///
 /// Examples:
 ///   hydra                    Run using prompt only
 ///   hydra plan.md            Run with plan injected after prompt
 ///   hydra plan.md --max 5    Run with plan, limit to 5 iterations
+///   hydra a.md b.md          Run plans one after another
 #[derive(Parser, Debug)]
 #[command(name = "hydra")]
 #[command(version, about, long_about = None)]
 pub struct Cli {
-    /// Optional path to implementation plan file (injected after prompt)
+    /// Optional path to implementation plan file (injected after prompt).
+    /// Several plans run one after another, sharing the iteration budget.
     #[arg(value_name = "PLAN")]
-    pub plan: Option<PathBuf>,
+    pub plan: Vec<PathBuf>,
+
+    /// Run every plan (*.md) in this directory one after another, in name order
+    #[arg(long, value_name = "DIR", conflicts_with = "plan")]
+    pub queue: Option<PathBuf>,
 
     #[command(subcommand)]
     pub command: Option<Command>,
@@ -37,10 +46,20 @@ pub struct Cli {
     #[arg(short, long)]
     pub prompt: Option<PathBuf>,
 
+    /// Override iteration instructions file path (default:
+    /// .hydra/iteration-instructions.md, then ~/.hydra/iteration-instructions.md,
+    /// then the built-in instructions)
+    #[arg(long, value_name = "FILE")]
+    pub instructions: Option<PathBuf>,
+
     /// Reset plan checkboxes and clear scratchpad before running
     #[arg(long)]
     pub reset_plan: bool,
 
+    /// Skip checking the plan with `hydra lint` before the session
+    #[arg(long)]
+    pub no_lint: bool,
+
     /// Install hydra to ~/.local/bin
     #[arg(long)]
     pub install: bool,
@@ -58,6 +77,15 @@ pub struct Cli {
     #[arg(long, value_name = "NAME")]
     pub harness: Option<String>,
 
+    /// Pause after each iteration to review it (continue, retry, edit, skip or quit)
+    #[arg(long)]
+    pub step: bool,
+
+    /// Only pause on these results (comma-separated: timeout, no-signal).
+    /// Implies --step.
+    #[arg(long, value_name = "RESULTS")]
+    pub step_on: Option<String>,
+
     /// Timeout per iteration in seconds (default: 3000 = 50 minutes)
     /// If Claude doesn't output a stop signal within this time, the iteration is terminated
     #[arg(short, long, default_value = "3000")]
@@ -73,11 +101,91 @@ pub enum Command {
         quick: bool,
     },
 
-    /// Start TUI mode with multi-tab interface
+    /// Start TUI mode with multi-tab interface, attaching to this
+    /// directory's daemon (started if needed)
     Tui {
-        /// Optional path to implementation plan file
+        /// Implementation plan files, one tab each (default: one tab without
+        /// a plan when no daemon is running)
+        #[arg(value_name = "PLAN")]
+        plans: Vec<std::path::PathBuf>,
+    },
+
+    /// Run the TUI daemon, which owns the tabs' agents, in the foreground
+    /// (`hydra tui` starts it in the background)
+    Daemon,
+
+    /// List the TUI daemon's tabs in this directory
+    Ls,
+
+    /// Show plan progress, recent sessions and whether hydra is running
+    Status {
+        /// Only show this plan (default: every plan in plans/)
+        #[arg(value_name = "PLAN")]
+        plan: Option<PathBuf>,
+
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Work with implementation plans
+    Plan {
+        #[command(subcommand)]
+        command: PlanCommand,
+    },
+
+    /// Check a plan for missing tasks, broken links and unclear tasks
+    Lint {
+        /// Path to the implementation plan file
+        #[arg(value_name = "PLAN")]
+        plan: PathBuf,
+    },
+
+    /// Stop a hydra session running in this directory after its current iteration
+    Stop {
+        /// Plan (path or name) of the session; optional when only one
+        /// session is running
+        #[arg(value_name = "PLAN")]
+        plan: Option<PathBuf>,
+
+        /// Kill the current iteration instead of letting it finish
+        #[arg(long)]
+        now: bool,
+    },
+
+    /// Pause a running session before its next iteration
+    Pause {
+        /// Plan (path or name) of the session
+        #[arg(value_name = "PLAN")]
+        plan: Option<PathBuf>,
+    },
+
+    /// Resume a paused session
+    ResumeLoop {
+        /// Plan (path or name) of the session
+        #[arg(value_name = "PLAN")]
+        plan: Option<PathBuf>,
+    },
+
+    /// Kill the current iteration of a running session and continue with the next
+    Skip {
+        /// Plan (path or name) of the session
         #[arg(value_name = "PLAN")]
-        plan: Option<std::path::PathBuf>,
+        plan: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PlanCommand {
+    /// Generate a plan from a spec with the configured harness
+    New {
+        /// Spec to plan (e.g. specs/auth.md)
+        #[arg(value_name = "SPEC")]
+        spec: PathBuf,
+
+        /// Where to write the plan (default: plans/<spec name>.md)
+        #[arg(long, value_name = "PLAN")]
+        out: Option<PathBuf>,
     },
 }
 
@@ -97,15 +205,97 @@ impl Cli {
         matches!(self.command, Some(Command::Tui { .. }))
     }
 
-    /// Get the plan path from the tui subcommand (if any)
-    pub fn tui_plan(&self) -> Option<&std::path::PathBuf> {
-        if let Some(Command::Tui { plan }) = &self.command {
-            plan.as_ref()
+    /// Check if this is a daemon command
+    pub fn is_daemon(&self) -> bool {
+        matches!(self.command, Some(Command::Daemon))
+    }
+
+    /// Check if this is an ls command
+    pub fn is_ls(&self) -> bool {
+        matches!(self.command, Some(Command::Ls))
+    }
+
+    /// Get the plan paths from the tui subcommand, one per startup tab
+    pub fn tui_plans(&self) -> &[std::path::PathBuf] {
+        if let Some(Command::Tui { plans }) = &self.command {
+            plans
+        } else {
+            &[]
+        }
+    }
+
+    /// Check if this is a status command
+    pub fn is_status(&self) -> bool {
+        matches!(self.command, Some(Command::Status { .. }))
+    }
+
+    /// Get the plan filter and --json flag from the status subcommand
+    pub fn status_args(&self) -> (Option<&PathBuf>, bool) {
+        if let Some(Command::Status { plan, json }) = &self.command {
+            (plan.as_ref(), *json)
+        } else {
+            (None, false)
+        }
+    }
+
+    /// Get the spec and --out path from `plan new` (if any)
+    pub fn plan_new_args(&self) -> Option<(&PathBuf, Option<&PathBuf>)> {
+        match &self.command {
+            Some(Command::Plan {
+                command: PlanCommand::New { spec, out },
+            }) => Some((spec, out.as_ref())),
+            _ => None,
+        }
+    }
+
+    /// Get the plan path from the lint subcommand (if any)
+    pub fn lint_plan(&self) -> Option<&PathBuf> {
+        if let Some(Command::Lint { plan }) = &self.command {
+            Some(plan)
         } else {
             None
         }
     }
 
+    /// Get the control request for stop/pause/resume-loop/skip (if any)
+    pub fn control_request(&self) -> Option<(ControlCommand, Option<&PathBuf>)> {
+        match &self.command {
+            Some(Command::Stop { plan, now: false }) => Some((ControlCommand::Stop, plan.as_ref())),
+            Some(Command::Stop { plan, now: true }) => {
+                Some((ControlCommand::StopNow, plan.as_ref()))
+            }
+            Some(Command::Pause { plan }) => Some((ControlCommand::Pause, plan.as_ref())),
+            Some(Command::ResumeLoop { plan }) => Some((ControlCommand::Resume, plan.as_ref())),
+            Some(Command::Skip { plan }) => Some((ControlCommand::Skip, plan.as_ref())),
+            _ => None,
+        }
+    }
+
+    /// Plans to run, in order: the positional plans, or the Markdown files
+    /// in the `--queue` directory. Empty when running on the prompt alone.
+    pub fn plan_queue(&self) -> Result<Vec<PathBuf>> {
+        let Some(dir) = &self.queue else {
+            return Ok(self.plan.clone());
+        };
+        let invalid = |reason: &str| HydraError::InvalidQueue {
+            dir: dir.clone(),
+            reason: reason.to_string(),
+        };
+        if !dir.is_dir() {
+            return Err(invalid("not a directory"));
+        }
+        let plans = plan::discover_plans(dir);
+        if plans.is_empty() {
+            return Err(invalid("no plans (*.md) found"));
+        }
+        Ok(plans)
+    }
+
+    /// Check if step mode was requested (--step or --step-on)
+    pub fn is_step(&self) -> bool {
+        self.step || self.step_on.is_some()
+    }
+
     /// Check if this is an install command
     pub fn is_install(&self) -> bool {
         self.install
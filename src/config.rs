@@ -1,4 +1,5 @@
 use crate::error::{HydraError, Result};
+use crate::tui::{Keymap, Notify};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -17,6 +18,41 @@ pub struct Config {
 
     /// Timeout per iteration in seconds (default: 3000 = 50 minutes)
     pub timeout_seconds: u64,
+
+    /// Extra attempts allowed when an iteration times out or ends without a
+    /// stop signal (default: 0 = no retries). Retries don't count against
+    /// `max_iterations`.
+    pub retries_per_task: u32,
+
+    /// Delay before the first retry in seconds, doubled for each further retry
+    pub retry_backoff_seconds: u64,
+
+    /// Factor applied to the iteration timeout on every retry (1.0 = unchanged)
+    pub retry_timeout_multiplier: f64,
+
+    /// Derive per-iteration timeouts from recorded durations (p95 × 1.5,
+    /// capped at `timeout_seconds`) when the next task has no timeout hint.
+    /// Off unless set, since it can shorten the configured timeout; an
+    /// explicit `--timeout` turns it off again.
+    pub adaptive_timeout: bool,
+
+    /// Files appended to every prompt, in order, after includes are
+    /// expanded. Relative paths are relative to `~/.hydra/`.
+    pub prompt_fragments: Vec<PathBuf>,
+
+    /// Lines of output each TUI tab keeps after they scroll off the screen
+    pub tui_scrollback: usize,
+
+    /// Minutes without output after which a running TUI tab needs
+    /// attention (0 = never)
+    pub tui_idle_minutes: u64,
+
+    /// How the TUI tells about a tab that comes to need attention: `off`,
+    /// `bell` or `desktop`
+    pub tui_notify: Notify,
+
+    /// TUI keybindings (`[keymap]` section)
+    pub keymap: Keymap,
 }
 
 impl Default for Config {
@@ -26,6 +62,15 @@ impl Default for Config {
             verbose: false,
             stop_file: ".hydra-stop".to_string(),
             timeout_seconds: 3000, // 50 minutes
+            retries_per_task: 0,
+            retry_backoff_seconds: 30,
+            retry_timeout_multiplier: 1.0,
+            adaptive_timeout: false,
+            prompt_fragments: Vec::new(),
+            tui_scrollback: 10_000,
+            tui_idle_minutes: 5,
+            tui_notify: Notify::Off,
+            keymap: Keymap::default(),
         }
     }
 }
@@ -71,11 +116,21 @@ impl Config {
         Self::global_hydra_dir().join("default-prompt.md")
     }
 
+    /// Get the path to the global iteration instructions override
+    pub fn global_instructions_path() -> PathBuf {
+        Self::global_hydra_dir().join("iteration-instructions.md")
+    }
+
     /// Get the path to the global prompt template file (used by `hydra init`)
     pub fn global_prompt_template_path() -> PathBuf {
         Self::global_hydra_dir().join("prompt-template.md")
     }
 
+    /// Get the path to the global plan-authoring template (used by `hydra plan new`)
+    pub fn global_plan_template_path() -> PathBuf {
+        Self::global_hydra_dir().join("plan-template.md")
+    }
+
     /// Get the path to the global skill templates directory (~/.hydra/skill-templates)
     pub fn global_skill_templates_dir() -> PathBuf {
         Self::global_hydra_dir().join("skill-templates")
@@ -91,6 +146,12 @@ impl Config {
         Self::local_hydra_dir().join("prompt.md")
     }
 
+    /// Get the path to the project iteration instructions override
+    /// (./.hydra/iteration-instructions.md)
+    pub fn local_instructions_path() -> PathBuf {
+        Self::local_hydra_dir().join("iteration-instructions.md")
+    }
+
     /// Get the path to the logs directory (./.hydra/logs)
     pub fn logs_dir() -> PathBuf {
         Self::local_hydra_dir().join("logs")
@@ -106,6 +167,36 @@ impl Config {
         Self::local_hydra_dir().join("reviews")
     }
 
+    /// Get the path to the duration history directory (./.hydra/history)
+    pub fn history_dir() -> PathBuf {
+        Self::local_hydra_dir().join("history")
+    }
+
+    /// Get the path to the session locks directory (./.hydra/locks)
+    pub fn locks_dir() -> PathBuf {
+        Self::local_hydra_dir().join("locks")
+    }
+
+    /// Get the path to the agent questions directory (./.hydra/questions)
+    pub fn questions_dir() -> PathBuf {
+        Self::local_hydra_dir().join("questions")
+    }
+
+    /// Get the path TUI copy mode saves yanked text to (./.hydra/yank.txt)
+    pub fn yank_path() -> PathBuf {
+        Self::local_hydra_dir().join("yank.txt")
+    }
+
+    /// Get the path of the TUI daemon's socket (./.hydra/daemon.sock)
+    pub fn daemon_socket_path() -> PathBuf {
+        Self::local_hydra_dir().join("daemon.sock")
+    }
+
+    /// Get the path the TUI daemon writes its errors to (./.hydra/daemon.log)
+    pub fn daemon_log_path() -> PathBuf {
+        Self::local_hydra_dir().join("daemon.log")
+    }
+
     /// Merge CLI options over config values
     /// CLI options take precedence when provided
     pub fn merge_cli(&mut self, max: Option<u32>, verbose: bool, timeout: Option<u64>) {
@@ -116,8 +207,10 @@ impl Config {
         if verbose {
             self.verbose = true;
         }
+        // An explicit --timeout is taken literally rather than adapted
         if let Some(t) = timeout {
             self.timeout_seconds = t;
+            self.adaptive_timeout = false;
         }
     }
 }
@@ -135,6 +228,101 @@ mod tests {
         assert!(!config.verbose);
         assert_eq!(config.stop_file, ".hydra-stop");
         assert_eq!(config.timeout_seconds, 3000);
+        assert_eq!(config.retries_per_task, 0);
+        assert_eq!(config.retry_backoff_seconds, 30);
+        assert_eq!(config.retry_timeout_multiplier, 1.0);
+        assert!(!config.adaptive_timeout);
+        assert!(config.prompt_fragments.is_empty());
+        assert_eq!(config.tui_scrollback, 10_000);
+    }
+
+    #[test]
+    fn test_load_retry_settings_from_toml() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+
+        fs::write(
+            &config_path,
+            "retries_per_task = 2\nretry_backoff_seconds = 5\nretry_timeout_multiplier = 1.5\n",
+        )
+        .unwrap();
+
+        let config = Config::load_from_path(&config_path).unwrap();
+        assert_eq!(config.retries_per_task, 2);
+        assert_eq!(config.retry_backoff_seconds, 5);
+        assert_eq!(config.retry_timeout_multiplier, 1.5);
+    }
+
+    #[test]
+    fn test_adaptive_timeout_is_opt_in() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+
+        fs::write(&config_path, "timeout_seconds = 1200\n").unwrap();
+        let config = Config::load_from_path(&config_path).unwrap();
+        assert!(!config.adaptive_timeout);
+
+        fs::write(&config_path, "adaptive_timeout = true\n").unwrap();
+        let mut config = Config::load_from_path(&config_path).unwrap();
+        assert!(config.adaptive_timeout);
+        config.merge_cli(None, false, Some(600));
+        assert!(!config.adaptive_timeout);
+    }
+
+    #[test]
+    fn test_load_prompt_fragments_from_toml() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+
+        fs::write(
+            &config_path,
+            "prompt_fragments = [\"fragments/standards.md\", \"~/shared/tests.md\"]\n",
+        )
+        .unwrap();
+
+        let config = Config::load_from_path(&config_path).unwrap();
+        assert_eq!(
+            config.prompt_fragments,
+            vec![
+                PathBuf::from("fragments/standards.md"),
+                PathBuf::from("~/shared/tests.md")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_keymap_from_toml() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+
+        fs::write(&config_path, "[keymap]\nprefix = \"ctrl+a\"\n").unwrap();
+        let config = Config::load_from_path(&config_path).unwrap();
+        assert_eq!(config.keymap.prefix.to_string(), "ctrl+a");
+        assert_eq!(config.keymap.detach, Keymap::default().detach);
+
+        fs::write(&config_path, "[keymap]\ndetach = [\"hyper+d\"]\n").unwrap();
+        let err = Config::load_from_path(&config_path).unwrap_err();
+        assert!(matches!(err, HydraError::ConfigParse { .. }));
+    }
+
+    #[test]
+    fn test_load_tui_notify() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+
+        fs::write(
+            &config_path,
+            "tui_notify = \"desktop\"\ntui_idle_minutes = 0\n",
+        )
+        .unwrap();
+        let config = Config::load_from_path(&config_path).unwrap();
+        assert_eq!(config.tui_notify, Notify::Desktop);
+        assert_eq!(config.tui_idle_minutes, 0);
+        assert_eq!(Config::default().tui_notify, Notify::Off);
+
+        fs::write(&config_path, "tui_notify = \"loud\"\n").unwrap();
+        let err = Config::load_from_path(&config_path).unwrap_err();
+        assert!(matches!(err, HydraError::ConfigParse { .. }));
     }
 
     #[test]
@@ -186,6 +374,7 @@ timeout_seconds = 600
         assert_eq!(config.max_iterations, 25);
         assert!(config.verbose);
         assert_eq!(config.timeout_seconds, 300);
+        assert!(!config.adaptive_timeout); // explicit --timeout disables adaptation
 
         // Merge with None keeps existing value
         config.merge_cli(None, false, None);
@@ -214,9 +403,18 @@ timeout_seconds = 600
         assert!(Config::global_config_path().ends_with("config.toml"));
         assert!(Config::global_default_prompt_path().ends_with("default-prompt.md"));
         assert!(Config::global_prompt_template_path().ends_with("prompt-template.md"));
+        assert!(Config::global_plan_template_path().ends_with("plan-template.md"));
         assert_eq!(Config::local_hydra_dir(), PathBuf::from(".hydra"));
         assert!(Config::local_prompt_path().ends_with("prompt.md"));
+        assert!(Config::local_instructions_path().ends_with(".hydra/iteration-instructions.md"));
+        assert!(Config::global_instructions_path().ends_with("iteration-instructions.md"));
         assert!(Config::logs_dir().ends_with("logs"));
         assert!(Config::scratchpad_dir().ends_with("scratchpad"));
+        assert!(Config::history_dir().ends_with("history"));
+        assert!(Config::locks_dir().ends_with("locks"));
+        assert!(Config::questions_dir().ends_with("questions"));
+        assert!(Config::yank_path().ends_with(".hydra/yank.txt"));
+        assert!(Config::daemon_socket_path().ends_with(".hydra/daemon.sock"));
+        assert!(Config::daemon_log_path().ends_with(".hydra/daemon.log"));
     }
 }
@@ -0,0 +1,372 @@
+//! Control socket for a running session
+//!
+//! Each session listens on `.hydra/locks/<plan>.sock`, next to its lock file.
+//! `hydra stop`, `hydra pause`, `hydra resume-loop` and `hydra skip` connect
+//! from another terminal, send a single command line and print the one-line
+//! reply.
+
+use crate::config::Config;
+use crate::error::{HydraError, Result};
+use crate::lock::{self, LockInfo};
+use crate::plan;
+use crate::signal;
+use std::fs;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// How long either side waits on a silent peer
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How often a paused loop checks whether it may continue
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Commands accepted on the control socket
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ControlCommand {
+    /// Stop after the current iteration
+    Stop,
+    /// Kill the current iteration and stop
+    StopNow,
+    /// Hold before the next iteration
+    Pause,
+    /// Release a pause
+    Resume,
+    /// Kill the current iteration and move on to the next one
+    Skip,
+}
+
+impl ControlCommand {
+    /// Wire name of the command
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ControlCommand::Stop => "stop",
+            ControlCommand::StopNow => "stop-now",
+            ControlCommand::Pause => "pause",
+            ControlCommand::Resume => "resume",
+            ControlCommand::Skip => "skip",
+        }
+    }
+
+    /// Parse a wire name
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "stop" => Some(ControlCommand::Stop),
+            "stop-now" => Some(ControlCommand::StopNow),
+            "pause" => Some(ControlCommand::Pause),
+            "resume" => Some(ControlCommand::Resume),
+            "skip" => Some(ControlCommand::Skip),
+            _ => None,
+        }
+    }
+}
+
+/// Requests received over the socket, read by the runner between and after
+/// iterations
+#[derive(Debug, Default)]
+pub struct ControlState {
+    stop_requested: AtomicBool,
+    paused: AtomicBool,
+    skip_requested: AtomicBool,
+}
+
+impl ControlState {
+    /// Whether `hydra stop` asked the loop to end
+    pub fn stop_requested(&self) -> bool {
+        self.stop_requested.load(Ordering::SeqCst)
+    }
+
+    /// Whether the loop should hold before the next iteration
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Consume a pending skip request for the iteration that just ended
+    pub fn take_skip(&self) -> bool {
+        self.skip_requested.swap(false, Ordering::SeqCst)
+    }
+
+    /// Block while paused; a stop request or `should_stop` ends the wait
+    pub fn wait_while_paused(&self, should_stop: &AtomicBool) {
+        while self.is_paused() && !self.stop_requested() && !should_stop.load(Ordering::SeqCst) {
+            thread::sleep(PAUSE_POLL_INTERVAL);
+        }
+    }
+
+    /// Apply a command and return the reply sent back to the client
+    fn apply(&self, command: ControlCommand, should_stop: &AtomicBool) -> String {
+        match command {
+            ControlCommand::Stop => {
+                self.stop_requested.store(true, Ordering::SeqCst);
+                self.paused.store(false, Ordering::SeqCst);
+                "stopping after the current iteration".to_string()
+            }
+            ControlCommand::StopNow => {
+                self.stop_requested.store(true, Ordering::SeqCst);
+                self.paused.store(false, Ordering::SeqCst);
+                should_stop.store(true, Ordering::SeqCst);
+                signal::terminate_child();
+                "stopping now".to_string()
+            }
+            ControlCommand::Pause => {
+                if self.paused.swap(true, Ordering::SeqCst) {
+                    "already paused".to_string()
+                } else {
+                    "pausing before the next iteration".to_string()
+                }
+            }
+            ControlCommand::Resume => {
+                if self.paused.swap(false, Ordering::SeqCst) {
+                    "resuming".to_string()
+                } else {
+                    "not paused".to_string()
+                }
+            }
+            ControlCommand::Skip => {
+                self.skip_requested.store(true, Ordering::SeqCst);
+                if signal::terminate_child() {
+                    "skipping the current iteration".to_string()
+                } else {
+                    self.skip_requested.store(false, Ordering::SeqCst);
+                    "no iteration is running".to_string()
+                }
+            }
+        }
+    }
+}
+
+/// Listener thread serving the control socket; the socket file is removed on drop
+pub struct ControlServer {
+    path: PathBuf,
+    state: Arc<ControlState>,
+}
+
+impl ControlServer {
+    /// Listen on the socket for the session holding `session_name`'s lock
+    pub fn start(session_name: &str, should_stop: Arc<AtomicBool>) -> Result<Self> {
+        Self::start_at(socket_path(session_name), should_stop)
+    }
+
+    fn start_at(path: PathBuf, should_stop: Arc<AtomicBool>) -> Result<Self> {
+        // The caller holds the session lock, so a leftover socket is from a
+        // session that died without cleaning up
+        if path.exists() {
+            let _ = fs::remove_file(&path);
+        }
+        let listener = UnixListener::bind(&path)
+            .map_err(|e| HydraError::io(format!("binding control socket {}", path.display()), e))?;
+
+        let state = Arc::new(ControlState::default());
+        let thread_state = Arc::clone(&state);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                handle_client(stream, &thread_state, &should_stop);
+            }
+        });
+
+        Ok(Self { path, state })
+    }
+
+    /// Shared request state for the runner
+    pub fn state(&self) -> Arc<ControlState> {
+        Arc::clone(&self.state)
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn handle_client(stream: UnixStream, state: &ControlState, should_stop: &AtomicBool) {
+    let _ = stream.set_read_timeout(Some(SOCKET_TIMEOUT));
+    let mut line = String::new();
+    let mut reader = BufReader::new(&stream);
+    if reader.read_line(&mut line).is_err() {
+        return;
+    }
+    let reply = match ControlCommand::parse(&line) {
+        Some(command) => format!("ok {}", state.apply(command, should_stop)),
+        None => format!("error unknown command: {}", line.trim()),
+    };
+    let _ = (&stream).write_all(format!("{}\n", reply).as_bytes());
+}
+
+/// Socket path for a session (./.hydra/locks/<name>.sock)
+pub fn socket_path(session_name: &str) -> PathBuf {
+    Config::locks_dir().join(format!("{}.sock", session_name))
+}
+
+/// Send a command to a session's socket and return its reply message
+fn send(path: &Path, command: ControlCommand) -> std::io::Result<String> {
+    let mut stream = UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(SOCKET_TIMEOUT))?;
+    stream.write_all(format!("{}\n", command.as_str()).as_bytes())?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream).read_line(&mut reply)?;
+    match reply.trim().split_once(' ') {
+        Some(("ok", message)) => Ok(message.to_string()),
+        Some(("error", message)) => Err(std::io::Error::other(message.to_string())),
+        _ => Err(std::io::Error::new(
+            ErrorKind::InvalidData,
+            format!("unexpected reply: {}", reply.trim()),
+        )),
+    }
+}
+
+/// Entry point for `hydra stop|pause|resume-loop|skip`
+pub fn control_command(plan: Option<&Path>, command: ControlCommand) -> Result<()> {
+    let Some(session) = select_session(plan)? else {
+        println!("No running hydra session found");
+        return Ok(());
+    };
+
+    let path = socket_path(session.name());
+    // A TUI tab shares the daemon's PID with every other tab, so it is
+    // never signalled
+    if session.tui {
+        return Err(HydraError::SessionInTui(session.name().to_string()));
+    }
+    match send(&path, command) {
+        Ok(message) => {
+            println!("{} (PID {}): {}", session.name(), session.pid, message);
+            Ok(())
+        }
+        // Sessions started before the control socket existed only
+        // understand signals; SIGTERM is the closest match to a stop
+        Err(e)
+            if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::ConnectionRefused)
+                && matches!(command, ControlCommand::Stop | ControlCommand::StopNow) =>
+        {
+            let pid = nix::unistd::Pid::from_raw(session.pid as i32);
+            nix::sys::signal::kill(pid, nix::sys::signal::Signal::SIGTERM).map_err(|e| {
+                HydraError::io(
+                    format!("signalling hydra process {}", session.pid),
+                    std::io::Error::from(e),
+                )
+            })?;
+            println!(
+                "{} (PID {}): no control socket, sent SIGTERM",
+                session.name(),
+                session.pid
+            );
+            Ok(())
+        }
+        Err(e) => Err(HydraError::io(
+            format!("sending {} to {}", command.as_str(), path.display()),
+            e,
+        )),
+    }
+}
+
+/// Pick the session a command is aimed at: the one running `plan`, or the
+/// only running session when no plan is given
+fn select_session(plan: Option<&Path>) -> Result<Option<LockInfo>> {
+    let sessions = lock::running_sessions();
+    match plan {
+        Some(plan_path) => {
+            let name = plan::plan_name(plan_path);
+            Ok(sessions.into_iter().find(|s| s.name() == name))
+        }
+        None if sessions.len() > 1 => Err(HydraError::AmbiguousSession(
+            sessions.iter().map(|s| s.name().to_string()).collect(),
+        )),
+        None => Ok(sessions.into_iter().next()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_command_round_trip() {
+        for command in [
+            ControlCommand::Stop,
+            ControlCommand::StopNow,
+            ControlCommand::Pause,
+            ControlCommand::Resume,
+            ControlCommand::Skip,
+        ] {
+            assert_eq!(ControlCommand::parse(command.as_str()), Some(command));
+        }
+        assert_eq!(
+            ControlCommand::parse("pause\n"),
+            Some(ControlCommand::Pause)
+        );
+        assert_eq!(ControlCommand::parse("reboot"), None);
+    }
+
+    #[test]
+    fn test_pause_resume_and_stop() {
+        let state = ControlState::default();
+        let should_stop = AtomicBool::new(false);
+
+        assert_eq!(
+            state.apply(ControlCommand::Pause, &should_stop),
+            "pausing before the next iteration"
+        );
+        assert!(state.is_paused());
+        assert_eq!(
+            state.apply(ControlCommand::Pause, &should_stop),
+            "already paused"
+        );
+        assert_eq!(
+            state.apply(ControlCommand::Resume, &should_stop),
+            "resuming"
+        );
+        assert!(!state.is_paused());
+
+        // Stop releases a pause so the loop can exit
+        state.apply(ControlCommand::Pause, &should_stop);
+        state.apply(ControlCommand::Stop, &should_stop);
+        assert!(state.stop_requested());
+        assert!(!state.is_paused());
+        assert!(!should_stop.load(Ordering::SeqCst));
+        state.wait_while_paused(&should_stop);
+    }
+
+    #[test]
+    fn test_stop_now_sets_stop_flag() {
+        let state = ControlState::default();
+        let should_stop = AtomicBool::new(false);
+        state.apply(ControlCommand::StopNow, &should_stop);
+        assert!(state.stop_requested());
+        assert!(should_stop.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_skip_without_running_iteration() {
+        let state = ControlState::default();
+        let should_stop = AtomicBool::new(false);
+        assert_eq!(
+            state.apply(ControlCommand::Skip, &should_stop),
+            "no iteration is running"
+        );
+        assert!(!state.take_skip());
+    }
+
+    #[test]
+    fn test_server_replies_over_socket() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("plan.sock");
+        let server =
+            ControlServer::start_at(path.clone(), Arc::new(AtomicBool::new(false))).unwrap();
+
+        assert_eq!(
+            send(&path, ControlCommand::Pause).unwrap(),
+            "pausing before the next iteration"
+        );
+        assert!(server.state().is_paused());
+
+        drop(server);
+        assert!(!path.exists());
+    }
+}
@@ -19,12 +19,88 @@ pub enum HydraError {
     #[error("Prompt file not found: {0}")]
     PromptNotFound(PathBuf),
 
+    /// Prompt file uses template syntax that can't be rendered
+    #[error("Invalid prompt template {path}: {message}")]
+    PromptTemplate { path: PathBuf, message: String },
+
+    /// A `hydra:include` directive points at a missing file
+    #[error("Prompt include not found: {path} (included from {included_from})")]
+    IncludeNotFound {
+        path: PathBuf,
+        included_from: PathBuf,
+    },
+
+    /// A file listed in `prompt_fragments` doesn't exist
+    #[error("Prompt fragment not found: {0} (listed in prompt_fragments in config.toml)")]
+    PromptFragmentNotFound(PathBuf),
+
+    /// Prompt includes form a cycle
+    #[error("Prompt includes form a cycle: {0}")]
+    IncludeCycle(String),
+
+    /// Iteration instructions file specified but doesn't exist
+    #[error("Iteration instructions file not found: {0}")]
+    InstructionsNotFound(PathBuf),
+
+    /// Custom iteration instructions spell out a stop signal
+    #[error(
+        "Iteration instructions {path} contain the literal signal {signal}\n\nDescribe stop sequences indirectly, as the built-in instructions do: harnesses that echo the prompt would otherwise end the iteration immediately."
+    )]
+    InstructionsContainSignal { path: PathBuf, signal: String },
+
     /// Plan file specified but doesn't exist
     #[error(
         "Plan file not found: {0}\n\nMake sure the implementation plan file exists at the specified path."
     )]
     PlanNotFound(PathBuf),
 
+    /// Plan lint found errors
+    #[error(
+        "Plan {path} has {errors} lint error(s)\n\nFix them (see `hydra lint {path}`) or pass --no-lint to run anyway."
+    )]
+    PlanInvalid { path: PathBuf, errors: usize },
+
+    /// `hydra plan new` got a reply that isn't a usable plan
+    #[error("Could not generate a plan from {spec}: {reason}")]
+    PlanGenerationFailed { spec: PathBuf, reason: String },
+
+    /// Another session already holds the lock for this plan
+    #[error(
+        "hydra is already running {plan} in this directory (PID {pid}, started {started_at})\n\nStop it with `hydra stop {plan}` or wait for it to finish."
+    )]
+    AlreadyRunning {
+        plan: String,
+        pid: u32,
+        started_at: String,
+    },
+
+    /// A control command without a plan while several sessions run
+    #[error(
+        "{} sessions are running ({}); pass the plan to choose one",
+        .0.len(),
+        .0.join(", ")
+    )]
+    AmbiguousSession(Vec<String>),
+
+    /// A control command aimed at a session running in a TUI tab, which has
+    /// no control socket
+    #[error(
+        "{0} runs in a `hydra tui` tab, which only takes commands from its keys (Ctrl+C stops it)"
+    )]
+    SessionInTui(String),
+
+    /// `--step-on` names no result or an unknown one
+    #[error("Invalid --step-on: {0} (expected timeout, no-signal)")]
+    InvalidStepOn(String),
+
+    /// `--reset-plan` given without a plan
+    #[error("--reset-plan requires a plan file argument")]
+    ResetPlanWithoutPlan,
+
+    /// `--queue` names something that isn't a directory of plans
+    #[error("Invalid plan queue {dir}: {reason}")]
+    InvalidQueue { dir: PathBuf, reason: String },
+
     /// Config file parse error
     #[error("Failed to parse config file {path}: {source}")]
     ConfigParse {
@@ -60,7 +136,6 @@ pub enum HydraError {
     SpawnFailed(#[source] std::io::Error),
 
     /// Subprocess exited with error
-    #[allow(dead_code)]
     #[error("Subprocess exited with code {0}")]
     SubprocessFailed(i32),
 }
@@ -79,7 +154,21 @@ impl HydraError {
             // Exit 2: Errors
             HydraError::NoPromptFound => EXIT_ERROR,
             HydraError::PromptNotFound(_) => EXIT_ERROR,
+            HydraError::PromptTemplate { .. } => EXIT_ERROR,
+            HydraError::IncludeNotFound { .. } => EXIT_ERROR,
+            HydraError::PromptFragmentNotFound(_) => EXIT_ERROR,
+            HydraError::IncludeCycle(_) => EXIT_ERROR,
+            HydraError::InstructionsNotFound(_) => EXIT_ERROR,
+            HydraError::InstructionsContainSignal { .. } => EXIT_ERROR,
             HydraError::PlanNotFound(_) => EXIT_ERROR,
+            HydraError::PlanInvalid { .. } => EXIT_ERROR,
+            HydraError::PlanGenerationFailed { .. } => EXIT_ERROR,
+            HydraError::AlreadyRunning { .. } => EXIT_ERROR,
+            HydraError::AmbiguousSession(_) => EXIT_ERROR,
+            HydraError::SessionInTui(_) => EXIT_ERROR,
+            HydraError::InvalidStepOn(_) => EXIT_ERROR,
+            HydraError::ResetPlanWithoutPlan => EXIT_ERROR,
+            HydraError::InvalidQueue { .. } => EXIT_ERROR,
             HydraError::ConfigParse { .. } => EXIT_ERROR,
             HydraError::Io { .. } => EXIT_ERROR,
             HydraError::SpawnFailed(_) => EXIT_ERROR,
@@ -134,6 +223,94 @@ mod tests {
 
         let err = HydraError::MaxIterations(5);
         assert!(err.to_string().contains("5"));
+
+        let err = HydraError::AlreadyRunning {
+            plan: "my-plan".to_string(),
+            pid: 4242,
+            started_at: "2026-01-01 10:00:00".to_string(),
+        };
+        assert!(err.to_string().contains("PID 4242"));
+        assert!(err.to_string().contains("hydra stop my-plan"));
+        assert_eq!(err.exit_code(), EXIT_ERROR);
+
+        let err = HydraError::AmbiguousSession(vec!["api".to_string(), "docs".to_string()]);
+        assert_eq!(
+            err.to_string(),
+            "2 sessions are running (api, docs); pass the plan to choose one"
+        );
+        assert_eq!(err.exit_code(), EXIT_ERROR);
+
+        let err = HydraError::SessionInTui("api".to_string());
+        assert_eq!(
+            err.to_string(),
+            "api runs in a `hydra tui` tab, which only takes commands from its keys (Ctrl+C stops it)"
+        );
+        assert_eq!(err.exit_code(), EXIT_ERROR);
+
+        let err = HydraError::InvalidStepOn("unknown value 'done'".to_string());
+        assert!(err.to_string().contains("--step-on: unknown value 'done'"));
+        assert_eq!(err.exit_code(), EXIT_ERROR);
+
+        let err = HydraError::ResetPlanWithoutPlan;
+        assert!(err.to_string().contains("requires a plan file"));
+        assert_eq!(err.exit_code(), EXIT_ERROR);
+
+        let err = HydraError::InvalidQueue {
+            dir: PathBuf::from("plans"),
+            reason: "no plans (*.md) found".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Invalid plan queue plans: no plans (*.md) found"
+        );
+        assert_eq!(err.exit_code(), EXIT_ERROR);
+
+        let err = HydraError::PromptTemplate {
+            path: PathBuf::from(".hydra/prompt.md"),
+            message: "line 3: unknown variable 'plan'".to_string(),
+        };
+        assert!(err.to_string().contains(".hydra/prompt.md: line 3"));
+        assert_eq!(err.exit_code(), EXIT_ERROR);
+
+        let err = HydraError::IncludeNotFound {
+            path: PathBuf::from("../shared/standards.md"),
+            included_from: PathBuf::from(".hydra/prompt.md"),
+        };
+        assert!(err.to_string().contains("(included from .hydra/prompt.md)"));
+        assert_eq!(err.exit_code(), EXIT_ERROR);
+
+        let err = HydraError::IncludeCycle("a.md -> b.md -> a.md".to_string());
+        assert!(err.to_string().ends_with("a.md -> b.md -> a.md"));
+        assert_eq!(err.exit_code(), EXIT_ERROR);
+
+        let err = HydraError::InstructionsContainSignal {
+            path: PathBuf::from(".hydra/iteration-instructions.md"),
+            signal: "###TASK_COMPLETE###".to_string(),
+        };
+        assert!(
+            err.to_string()
+                .contains("contain the literal signal ###TASK_COMPLETE###")
+        );
+        assert_eq!(err.exit_code(), EXIT_ERROR);
+
+        let err = HydraError::PlanInvalid {
+            path: PathBuf::from("plans/api.md"),
+            errors: 2,
+        };
+        assert!(err.to_string().contains("2 lint error(s)"));
+        assert!(err.to_string().contains("hydra lint plans/api.md"));
+        assert_eq!(err.exit_code(), EXIT_ERROR);
+
+        let err = HydraError::PlanGenerationFailed {
+            spec: PathBuf::from("specs/auth.md"),
+            reason: "the reply contains no checkbox tasks".to_string(),
+        };
+        assert!(err.to_string().contains("from specs/auth.md: the reply"));
+        assert_eq!(err.exit_code(), EXIT_ERROR);
+
+        let err = HydraError::SubprocessFailed(137);
+        assert!(err.to_string().contains("137"));
+        assert_eq!(err.exit_code(), EXIT_ERROR);
     }
 
     #[test]
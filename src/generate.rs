@@ -0,0 +1,177 @@
+//! Plan generation from a spec (`hydra plan new`)
+//!
+//! Runs the configured harness headlessly with a plan-authoring prompt and
+//! the spec, then checks the reply with the plan parser. The plan is written
+//! only if it has at least one task. The prompt template can be overridden
+//! at `~/.hydra/plan-template.md`, like the skill templates.
+
+use crate::config::Config;
+use crate::error::{HydraError, Result};
+use crate::harness::Harness;
+use crate::lint;
+use crate::plan::{PLANS_DIR, Plan, plan_name};
+use crate::skill::prompt_yes_no;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Embedded default plan-authoring prompt
+const PLAN_TEMPLATE: &str = include_str!("../templates/plan-new.md");
+
+/// Load the plan-authoring prompt: the user override at
+/// ~/.hydra/plan-template.md, falling back to the embedded default
+pub fn load_plan_template() -> String {
+    fs::read_to_string(Config::global_plan_template_path())
+        .unwrap_or_else(|_| PLAN_TEMPLATE.to_string())
+}
+
+/// Entry point for `hydra plan new <spec> [--out <plan>]`
+pub fn plan_new_command(spec: &Path, out: Option<&Path>, harness: Harness) -> Result<()> {
+    let spec_content = fs::read_to_string(spec)
+        .map_err(|e| HydraError::io(format!("reading spec {}", spec.display()), e))?;
+    let out = out
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| default_plan_path(spec));
+
+    if out.exists() && !prompt_yes_no(&format!("{} already exists. Overwrite?", out.display()))? {
+        println!("[hydra] Plan not written");
+        return Ok(());
+    }
+
+    println!(
+        "[hydra] Generating a plan from {} with {}...",
+        spec.display(),
+        harness
+    );
+    let prompt = build_prompt(&load_plan_template(), spec, &spec_content, &out);
+    let output = run_harness(harness, &prompt)?;
+    let plan = extract_plan(&output);
+
+    let tasks = Plan::parse(&plan).tasks.len();
+    if tasks == 0 {
+        return Err(HydraError::PlanGenerationFailed {
+            spec: spec.to_path_buf(),
+            reason: "the reply contains no checkbox tasks".to_string(),
+        });
+    }
+
+    if let Some(dir) = out.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)
+            .map_err(|e| HydraError::io(format!("creating {}", dir.display()), e))?;
+    }
+    fs::write(&out, format!("{}\n", plan.trim_end()))
+        .map_err(|e| HydraError::io(format!("writing plan {}", out.display()), e))?;
+    println!("[hydra] Wrote {} ({} tasks)", out.display(), tasks);
+
+    // Generated links may point at headings that don't exist; show the
+    // findings now rather than when the plan is first run
+    let issues = lint::lint_plan(&out)?;
+    if !issues.is_empty() {
+        lint::print_issues(&out, &issues);
+        println!(
+            "[hydra] Review the findings above (`hydra lint {}`)",
+            out.display()
+        );
+    }
+    Ok(())
+}
+
+/// plans/<spec name>.md
+fn default_plan_path(spec: &Path) -> PathBuf {
+    Path::new(PLANS_DIR).join(format!("{}.md", plan_name(spec)))
+}
+
+/// Template followed by where the plan goes and the spec itself
+fn build_prompt(template: &str, spec: &Path, spec_content: &str, out: &Path) -> String {
+    format!(
+        "{}\n\n## Plan File\n\nThe plan will be saved at: {} (make spec links relative to it)\n\n\
+         ## Spec\n\nSpec file: {}\n\n{}",
+        template.trim_end(),
+        out.display(),
+        spec.display(),
+        spec_content.trim_end()
+    )
+}
+
+/// The plan from the harness reply, without an enclosing code fence
+fn extract_plan(output: &str) -> String {
+    let trimmed = output.trim();
+    let lines: Vec<&str> = trimmed.lines().collect();
+    if lines.len() > 1 {
+        let (first, last) = (lines[0], lines[lines.len() - 1].trim());
+        for fence in ["```", "~~~"] {
+            if first.starts_with(fence) && last == fence {
+                return lines[1..lines.len() - 1].join("\n");
+            }
+        }
+    }
+    trimmed.to_string()
+}
+
+/// Run the harness in print mode with `prompt` on stdin and return stdout
+fn run_harness(harness: Harness, prompt: &str) -> Result<String> {
+    let mut cmd = Command::new(harness.command());
+    cmd.args(harness.review_headless_args());
+    for var in harness.env_removals() {
+        cmd.env_remove(var);
+    }
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| HydraError::io(format!("spawning {} -p", harness.command()), e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(prompt.as_bytes());
+    }
+
+    let mut output = String::new();
+    if let Some(mut stdout) = child.stdout.take() {
+        let _ = stdout.read_to_string(&mut output);
+    }
+
+    let status = child
+        .wait()
+        .map_err(|e| HydraError::io(format!("waiting for {}", harness.command()), e))?;
+    if !status.success() {
+        return Err(HydraError::SubprocessFailed(status.code().unwrap_or(-1)));
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_plan() {
+        let plan = "# Plan\n\n- [ ] Add parser";
+        assert_eq!(extract_plan(&format!("\n{}\n\n", plan)), plan);
+        assert_eq!(extract_plan(&format!("```markdown\n{}\n```", plan)), plan);
+        // A fence that only opens the reply is left alone
+        assert_eq!(
+            extract_plan("```\ncode\n```\n- [ ] Task"),
+            "```\ncode\n```\n- [ ] Task"
+        );
+    }
+
+    #[test]
+    fn test_build_prompt_and_default_path() {
+        let prompt = build_prompt(
+            "Write a plan.\n",
+            Path::new("specs/auth.md"),
+            "# Auth\n",
+            Path::new("plans/auth.md"),
+        );
+        assert!(prompt.starts_with("Write a plan.\n\n## Plan File"));
+        assert!(prompt.contains("saved at: plans/auth.md"));
+        assert!(prompt.ends_with("Spec file: specs/auth.md\n\n# Auth"));
+        assert_eq!(
+            default_plan_path(Path::new("specs/auth.md")),
+            PathBuf::from("plans/auth.md")
+        );
+        assert!(Plan::parse(PLAN_TEMPLATE).tasks.is_empty());
+    }
+}
@@ -18,7 +18,8 @@ use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
 /// Which coding agent harness to drive.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Harness {
     /// Claude Code (`claude` CLI). Default.
     Claude,
@@ -27,6 +28,9 @@ pub enum Harness {
 }
 
 impl Harness {
+    /// Every supported harness, in the order the TUI picker offers them.
+    pub const ALL: [Harness; 2] = [Harness::Claude, Harness::Pi];
+
     /// Parse a harness name. Accepts `claude` or `pi` (case-insensitive).
     pub fn parse(name: &str) -> Result<Self> {
         match name.trim().to_ascii_lowercase().as_str() {
@@ -134,6 +138,23 @@ impl Harness {
         }
     }
 
+    /// Whether the harness's TUI shows the submitted prompt. Claude gets a
+    /// pointer to the prompt file, but pi's `@<file>` puts the whole file on
+    /// screen, so a stop signal quoted in a user prompt or plan would be
+    /// seen before the agent has done anything.
+    pub fn echoes_prompt(self) -> bool {
+        match self {
+            Harness::Claude => false,
+            Harness::Pi => true,
+        }
+    }
+
+    /// The harness after this one in [`Harness::ALL`], wrapping around.
+    pub fn next(self) -> Harness {
+        let idx = Self::ALL.iter().position(|h| *h == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
     /// Environment variables that must be removed before spawning the
     /// harness. Returned as a list of names to `env_remove`.
     pub fn env_removals(self) -> &'static [&'static str] {
@@ -305,6 +326,14 @@ mod tests {
         assert!(!args.iter().any(|a| a == "stream-json"));
     }
 
+    #[test]
+    fn test_echoes_prompt_and_next() {
+        assert!(!Harness::Claude.echoes_prompt());
+        assert!(Harness::Pi.echoes_prompt());
+        assert_eq!(Harness::Claude.next(), Harness::Pi);
+        assert_eq!(Harness::Pi.next(), Harness::Claude);
+    }
+
     #[test]
     fn test_claude_env_removal_contains_claudecode() {
         assert!(Harness::Claude.env_removals().contains(&"CLAUDECODE"));
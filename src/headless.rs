@@ -1,89 +1,117 @@
-use crate::config::Config;
 use crate::error::{HydraError, Result};
 use crate::harness::Harness;
-use crate::prompt::ResolvedPrompt;
-use crate::runner::{IterationResult, RunResult};
+use crate::question;
+use crate::runner::{IterationResult, SessionLogger};
+use crate::session_loop::{Mode, SessionLoop};
 use crate::signal;
 use chrono::Local;
-use std::fs::{self, File, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
-use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
-
-/// Iteration instructions prepended to the prompt
-const ITERATION_INSTRUCTIONS: &str = include_str!("../templates/iteration-instructions.md");
-
-/// Session logger for headless mode (same format as PTY mode)
-struct SessionLogger {
-    path: PathBuf,
-    file: File,
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Number of trailing stderr lines kept for the console summary
+const STDERR_TAIL_LINES: usize = 20;
+
+/// How long to wait for the stderr reader to flush after the harness exits.
+/// Grandchildren can inherit the pipe and keep it open, so never block on it.
+const STDERR_DRAIN_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Headless additions to the session log
+impl SessionLogger {
+    /// Write a line captured from the harness's stderr, tagged so it can be
+    /// told apart from assistant text
+    fn log_stderr(&mut self, line: &str) -> Result<()> {
+        self.log(&format!("[stderr] {}", line))
+    }
+
+    /// Record how the harness process exited
+    fn log_exit_status(&mut self, iteration: u32, status: &ExitStatus) -> Result<()> {
+        self.log(&format!(
+            "ITERATION {} PROCESS EXIT: {}",
+            iteration,
+            describe_exit_status(status)
+        ))
+    }
 }
 
-impl SessionLogger {
-    fn new(plan_name: Option<&str>) -> Result<Self> {
-        let logs_dir = Config::logs_dir();
-        if !logs_dir.exists() {
-            fs::create_dir_all(&logs_dir).map_err(|e| {
-                HydraError::io(format!("creating logs directory {}", logs_dir.display()), e)
-            })?;
+/// Human-readable description of a harness exit status
+fn describe_exit_status(status: &ExitStatus) -> String {
+    match (status.code(), status.signal()) {
+        (Some(code), _) => format!("code {}", code),
+        (None, Some(sig)) => format!("killed by signal {}", sig),
+        (None, None) => "unknown status".to_string(),
+    }
+}
+
+/// Captures a harness's stderr on a background thread.
+///
+/// Lines are handed back to the runner through a channel so they can be
+/// written to the session log from the main thread, and the last
+/// `STDERR_TAIL_LINES` are kept for the console summary.
+struct StderrCapture {
+    rx: Receiver<String>,
+    tail: VecDeque<String>,
+}
+
+impl StderrCapture {
+    /// Start reading `stderr` line by line on a background thread
+    fn spawn(stderr: impl Read + Send + 'static) -> Self {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stderr).lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            rx,
+            tail: VecDeque::with_capacity(STDERR_TAIL_LINES),
         }
+    }
 
-        let timestamp = Local::now().format("%Y%m%d-%H%M%S");
-        let filename = match plan_name {
-            Some(name) => format!("{}-{}.log", name, timestamp),
-            None => format!("hydra-{}.log", timestamp),
-        };
-        let path = logs_dir.join(filename);
-
-        let file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(&path)
-            .map_err(|e| HydraError::io(format!("creating log file {}", path.display()), e))?;
-
-        Ok(Self { path, file })
-    }
-
-    fn log(&mut self, message: &str) -> Result<()> {
-        let timestamp = Local::now().format("%H:%M:%S");
-        writeln!(self.file, "[{}] {}", timestamp, message)
-            .map_err(|e| HydraError::io("writing to log file", e))?;
-        self.file
-            .flush()
-            .map_err(|e| HydraError::io("flushing log file", e))?;
-        Ok(())
-    }
-
-    fn append_content(&mut self, content: &str) -> Result<()> {
-        write!(self.file, "{}", content)
-            .map_err(|e| HydraError::io("writing content to log file", e))?;
-        self.file
-            .flush()
-            .map_err(|e| HydraError::io("flushing log file", e))?;
-        Ok(())
-    }
-
-    fn log_iteration_start(&mut self, iteration: u32, max: u32) -> Result<()> {
-        let separator = "=".repeat(80);
-        self.append_content(&format!("\n{}\n", separator))?;
-        self.log(&format!("ITERATION {}/{} START", iteration, max))?;
-        self.append_content(&format!("{}\n\n", separator))?;
-        Ok(())
-    }
-
-    fn log_iteration_end(&mut self, iteration: u32, result: &IterationResult) -> Result<()> {
-        let result_str = match result {
-            IterationResult::TaskComplete => "TASK_COMPLETE",
-            IterationResult::AllComplete => "ALL_COMPLETE",
-            IterationResult::NoSignal => "NO_SIGNAL",
-            IterationResult::Terminated => "TERMINATED",
-            IterationResult::Timeout => "TIMEOUT",
-        };
-        self.log(&format!("ITERATION {} END: {}", iteration, result_str))?;
-        Ok(())
+    /// Return the lines received since the last call without blocking
+    fn drain(&mut self) -> Vec<String> {
+        let lines: Vec<String> = self.rx.try_iter().collect();
+        self.remember(&lines);
+        lines
+    }
+
+    /// Return the remaining lines once the harness has exited, waiting at
+    /// most `STDERR_DRAIN_TIMEOUT` in total for the pipe to close. A reader
+    /// still blocked after that is left behind; it exits on its next line
+    /// once the receiver is gone.
+    fn finish(&mut self) -> Vec<String> {
+        let deadline = Instant::now() + STDERR_DRAIN_TIMEOUT;
+        let mut lines = Vec::new();
+        while let Some(left) = deadline.checked_duration_since(Instant::now())
+            && let Ok(line) = self.rx.recv_timeout(left)
+        {
+            lines.push(line);
+        }
+        self.remember(&lines);
+        lines
+    }
+
+    /// Push lines into the bounded tail buffer
+    fn remember(&mut self, lines: &[String]) {
+        for line in lines {
+            if self.tail.len() == STDERR_TAIL_LINES {
+                self.tail.pop_front();
+            }
+            self.tail.push_back(line.clone());
+        }
+    }
+
+    /// The most recent stderr lines, oldest first
+    fn tail(&self) -> impl Iterator<Item = &String> {
+        self.tail.iter()
     }
 }
 
@@ -103,12 +131,15 @@ trait HarnessStreamParser {
 }
 
 /// Default stop-signal scanner used by both parsers. `ALL_TASKS_COMPLETE`
-/// takes priority when both signals appear in the same accumulator.
+/// takes priority when both signals appear in the same accumulator, and
+/// either completion signal wins over a question.
 fn scan_stop_signal(accumulator: &str) -> Option<IterationResult> {
     if accumulator.contains("###ALL_TASKS_COMPLETE###") {
         Some(IterationResult::AllComplete)
     } else if accumulator.contains("###TASK_COMPLETE###") {
         Some(IterationResult::TaskComplete)
+    } else if accumulator.contains(question::QUESTION_SIGNAL) {
+        Some(IterationResult::Question)
     } else {
         None
     }
@@ -217,108 +248,65 @@ impl HarnessStreamParser for PiStreamJsonParser {
     }
 }
 
+/// Runs each iteration in the harness's print/pipe mode instead of a PTY
+pub struct Headless;
+
 /// Headless runner that invokes a coding-agent harness in print/pipe mode
 /// instead of via a PTY.
-pub struct HeadlessRunner {
-    config: Config,
-    prompt: ResolvedPrompt,
-    should_stop: Arc<AtomicBool>,
-    logger: Option<SessionLogger>,
-    plan_name: Option<String>,
-    scratchpad_path: Option<PathBuf>,
-    harness: Harness,
-}
-
-impl HeadlessRunner {
-    pub fn new(
-        config: Config,
-        prompt: ResolvedPrompt,
-        plan_name: Option<String>,
-        scratchpad_path: Option<PathBuf>,
-        harness: Harness,
-    ) -> Self {
-        let logger = match SessionLogger::new(plan_name.as_deref()) {
-            Ok(l) => Some(l),
-            Err(e) => {
-                eprintln!("[hydra] Warning: Could not create session log: {}", e);
-                None
-            }
-        };
+pub type HeadlessRunner = SessionLoop<Headless>;
 
-        Self {
-            config,
-            prompt,
-            should_stop: Arc::new(AtomicBool::new(false)),
-            logger,
-            plan_name,
-            scratchpad_path,
-            harness,
+impl Mode for Headless {
+    fn banner(session: &mut HeadlessRunner) {
+        println!("[hydra] Starting headless mode");
+        println!("[hydra] Using prompt: {}", session.prompt.path.display());
+        if let Some(ref logger) = session.logger {
+            println!("[hydra] Session log: {}", logger.path.display());
         }
-    }
-
-    /// Get a clone of the stop flag for signal handlers
-    pub fn stop_flag(&self) -> Arc<AtomicBool> {
-        Arc::clone(&self.should_stop)
-    }
-
-    /// Create the combined prompt string (iteration instructions + user prompt)
-    fn create_combined_prompt(&self) -> String {
-        format!("{}\n{}", ITERATION_INSTRUCTIONS, self.prompt.content)
-    }
 
-    /// Append a timeout note to the scratchpad so the next iteration knows to check logs
-    fn append_timeout_to_scratchpad(&self, iteration: u32) {
-        let Some(ref scratchpad_path) = self.scratchpad_path else {
-            return;
-        };
-        let log_path = self
-            .logger
-            .as_ref()
-            .map(|l| l.path.display().to_string())
-            .unwrap_or_else(|| "the session log".to_string());
-        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
-        let note = format!(
-            "\n## ⚠ Timeout — Iteration {} ({})\n\n\
-             The previous iteration (#{}) was terminated due to timeout ({}s limit).\n\
-             **Next iteration**: Check the logs at `{}` to understand what was in progress \
-             and resume or retry the interrupted work.\n",
-            iteration, timestamp, iteration, self.config.timeout_seconds, log_path,
-        );
-        if let Err(e) = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(scratchpad_path)
-            .and_then(|mut f| f.write_all(note.as_bytes()))
-        {
-            eprintln!(
-                "[hydra] Warning: Could not write timeout note to scratchpad: {}",
-                e
-            );
+        let max = session.config.max_iterations;
+        session.log(&format!(
+            "Session started (headless) - max iterations: {}",
+            max
+        ));
+    }
+
+    fn header(iteration: u32, max: u32, label: Option<&str>) {
+        match label {
+            None => println!(
+                "[hydra] Iteration {}/{}... [{}]",
+                iteration,
+                max,
+                Local::now().format("%Y-%m-%d %H:%M:%S")
+            ),
+            Some(label) => println!(
+                "[hydra] Iteration {}/{} ({})... [{}]",
+                iteration,
+                max,
+                label,
+                Local::now().format("%Y-%m-%d %H:%M:%S")
+            ),
         }
     }
 
-    /// Check if the stop file exists
-    fn check_stop_file(&self) -> bool {
-        let stop_path = PathBuf::from(&self.config.stop_file);
-        if stop_path.exists() {
-            let _ = fs::remove_file(&stop_path);
-            true
-        } else {
-            false
-        }
+    fn run_iteration(
+        session: &mut HeadlessRunner,
+        iteration: u32,
+        timeout_seconds: u64,
+        prompt: &str,
+    ) -> Result<IterationResult> {
+        session.run_headless(iteration, timeout_seconds, prompt)
     }
+}
 
-    /// Run a single headless iteration
-    fn run_iteration(&mut self, iteration: u32) -> Result<IterationResult> {
-        println!(
-            "[hydra] Iteration {}/{}... [{}]",
-            iteration,
-            self.config.max_iterations,
-            Local::now().format("%Y-%m-%d %H:%M:%S")
-        );
-
-        let combined_prompt = self.create_combined_prompt();
-
+impl HeadlessRunner {
+    /// Run a single headless iteration, writing the prompt to the
+    /// harness's stdin
+    fn run_headless(
+        &mut self,
+        iteration: u32,
+        timeout_secs: u64,
+        prompt: &str,
+    ) -> Result<IterationResult> {
         // Spawn the configured harness in print/pipe mode with stream-json
         // output. The Harness abstraction provides the command name, the
         // argument list, and any env vars that must be cleared before
@@ -328,20 +316,27 @@ impl HeadlessRunner {
         for var in self.harness.env_removals() {
             cmd.env_remove(var);
         }
+        // Own process group, so stop requests and timeouts can killpg() the
+        // harness and everything it spawned
+        cmd.process_group(0);
         let mut child = cmd
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::null())
+            .stderr(Stdio::piped())
             .spawn()
             .map_err(|e| HydraError::io(format!("spawning {} -p", self.harness.command()), e))?;
 
+        // Capture stderr on its own thread so crashes, auth errors and
+        // stack traces end up in the session log
+        let mut stderr = child.stderr.take().map(StderrCapture::spawn);
+
         // Track child PID for signal handling
         let child_id = child.id();
         signal::set_child_pid(child_id);
 
         // Write prompt to stdin, then close it
         if let Some(mut stdin) = child.stdin.take() {
-            stdin.write_all(combined_prompt.as_bytes()).map_err(|e| {
+            stdin.write_all(prompt.as_bytes()).map_err(|e| {
                 HydraError::io(
                     format!("writing prompt to {} stdin", self.harness.command()),
                     e,
@@ -368,7 +363,6 @@ impl HeadlessRunner {
         let mut result = IterationResult::NoSignal;
 
         // Set up timeout
-        let timeout_secs = self.config.timeout_seconds;
         let start_time = std::time::Instant::now();
 
         for line in reader.lines() {
@@ -412,6 +406,11 @@ impl HeadlessRunner {
                 }
             }
 
+            // Interleave any stderr received so far
+            if let Some(ref mut capture) = stderr {
+                self.log_stderr_lines(&capture.drain());
+            }
+
             // Check for stop signals after each line
             if let Some(signal_result) = parser.check_stop_signal() {
                 result = signal_result;
@@ -419,10 +418,26 @@ impl HeadlessRunner {
             }
         }
 
+        // A stop request may have killed the harness before it printed
+        // anything, in which case the loop above ends on EOF
+        if result == IterationResult::NoSignal && self.should_stop.load(Ordering::SeqCst) {
+            result = IterationResult::Terminated;
+        }
+
         // Wait for child to exit
-        let _ = child.wait();
+        let status = child.wait().ok();
         signal::clear_child_pid();
 
+        if let Some(ref mut capture) = stderr {
+            let remaining = capture.finish();
+            self.log_stderr_lines(&remaining);
+        }
+        if let Some(ref status) = status
+            && let Some(ref mut logger) = self.logger
+        {
+            let _ = logger.log_exit_status(iteration, status);
+        }
+
         // Print status based on result
         match &result {
             IterationResult::TaskComplete => {
@@ -437,125 +452,67 @@ impl HeadlessRunner {
             IterationResult::Terminated => {
                 println!("[hydra] Iteration terminated");
             }
+            IterationResult::Question => {
+                println!("[hydra] QUESTION detected");
+            }
             IterationResult::NoSignal => {
                 println!("[hydra] No stop signal detected");
+                self.report_unsignalled_exit(iteration, status.as_ref(), stderr.as_ref());
             }
         }
 
         Ok(result)
     }
 
-    /// Run the main headless loop
-    pub fn run(&mut self) -> Result<RunResult> {
-        let max = self.config.max_iterations;
-
-        println!("[hydra] Starting headless mode");
-        println!("[hydra] Using prompt: {}", self.prompt.path.display());
-        if let Some(ref logger) = self.logger {
-            println!("[hydra] Session log: {}", logger.path.display());
-        }
-
-        // Log session start
+    /// Write captured stderr lines to the session log
+    fn log_stderr_lines(&mut self, lines: &[String]) {
         if let Some(ref mut logger) = self.logger {
-            let _ = logger.log(&format!(
-                "Session started (headless) - max iterations: {}",
-                max
-            ));
-            let _ = logger.log(&format!("Prompt file: {}", self.prompt.path.display()));
-            if let Some(ref plan) = self.plan_name {
-                let _ = logger.log(&format!("Plan: {}", plan));
+            for line in lines {
+                let _ = logger.log_stderr(line);
             }
         }
+    }
 
-        for iteration in 1..=max {
-            // Check for stop file
-            if self.check_stop_file() {
-                println!("[hydra] Stop file detected, exiting gracefully");
-                if let Some(ref mut logger) = self.logger {
-                    let _ = logger.log("Session ended: stop file detected");
-                }
-                return Ok(RunResult::Stopped {
-                    iterations: iteration - 1,
-                });
-            }
-
-            // Check for graceful stop request
-            if self.should_stop.load(Ordering::SeqCst) {
-                println!("[hydra] Graceful shutdown complete");
-                if let Some(ref mut logger) = self.logger {
-                    let _ = logger.log("Session ended: graceful shutdown");
-                }
-                return Ok(RunResult::Stopped {
-                    iterations: iteration - 1,
-                });
-            }
-
-            // Log iteration start
-            if let Some(ref mut logger) = self.logger {
-                let _ = logger.log_iteration_start(iteration, max);
-            }
-
-            // Run the iteration
-            let result = self.run_iteration(iteration)?;
-
-            // Log iteration end
+    /// Explain why an iteration ended without a stop signal: report a
+    /// non-zero exit as `SubprocessFailed` and show the tail of stderr
+    fn report_unsignalled_exit(
+        &mut self,
+        iteration: u32,
+        status: Option<&ExitStatus>,
+        stderr: Option<&StderrCapture>,
+    ) {
+        if let Some(status) = status
+            && !status.success()
+        {
+            let err = HydraError::SubprocessFailed(status.code().unwrap_or(-1));
+            eprintln!("[hydra] {} ({})", err, describe_exit_status(status));
             if let Some(ref mut logger) = self.logger {
-                let _ = logger.log_iteration_end(iteration, &result);
-            }
-
-            match result {
-                IterationResult::AllComplete => {
-                    println!(
-                        "[hydra] All tasks complete! Total iterations: {}",
-                        iteration
-                    );
-                    if let Some(ref mut logger) = self.logger {
-                        let _ = logger.log(&format!(
-                            "Session ended: all tasks complete after {} iterations",
-                            iteration
-                        ));
-                    }
-                    return Ok(RunResult::AllTasksComplete {
-                        iterations: iteration,
-                    });
-                }
-                IterationResult::Terminated => {
-                    println!("[hydra] Graceful shutdown complete");
-                    if let Some(ref mut logger) = self.logger {
-                        let _ = logger.log("Session ended: terminated");
-                    }
-                    return Ok(RunResult::Stopped {
-                        iterations: iteration,
-                    });
-                }
-                IterationResult::Timeout => {
-                    self.append_timeout_to_scratchpad(iteration);
-                    self.should_stop.store(false, Ordering::SeqCst);
-                    if self.config.verbose {
-                        eprintln!("[hydra:debug] Timeout recorded in scratchpad, continuing");
-                    }
-                }
-                IterationResult::TaskComplete | IterationResult::NoSignal => {
-                    // Reset should_stop flag (may have been set during teardown)
-                    self.should_stop.store(false, Ordering::SeqCst);
-                    if self.config.verbose {
-                        eprintln!("[hydra:debug] Continuing to next iteration");
-                    }
-                }
+                let _ = logger.log(&format!("ITERATION {} FAILED: {}", iteration, err));
             }
         }
 
-        println!("[hydra] Max iterations reached");
-        if let Some(ref mut logger) = self.logger {
-            let _ = logger.log(&format!("Session ended: max iterations ({}) reached", max));
+        let Some(capture) = stderr else {
+            return;
+        };
+        let mut tail = capture.tail().peekable();
+        if tail.peek().is_none() {
+            return;
+        }
+        eprintln!(
+            "[hydra] Last {} stderr line(s) from {}:",
+            capture.tail.len(),
+            self.harness.command()
+        );
+        for line in tail {
+            eprintln!("  {}", line);
         }
-        Ok(RunResult::MaxIterations { iterations: max })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
 
     #[test]
     fn test_stream_json_parser_assistant_text() {
@@ -635,6 +592,16 @@ mod tests {
             parser.check_stop_signal(),
             Some(IterationResult::AllComplete)
         );
+
+        parser.text_accumulator = "Blocked on a decision\n###QUESTION###\n".to_string();
+        assert_eq!(parser.check_stop_signal(), Some(IterationResult::Question));
+
+        // A completion signal wins over a question
+        parser.text_accumulator = "###QUESTION###\n###TASK_COMPLETE###\n".to_string();
+        assert_eq!(
+            parser.check_stop_signal(),
+            Some(IterationResult::TaskComplete)
+        );
     }
 
     #[test]
@@ -792,6 +759,80 @@ mod tests {
         assert_eq!(parser.text_accumulator, "Reading file... done.");
     }
 
+    #[test]
+    fn test_stderr_capture_collects_lines_and_tail() {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg("for i in $(seq 1 25); do echo \"err $i\" >&2; done")
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut capture = StderrCapture::spawn(child.stderr.take().unwrap());
+        let status = child.wait().unwrap();
+        assert!(status.success());
+
+        let mut lines = capture.drain();
+        lines.extend(capture.finish());
+        assert_eq!(lines.len(), 25);
+        assert_eq!(lines[0], "err 1");
+
+        // Only the last STDERR_TAIL_LINES are kept for the console summary
+        let tail: Vec<&String> = capture.tail().collect();
+        assert_eq!(tail.len(), STDERR_TAIL_LINES);
+        assert_eq!(tail[0], "err 6");
+        assert_eq!(tail[STDERR_TAIL_LINES - 1], "err 25");
+    }
+
+    #[test]
+    fn test_stderr_capture_finish_is_bounded() {
+        // A grandchild holding the pipe open and writing forever
+        struct Chatty;
+        impl Read for Chatty {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                thread::sleep(Duration::from_millis(10));
+                buf[..2].copy_from_slice(b"x\n");
+                Ok(2)
+            }
+        }
+        let mut capture = StderrCapture::spawn(Chatty);
+        let started = Instant::now();
+        let lines = capture.finish();
+        assert!(started.elapsed() < STDERR_DRAIN_TIMEOUT * 3);
+        assert!(!lines.is_empty());
+    }
+
+    #[test]
+    fn test_describe_exit_status() {
+        let ok = Command::new("sh").arg("-c").arg("exit 0").status().unwrap();
+        assert_eq!(describe_exit_status(&ok), "code 0");
+        let failed = Command::new("sh").arg("-c").arg("exit 3").status().unwrap();
+        assert_eq!(describe_exit_status(&failed), "code 3");
+        let killed = Command::new("sh")
+            .arg("-c")
+            .arg("kill -9 $$")
+            .status()
+            .unwrap();
+        assert_eq!(describe_exit_status(&killed), "killed by signal 9");
+    }
+
+    #[test]
+    fn test_session_logger_stderr_and_exit_status() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let log_path = temp_dir.path().join("test.log");
+        let mut logger = SessionLogger {
+            path: log_path.clone(),
+            file: fs::File::create(&log_path).unwrap(),
+        };
+
+        let status = Command::new("sh").arg("-c").arg("exit 2").status().unwrap();
+        logger.log_stderr("Error: not authenticated").unwrap();
+        logger.log_exit_status(4, &status).unwrap();
+
+        let content = fs::read_to_string(&log_path).unwrap();
+        assert!(content.contains("] [stderr] Error: not authenticated"));
+        assert!(content.contains("ITERATION 4 PROCESS EXIT: code 2"));
+    }
+
     /// Exercise the same `Box<dyn HarnessStreamParser>` dispatch path that
     /// `HeadlessRunner::run_iteration` uses. This is a lightweight
     /// integration test that verifies the runner's per-harness parser
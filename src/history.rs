@@ -0,0 +1,385 @@
+//! Historical iteration durations and adaptive timeouts
+//!
+//! Every finished iteration is recorded in `.hydra/history/<plan>.json`.
+//! Before an iteration starts, its timeout is picked in this order:
+//!
+//! 1. A `(timeout: 90m)` hint on the next unchecked plan task
+//! 2. p95 of past successful iterations × 1.5, once enough samples exist
+//!    (never above `timeout_seconds`)
+//! 3. The configured `timeout_seconds`
+
+use crate::config::Config;
+use crate::error::{HydraError, Result};
+use crate::plan::Plan;
+use crate::runner::IterationResult;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Minimum number of successful samples before adaptive timeouts kick in
+const MIN_SAMPLES: usize = 5;
+
+/// Headroom applied to the p95 duration
+const ADAPTIVE_FACTOR: f64 = 1.5;
+
+/// Adaptive timeouts never go below this, however fast past iterations were
+const MIN_ADAPTIVE_TIMEOUT_SECONDS: u64 = 300;
+
+/// Only the most recent records are kept per plan
+const MAX_RECORDS: usize = 200;
+
+/// One finished iteration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DurationRecord {
+    /// Local time the iteration finished
+    pub finished_at: String,
+    /// Wall-clock duration in seconds
+    pub seconds: u64,
+    /// Iteration result as written to the session log (e.g. `TASK_COMPLETE`)
+    pub result: String,
+    /// Next unchecked task when the iteration started, if known
+    #[serde(default)]
+    pub task: Option<String>,
+}
+
+/// Duration history for a single plan
+#[derive(Debug, Clone)]
+pub struct DurationHistory {
+    path: PathBuf,
+    records: Vec<DurationRecord>,
+}
+
+impl DurationHistory {
+    /// History file for a plan (./.hydra/history/<plan>.json)
+    pub fn path_for(plan_name: &str) -> PathBuf {
+        Config::history_dir().join(format!("{}.json", plan_name))
+    }
+
+    /// Load the history for a plan; missing or unreadable files start empty
+    pub fn load(plan_name: &str) -> Self {
+        Self::load_from(Self::path_for(plan_name))
+    }
+
+    /// Load history from a specific file
+    pub fn load_from(path: PathBuf) -> Self {
+        let records = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self { path, records }
+    }
+
+    /// Append an iteration and write the history back to disk
+    pub fn record(
+        &mut self,
+        seconds: u64,
+        result: &IterationResult,
+        task: Option<&str>,
+    ) -> Result<()> {
+        self.records.push(DurationRecord {
+            finished_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            seconds,
+            result: result.label().to_string(),
+            task: task.map(str::to_string),
+        });
+        if self.records.len() > MAX_RECORDS {
+            let excess = self.records.len() - MAX_RECORDS;
+            self.records.drain(..excess);
+        }
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| {
+                HydraError::io(format!("creating history directory {}", dir.display()), e)
+            })?;
+        }
+        let json = serde_json::to_string_pretty(&self.records).map_err(|e| {
+            HydraError::io(
+                "serializing duration history",
+                std::io::Error::other(e.to_string()),
+            )
+        })?;
+        fs::write(&self.path, format!("{}\n", json))
+            .map_err(|e| HydraError::io(format!("writing history {}", self.path.display()), e))
+    }
+
+    /// Sorted durations of iterations that ended with a stop signal
+    fn successful_durations(&self) -> Vec<u64> {
+        let mut durations: Vec<u64> = self
+            .records
+            .iter()
+            .filter(|r| r.result == "TASK_COMPLETE" || r.result == "ALL_COMPLETE")
+            .map(|r| r.seconds)
+            .collect();
+        durations.sort_unstable();
+        durations
+    }
+
+    /// Nearest-rank percentile (0–100) of successful durations
+    pub fn percentile(&self, pct: f64) -> Option<u64> {
+        let durations = self.successful_durations();
+        if durations.is_empty() {
+            return None;
+        }
+        let rank = ((pct / 100.0) * durations.len() as f64).ceil() as usize;
+        Some(durations[rank.clamp(1, durations.len()) - 1])
+    }
+
+    /// p95 × 1.5 of successful durations, once enough samples exist
+    pub fn adaptive_timeout(&self) -> Option<u64> {
+        if self.successful_durations().len() < MIN_SAMPLES {
+            return None;
+        }
+        let p95 = self.percentile(95.0)?;
+        Some(((p95 as f64 * ADAPTIVE_FACTOR).round() as u64).max(MIN_ADAPTIVE_TIMEOUT_SECONDS))
+    }
+
+    /// Estimated time to finish `remaining` tasks, based on the median duration
+    pub fn estimate_remaining(&self, remaining: usize) -> Option<u64> {
+        if remaining == 0 {
+            return Some(0);
+        }
+        self.percentile(50.0)
+            .map(|median| median.saturating_mul(remaining as u64))
+    }
+}
+
+/// Where an iteration's timeout came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeoutSource {
+    /// `(timeout: ...)` annotation on the next plan task
+    PlanHint,
+    /// p95 of recorded durations
+    History,
+    /// `timeout_seconds` from config or `--timeout`
+    Config,
+}
+
+impl fmt::Display for TimeoutSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeoutSource::PlanHint => write!(f, "plan hint"),
+            TimeoutSource::History => write!(f, "history p95"),
+            TimeoutSource::Config => write!(f, "config"),
+        }
+    }
+}
+
+/// Timeout and progress information for the iteration about to start
+#[derive(Debug, Clone, PartialEq)]
+pub struct IterationEstimate {
+    /// Base timeout in seconds (before any retry multiplier)
+    pub timeout_seconds: u64,
+    /// Where the timeout came from
+    pub source: TimeoutSource,
+    /// Next unchecked task in the plan
+    pub next_task: Option<String>,
+    /// Number of unchecked tasks
+    pub remaining: Option<usize>,
+    /// Estimated seconds until all remaining tasks are done
+    pub eta_seconds: Option<u64>,
+}
+
+impl IterationEstimate {
+    /// Print the remaining-task ETA and the timeout chosen for this
+    /// iteration; returns the line for the session log
+    pub fn announce(&self) -> String {
+        if let (Some(remaining), Some(eta)) = (self.remaining, self.eta_seconds)
+            && remaining > 0
+        {
+            println!(
+                "[hydra] {} task(s) remaining, ETA ~{}",
+                remaining,
+                format_duration(eta)
+            );
+        }
+        if self.source != TimeoutSource::Config {
+            println!(
+                "[hydra] Iteration timeout: {} ({})",
+                format_duration(self.timeout_seconds),
+                self.source
+            );
+        }
+        format!(
+            "Iteration timeout: {}s ({})",
+            self.timeout_seconds, self.source
+        )
+    }
+}
+
+/// Work out the timeout and ETA for the next iteration
+pub fn estimate_iteration(
+    config: &Config,
+    plan_path: Option<&Path>,
+    history: Option<&DurationHistory>,
+) -> IterationEstimate {
+    let plan = plan_path.and_then(|p| Plan::load(p).ok());
+    let next = plan.as_ref().and_then(|p| p.next_unchecked());
+    let remaining = plan.as_ref().map(|p| p.unchecked_count());
+
+    let adaptive = if config.adaptive_timeout {
+        history
+            .and_then(|h| h.adaptive_timeout())
+            .map(|t| t.min(config.timeout_seconds))
+    } else {
+        None
+    };
+    let (timeout_seconds, source) = match (next.and_then(|t| t.timeout_hint), adaptive) {
+        (Some(hint), _) => (hint, TimeoutSource::PlanHint),
+        (None, Some(t)) => (t, TimeoutSource::History),
+        (None, None) => (config.timeout_seconds, TimeoutSource::Config),
+    };
+
+    IterationEstimate {
+        timeout_seconds,
+        source,
+        next_task: next.map(|t| t.text.clone()),
+        remaining,
+        eta_seconds: remaining.and_then(|n| history.and_then(|h| h.estimate_remaining(n))),
+    }
+}
+
+/// Record how long an iteration took in the plan's duration history, if
+/// there is one; a failed write only warns
+pub fn record_duration(
+    history: Option<&mut DurationHistory>,
+    elapsed: Duration,
+    result: &IterationResult,
+    task: Option<&str>,
+) {
+    if let Some(history) = history
+        && let Err(e) = history.record(elapsed.as_secs(), result, task)
+    {
+        eprintln!(
+            "[hydra] Warning: Could not record iteration duration: {}",
+            e
+        );
+    }
+}
+
+/// Human-readable duration, e.g. `45s`, `12m`, `1h 05m`
+pub fn format_duration(seconds: u64) -> String {
+    if seconds < 60 {
+        format!("{}s", seconds)
+    } else if seconds < 3600 {
+        format!("{}m", seconds / 60)
+    } else {
+        format!("{}h {:02}m", seconds / 3600, (seconds % 3600) / 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn history_with(durations: &[u64]) -> (TempDir, DurationHistory) {
+        let dir = TempDir::new().unwrap();
+        let mut history = DurationHistory::load_from(dir.path().join("plan.json"));
+        for &secs in durations {
+            history
+                .record(secs, &IterationResult::TaskComplete, None)
+                .unwrap();
+        }
+        (dir, history)
+    }
+
+    #[test]
+    fn test_record_round_trips_through_disk() {
+        let (dir, mut history) = history_with(&[120]);
+        history
+            .record(600, &IterationResult::Timeout, Some("Write docs"))
+            .unwrap();
+
+        let reloaded = DurationHistory::load_from(dir.path().join("plan.json"));
+        assert_eq!(reloaded.records.len(), 2);
+        assert_eq!(reloaded.records[1].result, "TIMEOUT");
+        assert_eq!(reloaded.records[1].task.as_deref(), Some("Write docs"));
+    }
+
+    #[test]
+    fn test_percentile_ignores_failed_iterations() {
+        let (_dir, mut history) = history_with(&[100, 200, 300, 400]);
+        history
+            .record(9999, &IterationResult::Timeout, None)
+            .unwrap();
+
+        assert_eq!(history.percentile(50.0), Some(200));
+        assert_eq!(history.percentile(95.0), Some(400));
+    }
+
+    #[test]
+    fn test_adaptive_timeout_needs_enough_samples() {
+        let (_dir, history) = history_with(&[600, 600, 600, 600]);
+        assert_eq!(history.adaptive_timeout(), None);
+
+        let (_dir, history) = history_with(&[600, 600, 600, 600, 1000]);
+        assert_eq!(history.adaptive_timeout(), Some(1500));
+
+        let (_dir, history) = history_with(&[10, 10, 10, 10, 10]);
+        assert_eq!(
+            history.adaptive_timeout(),
+            Some(MIN_ADAPTIVE_TIMEOUT_SECONDS)
+        );
+    }
+
+    #[test]
+    fn test_estimate_prefers_hint_then_history_then_config() {
+        let dir = TempDir::new().unwrap();
+        let plan_path = dir.path().join("plan.md");
+        fs::write(
+            &plan_path,
+            "- [x] Done\n- [ ] Big task (timeout: 90m)\n- [ ] Small\n",
+        )
+        .unwrap();
+        let config = Config {
+            adaptive_timeout: true,
+            ..Config::default()
+        };
+        let (_h, history) = history_with(&[600, 600, 600, 600, 600]);
+
+        let est = estimate_iteration(&config, Some(&plan_path), Some(&history));
+        assert_eq!(est.timeout_seconds, 5400);
+        assert_eq!(est.source, TimeoutSource::PlanHint);
+        assert_eq!(est.next_task.as_deref(), Some("Big task"));
+        assert_eq!(est.remaining, Some(2));
+        assert_eq!(est.eta_seconds, Some(1200));
+
+        fs::write(&plan_path, "- [ ] Small\n").unwrap();
+        let est = estimate_iteration(&config, Some(&plan_path), Some(&history));
+        assert_eq!(est.timeout_seconds, 900);
+        assert_eq!(est.source, TimeoutSource::History);
+
+        // Off by default
+        let disabled = Config::default();
+        let est = estimate_iteration(&disabled, Some(&plan_path), Some(&history));
+        assert_eq!(est.timeout_seconds, disabled.timeout_seconds);
+        assert_eq!(est.source, TimeoutSource::Config);
+    }
+
+    #[test]
+    fn test_adaptive_timeout_capped_by_config() {
+        let config = Config {
+            timeout_seconds: 700,
+            adaptive_timeout: true,
+            ..Config::default()
+        };
+        let (_h, history) = history_with(&[600, 600, 600, 600, 600]);
+        let est = estimate_iteration(&config, None, Some(&history));
+        assert_eq!(est.timeout_seconds, 700);
+        assert_eq!(est.remaining, None);
+        assert_eq!(est.eta_seconds, None);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(720), "12m");
+        assert_eq!(format_duration(3900), "1h 05m");
+    }
+}
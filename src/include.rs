@@ -0,0 +1,192 @@
+//! Prompt composition from fragment files
+//!
+//! A prompt may pull in other files with `<!-- hydra:include path -->`, where
+//! `path` is relative to the including file. Included files may include
+//! further files; a file that ends up including itself is an error. After
+//! includes are expanded, the `prompt_fragments` listed in config.toml are
+//! appended in order (relative paths are relative to `~/.hydra/`, where the
+//! config file lives).
+
+use crate::config::Config;
+use crate::error::{HydraError, Result};
+use crate::prompt::ResolvedPrompt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const INCLUDE_START: &str = "<!-- hydra:include ";
+const INCLUDE_END: &str = "-->";
+
+/// Expand includes in the resolved prompt and append the configured fragments
+pub fn compose_prompt(prompt: &mut ResolvedPrompt, fragments: &[PathBuf]) -> Result<()> {
+    let mut stack = vec![identity(&prompt.path)];
+    let mut content = expand(&prompt.content, &prompt.path, &mut stack)?;
+
+    for fragment in fragments {
+        let path = fragment_path(fragment);
+        if !path.exists() {
+            return Err(HydraError::PromptFragmentNotFound(path));
+        }
+        let mut stack = vec![identity(&path)];
+        let expanded = expand(&read(&path)?, &path, &mut stack)?;
+        content = format!("{}\n\n{}", content.trim_end(), expanded.trim_end());
+    }
+
+    prompt.content = content;
+    Ok(())
+}
+
+/// Replace include directives in `content`, read from `path`. `stack` holds
+/// the files currently being expanded, outermost first.
+fn expand(content: &str, path: &Path, stack: &mut Vec<PathBuf>) -> Result<String> {
+    let base = path.parent().unwrap_or(Path::new("."));
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find(INCLUDE_START) {
+        let after = &rest[start + INCLUDE_START.len()..];
+        let Some(end) = after.find(INCLUDE_END) else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        rest = &after[end + INCLUDE_END.len()..];
+
+        let target = base.join(after[..end].trim());
+        if !target.exists() {
+            return Err(HydraError::IncludeNotFound {
+                path: target,
+                included_from: path.to_path_buf(),
+            });
+        }
+        let id = identity(&target);
+        if let Some(pos) = stack.iter().position(|p| *p == id) {
+            let mut chain: Vec<String> = stack[pos..]
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect();
+            chain.push(id.display().to_string());
+            return Err(HydraError::IncludeCycle(chain.join(" -> ")));
+        }
+
+        stack.push(id);
+        let included = expand(&read(&target)?, &target, stack)?;
+        stack.pop();
+        out.push_str(included.trim_end());
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Where a configured fragment lives: `~/` is expanded and other relative
+/// paths are taken from `~/.hydra/`
+fn fragment_path(fragment: &Path) -> PathBuf {
+    if let Ok(rest) = fragment.strip_prefix("~")
+        && let Some(home) = dirs::home_dir()
+    {
+        return home.join(rest);
+    }
+    if fragment.is_absolute() {
+        fragment.to_path_buf()
+    } else {
+        Config::global_hydra_dir().join(fragment)
+    }
+}
+
+/// Canonical path used to recognise a file reached through different paths
+fn identity(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path)
+        .map_err(|e| HydraError::io(format!("reading prompt include {}", path.display()), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt::PromptSource;
+    use tempfile::TempDir;
+
+    fn prompt_at(path: &Path) -> ResolvedPrompt {
+        ResolvedPrompt {
+            path: path.to_path_buf(),
+            content: fs::read_to_string(path).unwrap(),
+            source: PromptSource::CliOverride,
+        }
+    }
+
+    #[test]
+    fn test_nested_includes_relative_to_including_file() {
+        let dir = TempDir::new().unwrap();
+        let project = dir.path().join("project");
+        let shared = dir.path().join("shared");
+        fs::create_dir_all(&project).unwrap();
+        fs::create_dir_all(&shared).unwrap();
+        fs::write(
+            project.join("prompt.md"),
+            "Intro\n<!-- hydra:include ../shared/standards.md -->\nOutro\n",
+        )
+        .unwrap();
+        fs::write(
+            shared.join("standards.md"),
+            "Standards\n<!-- hydra:include tests.md -->\n",
+        )
+        .unwrap();
+        fs::write(shared.join("tests.md"), "Run cargo test\n").unwrap();
+
+        let mut prompt = prompt_at(&project.join("prompt.md"));
+        compose_prompt(&mut prompt, &[]).unwrap();
+        assert_eq!(prompt.content, "Intro\nStandards\nRun cargo test\nOutro\n");
+    }
+
+    #[test]
+    fn test_fragments_appended_in_order() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("prompt.md"), "Prompt\n").unwrap();
+        fs::write(dir.path().join("a.md"), "Fragment A\n").unwrap();
+        fs::write(dir.path().join("b.md"), "Fragment B\n").unwrap();
+
+        let mut prompt = prompt_at(&dir.path().join("prompt.md"));
+        let fragments = [dir.path().join("a.md"), dir.path().join("b.md")];
+        compose_prompt(&mut prompt, &fragments).unwrap();
+        assert_eq!(prompt.content, "Prompt\n\nFragment A\n\nFragment B");
+
+        let missing = [dir.path().join("missing.md")];
+        assert!(matches!(
+            compose_prompt(&mut prompt_at(&dir.path().join("prompt.md")), &missing),
+            Err(HydraError::PromptFragmentNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_include_errors() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("prompt.md"),
+            "<!-- hydra:include nope.md -->",
+        )
+        .unwrap();
+        match compose_prompt(&mut prompt_at(&dir.path().join("prompt.md")), &[]) {
+            Err(HydraError::IncludeNotFound {
+                path,
+                included_from,
+            }) => {
+                assert!(path.ends_with("nope.md"));
+                assert!(included_from.ends_with("prompt.md"));
+            }
+            other => panic!("expected IncludeNotFound, got {:?}", other),
+        }
+
+        fs::write(dir.path().join("a.md"), "<!-- hydra:include b.md -->").unwrap();
+        fs::write(dir.path().join("b.md"), "<!-- hydra:include a.md -->").unwrap();
+        fs::write(dir.path().join("prompt.md"), "<!-- hydra:include a.md -->").unwrap();
+        match compose_prompt(&mut prompt_at(&dir.path().join("prompt.md")), &[]) {
+            Err(HydraError::IncludeCycle(chain)) => {
+                assert!(chain.contains("a.md -> "));
+                assert!(chain.ends_with("a.md"));
+            }
+            other => panic!("expected IncludeCycle, got {:?}", other),
+        }
+    }
+}
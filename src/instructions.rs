@@ -0,0 +1,172 @@
+//! Iteration instructions resolution
+//!
+//! The instructions prepended to every prompt (one task per iteration, stop
+//! sequences, scratchpad rules) can be replaced per project or per user. They
+//! must describe the stop sequences indirectly: harnesses that echo the prompt
+//! back would otherwise trip the signal scanner on the first screen.
+
+use crate::config::Config;
+use crate::error::{HydraError, Result};
+use crate::question::QUESTION_SIGNAL;
+use std::fs;
+use std::path::PathBuf;
+
+/// Built-in instructions, used when no override exists
+pub const DEFAULT_INSTRUCTIONS: &str = include_str!("../templates/iteration-instructions.md");
+
+/// Signals the agent emits; none may appear literally in the instructions
+const STOP_SIGNALS: &[&str] = &[
+    "###TASK_COMPLETE###",
+    "###ALL_TASKS_COMPLETE###",
+    QUESTION_SIGNAL,
+];
+
+/// Result of instructions resolution
+#[derive(Debug)]
+pub struct ResolvedInstructions {
+    /// The file the instructions came from (`None` for the built-in ones)
+    pub path: Option<PathBuf>,
+    /// The instructions text
+    pub content: String,
+    /// Which priority level matched
+    pub source: InstructionsSource,
+}
+
+/// Source of the resolved instructions
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstructionsSource {
+    /// CLI --instructions flag (highest priority)
+    CliOverride,
+    /// ./.hydra/iteration-instructions.md (project-specific)
+    ProjectHydra,
+    /// ~/.hydra/iteration-instructions.md (global)
+    Global,
+    /// Built into the binary (lowest priority)
+    Embedded,
+}
+
+impl std::fmt::Display for InstructionsSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InstructionsSource::CliOverride => write!(f, "CLI --instructions flag"),
+            InstructionsSource::ProjectHydra => write!(f, "./.hydra/iteration-instructions.md"),
+            InstructionsSource::Global => write!(f, "~/.hydra/iteration-instructions.md"),
+            InstructionsSource::Embedded => write!(f, "built-in"),
+        }
+    }
+}
+
+/// Resolve the iteration instructions according to priority chain:
+/// 1. CLI --instructions flag (highest)
+/// 2. ./.hydra/iteration-instructions.md (project-specific)
+/// 3. ~/.hydra/iteration-instructions.md (global)
+/// 4. Built-in instructions (lowest)
+///
+/// Custom instructions containing a literal stop signal are rejected.
+pub fn resolve_instructions(cli_instructions: Option<&PathBuf>) -> Result<ResolvedInstructions> {
+    // Priority 1: CLI --instructions flag
+    if let Some(path) = cli_instructions {
+        if !path.exists() {
+            return Err(HydraError::InstructionsNotFound(path.clone()));
+        }
+        return load(path.clone(), InstructionsSource::CliOverride);
+    }
+
+    // Priority 2: ./.hydra/iteration-instructions.md
+    let project = Config::local_instructions_path();
+    if project.exists() {
+        return load(project, InstructionsSource::ProjectHydra);
+    }
+
+    // Priority 3: ~/.hydra/iteration-instructions.md
+    let global = Config::global_instructions_path();
+    if global.exists() {
+        return load(global, InstructionsSource::Global);
+    }
+
+    // Priority 4: built-in
+    Ok(ResolvedInstructions {
+        path: None,
+        content: DEFAULT_INSTRUCTIONS.to_string(),
+        source: InstructionsSource::Embedded,
+    })
+}
+
+fn load(path: PathBuf, source: InstructionsSource) -> Result<ResolvedInstructions> {
+    let content = fs::read_to_string(&path).map_err(|e| {
+        HydraError::io(
+            format!("reading iteration instructions {}", path.display()),
+            e,
+        )
+    })?;
+    if let Some(signal) = literal_signal(&content) {
+        return Err(HydraError::InstructionsContainSignal {
+            path,
+            signal: signal.to_string(),
+        });
+    }
+    Ok(ResolvedInstructions {
+        path: Some(path),
+        content,
+        source,
+    })
+}
+
+/// The first stop signal that appears literally in `content`
+pub fn literal_signal(content: &str) -> Option<&'static str> {
+    STOP_SIGNALS
+        .iter()
+        .copied()
+        .find(|signal| content.contains(signal))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_default_instructions_have_no_literal_signals() {
+        assert_eq!(literal_signal(DEFAULT_INSTRUCTIONS), None);
+        assert_eq!(
+            literal_signal("then print ###TASK_COMPLETE### and stop"),
+            Some("###TASK_COMPLETE###")
+        );
+        assert_eq!(
+            literal_signal("ask via ###QUESTION###"),
+            Some(QUESTION_SIGNAL)
+        );
+    }
+
+    #[test]
+    fn test_cli_override() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("rules.md");
+        fs::write(&path, "Complete up to THREE tasks per iteration.").unwrap();
+
+        let resolved = resolve_instructions(Some(&path)).unwrap();
+        assert_eq!(resolved.source, InstructionsSource::CliOverride);
+        assert_eq!(resolved.path.as_ref(), Some(&path));
+        assert!(resolved.content.contains("THREE tasks"));
+
+        let missing = dir.path().join("missing.md");
+        assert!(matches!(
+            resolve_instructions(Some(&missing)),
+            Err(HydraError::InstructionsNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_literal_signal() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("rules.md");
+        fs::write(&path, "When done, output ###ALL_TASKS_COMPLETE###").unwrap();
+
+        match resolve_instructions(Some(&path)) {
+            Err(HydraError::InstructionsContainSignal { signal, .. }) => {
+                assert_eq!(signal, "###ALL_TASKS_COMPLETE###")
+            }
+            other => panic!("expected InstructionsContainSignal, got {:?}", other),
+        }
+    }
+}
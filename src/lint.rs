@@ -0,0 +1,407 @@
+//! Plan linting
+//!
+//! Catches plan mistakes that would otherwise only surface mid-run: no tasks
+//! at all, links to spec files or anchors that don't exist, unclosed code
+//! fences, and tasks that are duplicated, vague or oversized. Errors stop a
+//! session before it starts (unless `--no-lint`); warnings are only reported.
+
+use crate::error::{HydraError, Result};
+use crate::plan::{self, Plan};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Tasks longer than this are flagged as oversized
+const MAX_TASK_CHARS: usize = 300;
+
+/// Tasks with fewer words than this are flagged as ambiguous
+const MIN_TASK_WORDS: usize = 2;
+
+/// How serious a lint finding is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    /// Blocks a session
+    Error,
+    /// Reported, but the session runs
+    Warning,
+}
+
+/// One lint finding
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintIssue {
+    /// 1-based line number in the plan (0 for whole-file findings)
+    pub line: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        if self.line > 0 {
+            write!(f, "{}: ", self.line)?;
+        }
+        write!(f, "{}: {}", severity, self.message)
+    }
+}
+
+/// Lint a plan file; links are resolved relative to the plan's directory
+pub fn lint_plan(path: &Path) -> Result<Vec<LintIssue>> {
+    if !path.exists() {
+        return Err(HydraError::PlanNotFound(path.to_path_buf()));
+    }
+    let content = fs::read_to_string(path)
+        .map_err(|e| HydraError::io(format!("reading plan {}", path.display()), e))?;
+    Ok(lint(&content, path))
+}
+
+/// Lint plan content read from `path`
+pub fn lint(content: &str, path: &Path) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    let base = path.parent().unwrap_or(Path::new("."));
+
+    // Fences: checkboxes inside them are not tasks, and an unclosed fence
+    // silently hides every task after it
+    let mut fence_start = None;
+    for (idx, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence_start = match fence_start {
+                Some(_) => None,
+                None => Some(idx + 1),
+            };
+            continue;
+        }
+        if fence_start.is_some() && plan::parse_checkbox(trimmed).is_some() {
+            issues.push(warning(
+                idx + 1,
+                "checkbox inside a code fence is not a task".to_string(),
+            ));
+        }
+        if fence_start.is_none() {
+            for target in link_targets(line) {
+                if let Some(message) = check_link(target, content, base) {
+                    issues.push(error(idx + 1, message));
+                }
+            }
+        }
+    }
+    if let Some(line) = fence_start {
+        issues.push(error(
+            line,
+            "code fence is never closed; tasks after it are ignored".to_string(),
+        ));
+    }
+
+    let parsed = Plan::parse(content);
+    if parsed.tasks.is_empty() {
+        issues.push(error(
+            0,
+            "no tasks found (tasks are checkbox items: `- [ ] task`)".to_string(),
+        ));
+    }
+
+    let mut first_seen: HashMap<String, usize> = HashMap::new();
+    for task in &parsed.tasks {
+        let key = task.text.to_lowercase();
+        if let Some(first) = first_seen.get(&key) {
+            issues.push(warning(
+                task.line,
+                format!("duplicate of the task on line {}", first),
+            ));
+        } else {
+            first_seen.insert(key, task.line);
+        }
+        let words = task.text.split_whitespace().count();
+        if words < MIN_TASK_WORDS {
+            issues.push(warning(
+                task.line,
+                format!("task \"{}\" is too vague to act on", task.text),
+            ));
+        }
+        if task.text.chars().count() > MAX_TASK_CHARS {
+            issues.push(warning(
+                task.line,
+                format!(
+                    "task is {} characters; consider splitting it",
+                    task.text.chars().count()
+                ),
+            ));
+        }
+    }
+
+    issues.sort_by_key(|issue| issue.line);
+    issues
+}
+
+/// A finding as `plan.md:12: error: …`, or `plan.md: error: …` for the
+/// whole file
+pub fn describe(path: &Path, issue: &LintIssue) -> String {
+    let separator = if issue.line > 0 { ":" } else { ": " };
+    format!("{}{}{}", path.display(), separator, issue)
+}
+
+/// Print issues to stderr, one `describe` line each
+pub fn print_issues(path: &Path, issues: &[LintIssue]) {
+    for issue in issues {
+        eprintln!("{}", describe(path, issue));
+    }
+}
+
+/// Print issues and fail when any is an error
+pub fn report(path: &Path, issues: &[LintIssue]) -> Result<()> {
+    print_issues(path, issues);
+    check(path, issues)
+}
+
+/// Fail when any issue is an error, without printing them
+pub fn check(path: &Path, issues: &[LintIssue]) -> Result<()> {
+    let errors = issues
+        .iter()
+        .filter(|i| i.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        return Err(HydraError::PlanInvalid {
+            path: path.to_path_buf(),
+            errors,
+        });
+    }
+    Ok(())
+}
+
+/// Entry point for `hydra lint <plan>`
+pub fn lint_command(path: &Path) -> Result<()> {
+    let issues = lint_plan(path)?;
+    report(path, &issues)?;
+    // report() returned Ok, so every remaining issue is a warning
+    if issues.is_empty() {
+        println!("{}: ok", path.display());
+    } else {
+        println!("{}: ok ({} warning(s))", path.display(), issues.len());
+    }
+    Ok(())
+}
+
+fn error(line: usize, message: String) -> LintIssue {
+    LintIssue {
+        line,
+        severity: Severity::Error,
+        message,
+    }
+}
+
+fn warning(line: usize, message: String) -> LintIssue {
+    LintIssue {
+        line,
+        severity: Severity::Warning,
+        message,
+    }
+}
+
+/// Targets of inline Markdown links (`[text](target)`) on a line
+fn link_targets(line: &str) -> Vec<&str> {
+    let mut targets = Vec::new();
+    let mut rest = line;
+    while let Some(pos) = rest.find("](") {
+        let after = &rest[pos + 2..];
+        let Some(end) = after.find(')') else {
+            break;
+        };
+        // Drop an optional title: [text](path "title")
+        let target = after[..end].split_whitespace().next().unwrap_or("");
+        if !target.is_empty() {
+            targets.push(target);
+        }
+        rest = &after[end..];
+    }
+    targets
+}
+
+/// Check a local link target; external links are not checked
+fn check_link(target: &str, plan_content: &str, base: &Path) -> Option<String> {
+    if target.contains("://") || target.starts_with("mailto:") {
+        return None;
+    }
+    let (file, anchor) = match target.split_once('#') {
+        Some((file, anchor)) => (file, Some(anchor)),
+        None => (target, None),
+    };
+
+    let linked;
+    let content = if file.is_empty() {
+        plan_content
+    } else {
+        let path = base.join(file);
+        if !path.exists() {
+            return Some(format!("link target {} does not exist", file));
+        }
+        let is_markdown = path.extension().is_some_and(|ext| ext == "md");
+        if anchor.is_none() || !is_markdown {
+            return None;
+        }
+        linked = fs::read_to_string(&path).unwrap_or_default();
+        &linked
+    };
+
+    let anchor = anchor?;
+    if heading_anchors(content).iter().any(|a| a == anchor) {
+        None
+    } else {
+        Some(format!(
+            "anchor #{} not found in {}",
+            anchor,
+            if file.is_empty() { "this plan" } else { file }
+        ))
+    }
+}
+
+/// GitHub-style anchors for the headings in Markdown content
+fn heading_anchors(content: &str) -> Vec<String> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut anchors = Vec::new();
+    let mut in_fence = false;
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence || !trimmed.starts_with('#') {
+            continue;
+        }
+        let text = trimmed.trim_start_matches('#');
+        if !text.starts_with(' ') && !text.is_empty() {
+            continue;
+        }
+        let slug = slugify(text.trim());
+        // Repeated headings get -1, -2, … suffixes
+        let count = seen.entry(slug.clone()).or_insert(0);
+        anchors.push(if *count == 0 {
+            slug.clone()
+        } else {
+            format!("{}-{}", slug, count)
+        });
+        *count += 1;
+    }
+    anchors
+}
+
+fn slugify(heading: &str) -> String {
+    heading
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn messages(issues: &[LintIssue], severity: Severity) -> Vec<String> {
+        issues
+            .iter()
+            .filter(|i| i.severity == severity)
+            .map(|i| format!("{}: {}", i.line, i.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_plan() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("specs")).unwrap();
+        fs::write(
+            dir.path().join("specs/api.md"),
+            "# API\n\n## Login Flow (v2)\n",
+        )
+        .unwrap();
+        let plan = "# Plan\n\n\
+                    ## Tasks\n\
+                    - [ ] Add login endpoint [spec](specs/api.md#login-flow-v2)\n\
+                    - [x] Set up project, see [tasks](#tasks)\n";
+        let issues = lint(plan, &dir.path().join("plan.md"));
+        assert!(issues.is_empty(), "{:?}", issues);
+    }
+
+    #[test]
+    fn test_errors() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("api.md"), "# API\n").unwrap();
+        let plan = "- [ ] Add login [spec](missing.md)\n\
+                    - [ ] Add logout [spec](api.md#logout)\n\
+                    ```\n\
+                    - [ ] Example only\n";
+        let issues = lint(plan, &dir.path().join("plan.md"));
+        assert_eq!(
+            describe(Path::new("plan.md"), &issues[0]),
+            "plan.md:1: error: link target missing.md does not exist"
+        );
+        assert_eq!(
+            messages(&issues, Severity::Error),
+            vec![
+                "1: link target missing.md does not exist",
+                "2: anchor #logout not found in api.md",
+                "3: code fence is never closed; tasks after it are ignored",
+            ]
+        );
+        assert_eq!(
+            messages(&issues, Severity::Warning),
+            vec!["4: checkbox inside a code fence is not a task"]
+        );
+
+        let issues = lint("# Plan\n\nJust prose.\n", Path::new("plan.md"));
+        assert_eq!(
+            messages(&issues, Severity::Error),
+            vec!["0: no tasks found (tasks are checkbox items: `- [ ] task`)"]
+        );
+        assert_eq!(
+            describe(Path::new("plan.md"), &issues[0]),
+            "plan.md: error: no tasks found (tasks are checkbox items: `- [ ] task`)"
+        );
+        assert!(matches!(
+            report(Path::new("plan.md"), &issues),
+            Err(HydraError::PlanInvalid { errors: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_warnings() {
+        let long = "word ".repeat(80);
+        let plan = format!(
+            "- [ ] Write the docs\n- [ ] Refactor\n- [x] write the DOCS\n- [ ] {}\n",
+            long
+        );
+        let issues = lint(&plan, Path::new("plan.md"));
+        assert!(messages(&issues, Severity::Error).is_empty());
+        assert_eq!(
+            messages(&issues, Severity::Warning),
+            vec![
+                "2: task \"Refactor\" is too vague to act on",
+                "3: duplicate of the task on line 1",
+                "4: task is 399 characters; consider splitting it",
+            ]
+        );
+        assert!(report(Path::new("plan.md"), &issues).is_ok());
+    }
+
+    #[test]
+    fn test_heading_anchors() {
+        let anchors = heading_anchors(
+            "# Intro\n## Set-up & Run\n# Intro\n```\n# not a heading\n```\n#hashtag\n",
+        );
+        assert_eq!(anchors, vec!["intro", "set-up--run", "intro-1"]);
+        assert_eq!(
+            link_targets("see [a](x.md) and [b](y.md#z \"title\")"),
+            vec!["x.md", "y.md#z"]
+        );
+    }
+}
@@ -0,0 +1,356 @@
+//! Per-plan session locks
+//!
+//! A running session holds `.hydra/locks/<plan>.lock` (JSON with PID, plan
+//! and start time) so a second `hydra plan.md` in the same directory refuses
+//! to start instead of sharing the plan and scratchpad. Sessions without a
+//! plan use `hydra.lock`. Tabs of the `hydra tui` daemon take the same locks,
+//! under the daemon's PID. Locks left behind by dead processes are stale and
+//! are replaced.
+//!
+//! Lock files are only created, replaced or removed while holding an
+//! exclusive `flock` on `.hydra/locks/.guard`, and are written to a temp file
+//! and renamed into place, so no session ever sees a half-written lock or
+//! removes one another session just took.
+
+use crate::config::Config;
+use crate::error::{HydraError, Result};
+use crate::plan;
+use chrono::Local;
+use nix::errno::Errno;
+use nix::sys::signal::kill;
+use nix::unistd::Pid;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tempfile::NamedTempFile;
+
+/// Lock name used by sessions started without a plan
+const NO_PLAN_LOCK: &str = "hydra";
+
+/// File whose `flock` serializes changes to the lock files
+const GUARD_FILE: &str = ".guard";
+
+/// A lock file that isn't valid JSON is only treated as stale once it is
+/// this old; until then it may belong to a session we can't identify
+const UNREADABLE_STALE_AFTER: Duration = Duration::from_secs(60);
+
+/// Contents of a lock file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockInfo {
+    /// PID of the hydra process holding the lock
+    pub pid: u32,
+    /// Plan name (file stem), if the session was started with a plan
+    pub plan: Option<String>,
+    /// Plan path as given on the command line
+    pub plan_path: Option<PathBuf>,
+    /// Local start time (`YYYY-MM-DD HH:MM:SS`)
+    pub started_at: String,
+    /// Held for a `hydra tui` tab, which has no control socket
+    #[serde(default)]
+    pub tui: bool,
+}
+
+impl LockInfo {
+    /// Name used for the lock file and in messages
+    pub fn name(&self) -> &str {
+        self.plan.as_deref().unwrap_or(NO_PLAN_LOCK)
+    }
+}
+
+/// Session name for a plan: its file stem, or `hydra` without a plan
+pub fn session_name(plan_path: Option<&Path>) -> String {
+    plan_path.map_or_else(|| NO_PLAN_LOCK.to_string(), plan::plan_name)
+}
+
+/// Lock held for the lifetime of a session; removed on drop
+#[derive(Debug)]
+pub struct SessionLock {
+    path: PathBuf,
+    info: LockInfo,
+}
+
+impl SessionLock {
+    /// Acquire the lock for a plan (or the no-plan lock) in `.hydra/locks/`
+    pub fn acquire(plan_path: Option<&Path>) -> Result<Self> {
+        Self::acquire_in(&Config::locks_dir(), plan_path, false)
+    }
+
+    /// Acquire the lock for a `hydra tui` tab working on a plan (or none)
+    pub fn acquire_for_tab(plan_path: Option<&Path>) -> Result<Self> {
+        Self::acquire_in(&Config::locks_dir(), plan_path, true)
+    }
+
+    fn acquire_in(dir: &Path, plan_path: Option<&Path>, tui: bool) -> Result<Self> {
+        fs::create_dir_all(dir).map_err(|e| {
+            HydraError::io(format!("creating locks directory {}", dir.display()), e)
+        })?;
+
+        let info = LockInfo {
+            pid: std::process::id(),
+            plan: plan_path.map(plan::plan_name),
+            plan_path: plan_path.map(Path::to_path_buf),
+            started_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            tui,
+        };
+        let path = dir.join(format!("{}.lock", info.name()));
+        let content = serde_json::to_string_pretty(&info).map_err(|e| {
+            HydraError::io(
+                "serializing lock file",
+                std::io::Error::other(e.to_string()),
+            )
+        })?;
+
+        let _guard = lock_guard(dir)?;
+        if path.exists() {
+            match read_lock(&path) {
+                Some(holder) if is_alive(holder.pid) => {
+                    return Err(HydraError::AlreadyRunning {
+                        plan: holder.name().to_string(),
+                        pid: holder.pid,
+                        started_at: holder.started_at,
+                    });
+                }
+                Some(_) => eprintln!(
+                    "[hydra] Replacing stale lock {} (process no longer running)",
+                    path.display()
+                ),
+                None if is_old(&path) => {
+                    eprintln!("[hydra] Replacing unreadable lock {}", path.display())
+                }
+                None => {
+                    return Err(HydraError::io(
+                        format!("reading lock file {}", path.display()),
+                        std::io::Error::new(
+                            ErrorKind::InvalidData,
+                            "not a hydra lock; remove it if no session is running",
+                        ),
+                    ));
+                }
+            }
+        }
+
+        let mut temp = NamedTempFile::new_in(dir)
+            .map_err(|e| HydraError::io(format!("creating lock file in {}", dir.display()), e))?;
+        temp.write_all(content.as_bytes())
+            .map_err(|e| HydraError::io(format!("writing lock file {}", path.display()), e))?;
+        temp.persist(&path).map_err(|e| {
+            HydraError::io(format!("creating lock file {}", path.display()), e.error)
+        })?;
+        Ok(Self { path, info })
+    }
+}
+
+/// Take the exclusive `flock` on the guard file; it is released when the
+/// returned file is dropped
+fn lock_guard(dir: &Path) -> Result<File> {
+    let path = dir.join(GUARD_FILE);
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .map_err(|e| HydraError::io(format!("opening {}", path.display()), e))?;
+    file.lock()
+        .map_err(|e| HydraError::io(format!("locking {}", path.display()), e))?;
+    Ok(file)
+}
+
+impl Drop for SessionLock {
+    fn drop(&mut self) {
+        let Some(dir) = self.path.parent() else {
+            return;
+        };
+        let Ok(_guard) = lock_guard(dir) else {
+            return;
+        };
+        // Only remove the file if it still belongs to us
+        if read_lock(&self.path).is_some_and(|holder| holder == self.info) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Sessions currently holding a lock in `.hydra/locks/`; stale locks are removed
+pub fn running_sessions() -> Vec<LockInfo> {
+    running_sessions_in(&Config::locks_dir())
+}
+
+fn running_sessions_in(dir: &Path) -> Vec<LockInfo> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let Ok(_guard) = lock_guard(dir) else {
+        return Vec::new();
+    };
+    let mut sessions = Vec::new();
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if path.extension().is_none_or(|ext| ext != "lock") {
+            continue;
+        }
+        // Leave unreadable files to the next acquire, which knows their age
+        let Some(info) = read_lock(&path) else {
+            continue;
+        };
+        if is_alive(info.pid) {
+            sessions.push(info);
+        } else {
+            let _ = fs::remove_file(&path);
+        }
+    }
+    sessions.sort_by(|a, b| a.name().cmp(b.name()));
+    sessions
+}
+
+fn read_lock(path: &Path) -> Option<LockInfo> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Whether a file was last written more than `UNREADABLE_STALE_AFTER` ago
+fn is_old(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age > UNREADABLE_STALE_AFTER)
+}
+
+/// Whether a process with this PID exists
+pub fn is_alive(pid: u32) -> bool {
+    let Ok(raw) = i32::try_from(pid) else {
+        return false;
+    };
+    // Signal 0 only checks for existence; EPERM means it exists but isn't ours
+    matches!(kill(Pid::from_raw(raw), None), Ok(()) | Err(Errno::EPERM))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A PID that is certainly not running (above the kernel's pid_max)
+    const DEAD_PID: u32 = 99_999_999;
+
+    fn write_lock(dir: &Path, name: &str, pid: u32) {
+        let info = LockInfo {
+            pid,
+            plan: Some(name.to_string()),
+            plan_path: Some(PathBuf::from(format!("plans/{}.md", name))),
+            started_at: "2026-01-01 10:00:00".to_string(),
+            tui: false,
+        };
+        fs::write(
+            dir.join(format!("{}.lock", name)),
+            serde_json::to_string(&info).unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_acquire_writes_and_drop_removes_lock() {
+        let dir = TempDir::new().unwrap();
+        let lock =
+            SessionLock::acquire_in(dir.path(), Some(Path::new("plans/a.md")), false).unwrap();
+        let lock_path = dir.path().join("a.lock");
+
+        let on_disk = read_lock(&lock_path).unwrap();
+        assert_eq!(on_disk.pid, std::process::id());
+        assert_eq!(on_disk.plan.as_deref(), Some("a"));
+        assert_eq!(&on_disk, &lock.info);
+
+        drop(lock);
+        assert!(!lock_path.exists());
+    }
+
+    #[test]
+    fn test_second_acquire_on_same_plan_fails() {
+        let dir = TempDir::new().unwrap();
+        let _lock = SessionLock::acquire_in(dir.path(), Some(Path::new("a.md")), false).unwrap();
+
+        let err =
+            SessionLock::acquire_in(dir.path(), Some(Path::new("plans/a.md")), false).unwrap_err();
+        assert!(matches!(err, HydraError::AlreadyRunning { ref plan, .. } if plan == "a"));
+        // A TUI tab on the plan is turned away the same way
+        let err = SessionLock::acquire_in(dir.path(), Some(Path::new("a.md")), true).unwrap_err();
+        assert!(matches!(err, HydraError::AlreadyRunning { .. }));
+        let _tab = SessionLock::acquire_in(dir.path(), Some(Path::new("c.md")), true).unwrap();
+        assert!(read_lock(&dir.path().join("c.lock")).unwrap().tui);
+
+        // Other plans and plan-less sessions are independent
+        let _b = SessionLock::acquire_in(dir.path(), Some(Path::new("b.md")), false).unwrap();
+        let _none = SessionLock::acquire_in(dir.path(), None, false).unwrap();
+        assert!(dir.path().join("hydra.lock").exists());
+    }
+
+    #[test]
+    fn test_stale_lock_is_replaced() {
+        let dir = TempDir::new().unwrap();
+        write_lock(dir.path(), "a", DEAD_PID);
+
+        let lock = SessionLock::acquire_in(dir.path(), Some(Path::new("a.md")), false).unwrap();
+        assert_eq!(lock.info.pid, std::process::id());
+    }
+
+    #[test]
+    fn test_unreadable_lock_is_kept_until_old() {
+        let dir = TempDir::new().unwrap();
+        let lock_path = dir.path().join("a.lock");
+        fs::write(&lock_path, "").unwrap();
+
+        let err = SessionLock::acquire_in(dir.path(), Some(Path::new("a.md")), false).unwrap_err();
+        assert!(err.to_string().contains("not a hydra lock"));
+        assert!(lock_path.exists());
+
+        let old = SystemTime::now() - UNREADABLE_STALE_AFTER * 2;
+        File::options()
+            .write(true)
+            .open(&lock_path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        let lock = SessionLock::acquire_in(dir.path(), Some(Path::new("a.md")), false).unwrap();
+        assert_eq!(read_lock(&lock_path).unwrap(), lock.info);
+    }
+
+    #[test]
+    fn test_concurrent_acquires_have_one_winner() {
+        let dir = TempDir::new().unwrap();
+        write_lock(dir.path(), "a", DEAD_PID);
+        let attempts: Vec<_> = (0..8)
+            .map(|_| {
+                let dir = dir.path().to_path_buf();
+                std::thread::spawn(move || {
+                    SessionLock::acquire_in(&dir, Some(Path::new("a.md")), false).ok()
+                })
+            })
+            .collect();
+        let held: Vec<SessionLock> = attempts
+            .into_iter()
+            .filter_map(|t| t.join().unwrap())
+            .collect();
+        assert_eq!(held.len(), 1);
+    }
+
+    #[test]
+    fn test_running_sessions_skips_and_cleans_stale() {
+        let dir = TempDir::new().unwrap();
+        write_lock(dir.path(), "dead", DEAD_PID);
+        write_lock(dir.path(), "alive", std::process::id());
+        fs::write(dir.path().join("garbage.lock"), "not json").unwrap();
+
+        let sessions = running_sessions_in(dir.path());
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].name(), "alive");
+        assert!(!dir.path().join("dead.lock").exists());
+        assert!(dir.path().join("garbage.lock").exists());
+    }
+
+    #[test]
+    fn test_is_alive() {
+        assert!(is_alive(std::process::id()));
+        assert!(!is_alive(DEAD_PID));
+    }
+}
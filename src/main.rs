@@ -1,27 +1,66 @@
 mod cli;
 mod config;
+mod control;
 mod error;
+mod generate;
 mod harness;
 mod headless;
+mod history;
+mod include;
+mod instructions;
+mod lint;
+mod lock;
+mod plan;
 mod prompt;
 mod pty;
+mod question;
+mod queue;
+mod retry;
 mod runner;
+mod session_loop;
 mod signal;
 mod skill;
+mod status;
+mod step;
+mod template;
 mod tui;
 
 use clap::Parser;
 use cli::Cli;
 use config::Config;
+use control::ControlServer;
 use error::{EXIT_SUCCESS, HydraError, Result};
 use harness::{Harness, HarnessConfig};
 use headless::HeadlessRunner;
-use prompt::{inject_plan_path, inject_scratchpad_path, resolve_prompt};
+use instructions::resolve_instructions;
+use lock::SessionLock;
+use prompt::{
+    ResolvedPrompt, inject_plan_path, inject_question_path, inject_scratchpad_path, resolve_prompt,
+};
 use runner::{RunResult, Runner};
 use skill::{SkillType, create_skill_with_claude, prompt_yes_no, spawn_claude_interactive};
 use std::fs::{self, OpenOptions};
 use std::io::{BufRead, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use step::StepMode;
+use template::TemplateVars;
+
+/// Start the control socket for `hydra stop/pause/resume-loop/skip`.
+/// A session without one still runs; it can only be stopped by signal.
+fn start_control(
+    plan: Option<&Path>,
+    stop_flag: std::sync::Arc<std::sync::atomic::AtomicBool>,
+) -> Option<ControlServer> {
+    match ControlServer::start(&lock::session_name(plan), stop_flag) {
+        Ok(server) => Some(server),
+        Err(e) => {
+            eprintln!("[hydra] Warning: Failed to start control socket: {}", e);
+            None
+        }
+    }
+}
 
 /// Debug log to file (since terminal may be frozen)
 fn debug_log(msg: &str) {
@@ -97,135 +136,216 @@ fn run(cli: Cli) -> Result<()> {
         install_command()
     } else if cli.is_init() {
         init_command(config.verbose, cli.is_init_quick())
+    } else if cli.is_status() {
+        let (plan, json) = cli.status_args();
+        status::status_command(&config, plan.map(PathBuf::as_path), json)
+    } else if let Some((spec, out)) = cli.plan_new_args() {
+        generate::plan_new_command(spec, out.map(PathBuf::as_path), harness)
+    } else if let Some(plan) = cli.lint_plan() {
+        lint::lint_command(plan)
+    } else if let Some((command, plan)) = cli.control_request() {
+        control::control_command(plan.map(PathBuf::as_path), command)
+    } else if cli.is_daemon() {
+        tui::run_daemon()
+    } else if cli.is_ls() {
+        tui::ls_command()
     } else if cli.is_tui() {
         // TUI mode
-        let mut resolved = resolve_prompt(cli.prompt.as_ref())?;
+        let resolved = resolve_prompt(cli.prompt.as_ref())?;
+        let instructions = resolve_instructions(cli.instructions.as_ref())?;
+
+        // One tab per plan; errors and lint findings are reported before
+        // the TUI takes the terminal
+        let sessions = cli
+            .tui_plans()
+            .iter()
+            .map(|plan| {
+                if !cli.no_lint {
+                    lint::report(plan, &lint::lint_plan(plan)?)?;
+                }
+                tui::TabSession::prepare(
+                    &config,
+                    harness,
+                    resolved.clone(),
+                    &instructions.content,
+                    Some(plan),
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        tui::run_tui(
+            config,
+            resolved,
+            instructions.content,
+            harness,
+            !cli.no_lint,
+            sessions,
+        )
+    } else {
+        let plans = cli.plan_queue()?;
+        if plans.len() > 1 {
+            return queue::run_queue(&cli, &config, harness, &plans);
+        }
+        let plan = plans.first().map(PathBuf::as_path);
+
+        // Fail fast on plan mistakes instead of discovering them mid-run
+        if let Some(plan_path) = plan
+            && !cli.no_lint
+        {
+            lint::report(plan_path, &lint::lint_plan(plan_path)?)?;
+        }
 
-        // If a plan file is provided in tui subcommand, inject it
-        if let Some(plan_path) = cli.tui_plan() {
-            if !plan_path.exists() {
-                return Err(HydraError::PlanNotFound(plan_path.clone()));
+        match run_session(&cli, &config, harness, plan, None)? {
+            Some(result) => {
+                // Launch plan review if all tasks completed and a plan was
+                // provided (unless --no-review)
+                if matches!(result, RunResult::AllTasksComplete { .. })
+                    && !cli.no_review
+                    && let Some(plan_path) = plan
+                {
+                    review_plan(&cli, &config, harness, plan_path);
+                }
+                exit_result(&result)
             }
-            resolved.content = inject_plan_path(&resolved.content, plan_path);
+            None => Ok(()),
         }
+    }
+}
 
-        tui::run_tui(config, resolved)
+/// Run the task loop for one plan (or none). Returns `None` for a dry run.
+///
+/// `stop_flag` is shared by the sessions of a plan queue, which install the
+/// signal handlers once; a lone session installs its own.
+pub(crate) fn run_session(
+    cli: &Cli,
+    config: &Config,
+    harness: Harness,
+    plan: Option<&Path>,
+    stop_flag: Option<Arc<AtomicBool>>,
+) -> Result<Option<RunResult>> {
+    // Resolve prompt file according to priority chain
+    let mut resolved = resolve_prompt(cli.prompt.as_ref())?;
+    include::compose_prompt(&mut resolved, &config.prompt_fragments)?;
+
+    if config.verbose {
+        eprintln!("Prompt resolved from: {}", resolved.source);
+        eprintln!("Prompt path: {}", resolved.path.display());
+    }
+
+    let instructions = resolve_instructions(cli.instructions.as_ref())?;
+    if config.verbose {
+        eprintln!("Iteration instructions from: {}", instructions.source);
+    }
+
+    // --step / --step-on: validated before anything touches the plan
+    let step_mode = if cli.is_step() {
+        Some(StepMode::new(cli.step_on.as_deref())?)
     } else {
-        // Resolve prompt file according to priority chain
-        let mut resolved = resolve_prompt(cli.prompt.as_ref())?;
+        None
+    };
 
-        if config.verbose {
-            eprintln!("Prompt resolved from: {}", resolved.source);
-            eprintln!("Prompt path: {}", resolved.path.display());
+    // Refuse to run the same plan twice in this directory. Held until
+    // run() returns; dry runs don't touch the plan so don't need it.
+    let _lock = if cli.dry_run {
+        None
+    } else {
+        Some(SessionLock::acquire(plan)?)
+    };
+
+    // Handle --reset-plan flag
+    if cli.reset_plan {
+        let plan_path = plan.ok_or(HydraError::ResetPlanWithoutPlan)?;
+
+        // Uncheck all checkboxes in the plan file
+        let plan_content = fs::read_to_string(plan_path)
+            .map_err(|e| HydraError::io(format!("reading plan {}", plan_path.display()), e))?;
+        let reset_content = plan_content
+            .replace("- [x]", "- [ ]")
+            .replace("- [X]", "- [ ]");
+        if reset_content != plan_content {
+            fs::write(plan_path, &reset_content)
+                .map_err(|e| HydraError::io(format!("writing plan {}", plan_path.display()), e))?;
         }
 
-        // Handle --reset-plan flag
-        if cli.reset_plan {
-            let plan_path = cli.plan.as_ref().ok_or_else(|| {
+        // Clear scratchpad if it exists
+        let plan_stem = plan_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("scratchpad");
+        let scratchpad_path = Config::scratchpad_dir().join(format!("{}.md", plan_stem));
+        if scratchpad_path.exists() {
+            let header = format!(
+                "# Scratchpad — {}\n\nCross-iteration notes for this plan.\n",
+                plan_stem
+            );
+            fs::write(&scratchpad_path, header).map_err(|e| {
                 HydraError::io(
-                    "--reset-plan requires a plan file argument",
-                    std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        "--reset-plan requires a plan file",
-                    ),
+                    format!("resetting scratchpad {}", scratchpad_path.display()),
+                    e,
                 )
             })?;
-
-            // Uncheck all checkboxes in the plan file
-            let plan_content = fs::read_to_string(plan_path)
-                .map_err(|e| HydraError::io(format!("reading plan {}", plan_path.display()), e))?;
-            let reset_content = plan_content
-                .replace("- [x]", "- [ ]")
-                .replace("- [X]", "- [ ]");
-            if reset_content != plan_content {
-                fs::write(plan_path, &reset_content).map_err(|e| {
-                    HydraError::io(format!("writing plan {}", plan_path.display()), e)
-                })?;
-            }
-
-            // Clear scratchpad if it exists
-            let plan_stem = plan_path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("scratchpad");
-            let scratchpad_path = Config::scratchpad_dir().join(format!("{}.md", plan_stem));
-            if scratchpad_path.exists() {
-                let header = format!(
-                    "# Scratchpad — {}\n\nCross-iteration notes for this plan.\n",
-                    plan_stem
-                );
-                fs::write(&scratchpad_path, header).map_err(|e| {
-                    HydraError::io(
-                        format!("resetting scratchpad {}", scratchpad_path.display()),
-                        e,
-                    )
-                })?;
-            }
-
-            println!("[hydra] Plan reset: unchecked all tasks, cleared scratchpad");
         }
 
-        // If a plan file is provided, inject a reference to it in the prompt
-        let mut scratchpad_path: Option<PathBuf> = None;
-        if let Some(ref plan_path) = cli.plan {
-            // Verify plan file exists
-            if !plan_path.exists() {
-                return Err(HydraError::PlanNotFound(plan_path.clone()));
-            }
-
-            if config.verbose {
-                eprintln!("Plan file: {}", plan_path.display());
-            }
-
-            // Inject plan path reference into prompt
-            resolved.content = inject_plan_path(&resolved.content, plan_path);
+        println!("[hydra] Plan reset: unchecked all tasks, cleared scratchpad");
+    }
 
-            // Create scratchpad file for cross-iteration notes
-            let scratchpad_dir = Config::scratchpad_dir();
-            if let Err(e) = fs::create_dir_all(&scratchpad_dir) {
-                eprintln!(
-                    "[hydra] Warning: Could not create scratchpad directory: {}",
-                    e
-                );
-            } else {
-                let plan_stem = plan_path
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("scratchpad");
-                let sp = scratchpad_dir.join(format!("{}.md", plan_stem));
-                if !sp.exists() {
-                    let header = format!(
-                        "# Scratchpad — {}\n\nCross-iteration notes for this plan.\n",
-                        plan_stem
-                    );
-                    if let Err(e) = fs::write(&sp, header) {
-                        eprintln!("[hydra] Warning: Could not create scratchpad file: {}", e);
-                    }
-                }
-                // Inject scratchpad path into prompt
-                resolved.content = inject_scratchpad_path(&resolved.content, &sp);
-                scratchpad_path = Some(sp);
-            }
+    let scratchpad_path = prepare_session_prompt(&mut resolved, plan)?;
+    if let Some(plan_path) = plan
+        && config.verbose
+    {
+        eprintln!("Plan file: {}", plan_path.display());
+    }
+    let question_path = question::question_path(&lock::session_name(plan));
+
+    // Templates are rendered before every iteration; rendering the first
+    // one now reports template errors before the session starts
+    let first_prompt = template::render_prompt(
+        &resolved,
+        &TemplateVars::collect(
+            plan,
+            scratchpad_path.as_deref(),
+            Some(&question_path),
+            1,
+            config.max_iterations,
+        ),
+    )?;
+
+    if cli.dry_run {
+        // Dry run: show configuration without executing
+        println!("Configuration (dry-run):");
+        println!("  max_iterations: {}", config.max_iterations);
+        println!(
+            "  timeout_seconds: {} ({} minutes)",
+            config.timeout_seconds,
+            config.timeout_seconds / 60
+        );
+        println!("  adaptive_timeout: {}", config.adaptive_timeout);
+        println!("  retries_per_task: {}", config.retries_per_task);
+        println!("  verbose: {}", config.verbose);
+        println!("  stop_file: {}", config.stop_file);
+        println!("  headless: {}", cli.headless);
+        if let Some(ref mode) = step_mode {
+            println!("  step: pause {}", mode.describe());
         }
-
-        if cli.dry_run {
-            // Dry run: show configuration without executing
-            println!("Configuration (dry-run):");
-            println!("  max_iterations: {}", config.max_iterations);
+        println!("  harness: {}", harness);
+        println!("  prompt_source: {}", resolved.source);
+        println!("  prompt_path: {}", resolved.path.display());
+        println!("  instructions_source: {}", instructions.source);
+        if let Some(ref path) = instructions.path {
+            println!("  instructions_path: {}", path.display());
+        }
+        if let Some(plan_path) = plan {
+            println!("  plan_path: {}", plan_path.display());
+        }
+        if template::is_template(&resolved.content) {
+            // Show the whole rendering so every placeholder can be checked
             println!(
-                "  timeout_seconds: {} ({} minutes)",
-                config.timeout_seconds,
-                config.timeout_seconds / 60
+                "\nPrompt content (template rendered for iteration 1, {} bytes):",
+                first_prompt.len()
             );
-            println!("  verbose: {}", config.verbose);
-            println!("  stop_file: {}", config.stop_file);
-            println!("  headless: {}", cli.headless);
-            println!("  harness: {}", harness);
-            println!("  prompt_source: {}", resolved.source);
-            println!("  prompt_path: {}", resolved.path.display());
-            if let Some(ref plan_path) = cli.plan {
-                println!("  plan_path: {}", plan_path.display());
-            }
+            println!("---");
+            println!("{}", first_prompt);
+        } else {
             println!("\nPrompt content ({} bytes):", resolved.content.len());
             println!("---");
             // Show first 500 chars of prompt for preview
@@ -234,115 +354,200 @@ fn run(cli: Cli) -> Result<()> {
             } else {
                 println!("{}", resolved.content);
             }
-            println!("---");
-            Ok(())
-        } else {
-            // Extract plan name from plan path (file stem without extension)
-            let plan_name = cli.plan.as_ref().and_then(|p| {
-                p.file_stem()
-                    .and_then(|s| s.to_str())
-                    .map(|s| s.to_string())
-            });
-
-            let result = if cli.headless {
-                // Headless mode: use harness print/pipe mode
-                let mut runner = HeadlessRunner::new(
-                    config.clone(),
-                    resolved,
-                    plan_name,
-                    scratchpad_path,
-                    harness,
-                );
+        }
+        println!("---");
+        Ok(None)
+    } else {
+        let result = if cli.headless {
+            // Headless mode: use harness print/pipe mode
+            let mut runner = HeadlessRunner::new(
+                config.clone(),
+                resolved,
+                plan.map(Path::to_path_buf),
+                scratchpad_path,
+                harness,
+            );
 
-                let stop_flag = runner.stop_flag();
-                if let Err(e) = signal::install_handlers(stop_flag) {
-                    eprintln!("[hydra] Warning: Failed to install signal handlers: {}", e);
+            match stop_flag {
+                Some(ref flag) => runner.set_stop_flag(Arc::clone(flag)),
+                None => {
+                    if let Err(e) = signal::install_handlers(runner.stop_flag()) {
+                        eprintln!("[hydra] Warning: Failed to install signal handlers: {}", e);
+                    }
                 }
+            }
 
-                runner.run()?
-            } else {
-                // PTY mode: interactive terminal
-                // Print banner and version
-                println!("{}", BANNER);
-                println!(
-                    "                                  hydra v{}",
-                    env!("CARGO_PKG_VERSION")
-                );
-                println!();
+            // Kept alive until run() returns; dropping it removes the socket
+            let control_server = start_control(plan, runner.stop_flag());
+            if let Some(ref control) = control_server {
+                runner.set_control(control.state());
+            }
+            if let Some(ref mode) = step_mode {
+                runner.set_step_mode(mode.clone());
+            }
+            runner.set_instructions(instructions.content);
 
-                // Print the prompt content so user knows what they're sending
-                println!("─── prompt ({}) ───", resolved.source);
-                println!();
-                for line in resolved.content.lines() {
-                    println!("  {}", line);
-                }
-                println!();
-                println!("─────────────────────────────────────────");
-                println!();
-
-                let mut runner = Runner::new(
-                    config.clone(),
-                    resolved,
-                    plan_name,
-                    scratchpad_path,
-                    harness,
-                );
+            runner.run()?
+        } else {
+            // PTY mode: interactive terminal
+            // Print banner and version
+            println!("{}", BANNER);
+            println!(
+                "                                  hydra v{}",
+                env!("CARGO_PKG_VERSION")
+            );
+            println!();
 
-                let stop_flag = runner.stop_flag();
-                if let Err(e) = signal::install_handlers(stop_flag) {
-                    eprintln!("[hydra] Warning: Failed to install signal handlers: {}", e);
-                }
+            // Print the prompt content so user knows what they're sending
+            println!("─── prompt ({}) ───", resolved.source);
+            println!();
+            for line in resolved.content.lines() {
+                println!("  {}", line);
+            }
+            println!();
+            println!("─────────────────────────────────────────");
+            println!();
+
+            let mut runner = Runner::new(
+                config.clone(),
+                resolved,
+                plan.map(Path::to_path_buf),
+                scratchpad_path,
+                harness,
+            );
 
-                runner.run()?
-            };
-
-            // Launch plan review if all tasks completed and a plan was provided (unless --no-review)
-            if matches!(result, RunResult::AllTasksComplete { .. })
-                && !cli.no_review
-                && let Some(ref plan_path) = cli.plan
-            {
-                println!();
-                println!("[hydra] Launching plan review...");
-                println!();
-
-                // Build the review prompt — scratchpad is read by the skill itself
-                let review_prompt = format!("/hydra-review {}", plan_path.display());
-                let temp_dir = std::env::temp_dir();
-                let review_file = temp_dir.join("hydra-plan-review.md");
-                if let Err(e) = fs::write(&review_file, &review_prompt) {
-                    eprintln!(
-                        "[hydra] Warning: Could not create review prompt file: {}",
-                        e
-                    );
-                } else if cli.headless {
-                    // Headless mode: run review via the harness in print
-                    // mode, save to .hydra/reviews/
-                    if let Err(e) = run_headless_review(&review_file, plan_path, harness) {
-                        eprintln!("[hydra] Warning: Plan review failed: {}", e);
+            match stop_flag {
+                Some(ref flag) => runner.set_stop_flag(Arc::clone(flag)),
+                None => {
+                    if let Err(e) = signal::install_handlers(runner.stop_flag()) {
+                        eprintln!("[hydra] Warning: Failed to install signal handlers: {}", e);
                     }
-                    let _ = fs::remove_file(&review_file);
-                } else {
-                    // Interactive plan review runs through the active
-                    // harness (claude or pi). Pi accepts the review prompt
-                    // via `@<file>`; claude via the usual skip-permissions
-                    // + "read instructions here" wrapper.
-                    if let Err(e) = spawn_claude_interactive(&review_file, config.verbose, harness)
-                    {
-                        eprintln!("[hydra] Warning: Plan review failed: {}", e);
-                    }
-                    let _ = fs::remove_file(&review_file);
                 }
             }
 
-            // Convert run result to appropriate exit
-            match result {
-                RunResult::AllTasksComplete { .. } => Ok(()),
-                RunResult::MaxIterations { .. } => Ok(()),
-                RunResult::Timeout { .. } => Ok(()), // Timeout is success - we just move to next iteration
-                RunResult::Stopped { .. } => Err(HydraError::GracefulStop),
-                RunResult::Interrupted => Err(HydraError::Interrupted),
+            // Kept alive until run() returns; dropping it removes the socket
+            let control_server = start_control(plan, runner.stop_flag());
+            if let Some(ref control) = control_server {
+                runner.set_control(control.state());
+            }
+            if let Some(ref mode) = step_mode {
+                runner.set_step_mode(mode.clone());
             }
+            runner.set_instructions(instructions.content);
+
+            runner.run()?
+        };
+
+        Ok(Some(result))
+    }
+}
+
+/// Review a plan whose tasks are all complete: an interactive session with
+/// the harness, or in headless mode a review saved to `.hydra/reviews/`
+pub(crate) fn review_plan(cli: &Cli, config: &Config, harness: Harness, plan_path: &Path) {
+    println!();
+    println!("[hydra] Launching plan review...");
+    println!();
+
+    // Build the review prompt — scratchpad is read by the skill itself
+    let review_prompt = format!("/hydra-review {}", plan_path.display());
+    let temp_dir = std::env::temp_dir();
+    let review_file = temp_dir.join("hydra-plan-review.md");
+    if let Err(e) = fs::write(&review_file, &review_prompt) {
+        eprintln!(
+            "[hydra] Warning: Could not create review prompt file: {}",
+            e
+        );
+    } else if cli.headless {
+        // Headless mode: run review via the harness in print mode, save to
+        // .hydra/reviews/
+        if let Err(e) = run_headless_review(&review_file, plan_path, harness) {
+            eprintln!("[hydra] Warning: Plan review failed: {}", e);
+        }
+        let _ = fs::remove_file(&review_file);
+    } else {
+        // Interactive plan review runs through the active harness (claude
+        // or pi). Pi accepts the review prompt via `@<file>`; claude via the
+        // usual skip-permissions + "read instructions here" wrapper.
+        if let Err(e) = spawn_claude_interactive(&review_file, config.verbose, harness) {
+            eprintln!("[hydra] Warning: Plan review failed: {}", e);
         }
+        let _ = fs::remove_file(&review_file);
+    }
+}
+
+/// Point the prompt at the plan, its scratchpad (created if missing) and the
+/// question file, unless the prompt template places them itself. Returns the
+/// scratchpad path.
+pub(crate) fn prepare_session_prompt(
+    resolved: &mut ResolvedPrompt,
+    plan: Option<&Path>,
+) -> Result<Option<PathBuf>> {
+    let mut scratchpad_path: Option<PathBuf> = None;
+    if let Some(plan_path) = plan {
+        // Verify plan file exists
+        if !plan_path.exists() {
+            return Err(HydraError::PlanNotFound(plan_path.to_path_buf()));
+        }
+
+        // Inject plan path reference into prompt, unless the prompt
+        // template places it itself
+        if !template::references(&resolved.content, "plan_path") {
+            resolved.content = inject_plan_path(&resolved.content, plan_path);
+        }
+
+        // Create scratchpad file for cross-iteration notes
+        let scratchpad_dir = Config::scratchpad_dir();
+        if let Err(e) = fs::create_dir_all(&scratchpad_dir) {
+            eprintln!(
+                "[hydra] Warning: Could not create scratchpad directory: {}",
+                e
+            );
+        } else {
+            let plan_stem = plan_path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("scratchpad");
+            let sp = scratchpad_dir.join(format!("{}.md", plan_stem));
+            if !sp.exists() {
+                let header = format!(
+                    "# Scratchpad — {}\n\nCross-iteration notes for this plan.\n",
+                    plan_stem
+                );
+                if let Err(e) = fs::write(&sp, header) {
+                    eprintln!("[hydra] Warning: Could not create scratchpad file: {}", e);
+                }
+            }
+            // Inject scratchpad path into prompt
+            if !template::references(&resolved.content, "scratchpad_path") {
+                resolved.content = inject_scratchpad_path(&resolved.content, &sp);
+            }
+            scratchpad_path = Some(sp);
+        }
+    }
+
+    // Tell the agent where to leave questions for the operator
+    if let Err(e) = fs::create_dir_all(Config::questions_dir()) {
+        eprintln!(
+            "[hydra] Warning: Could not create questions directory: {}",
+            e
+        );
+    }
+    let question_path = question::question_path(&lock::session_name(plan));
+    if !template::references(&resolved.content, "question_path") {
+        resolved.content = inject_question_path(&resolved.content, &question_path);
+    }
+    Ok(scratchpad_path)
+}
+
+/// Map a session result to hydra's exit status
+pub(crate) fn exit_result(result: &RunResult) -> Result<()> {
+    match result {
+        RunResult::AllTasksComplete { .. } => Ok(()),
+        RunResult::MaxIterations { .. } => Ok(()),
+        RunResult::Timeout { .. } => Ok(()), // Timeout is success - we just move to next iteration
+        RunResult::Stopped { .. } => Err(HydraError::GracefulStop),
+        RunResult::Interrupted => Err(HydraError::Interrupted),
     }
 }
 
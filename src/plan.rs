@@ -0,0 +1,275 @@
+//! Implementation plan parsing
+//!
+//! Plans are free-form Markdown where tasks are checkbox list items
+//! (`- [ ] task` / `- [x] task`). Checkboxes inside fenced code blocks are
+//! examples, not tasks, and are skipped.
+
+use crate::error::{HydraError, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory where project plans live by convention
+pub const PLANS_DIR: &str = "plans";
+
+/// A single checkbox task from a plan
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanTask {
+    /// 1-based line number in the plan file
+    pub line: usize,
+    /// Task text with the checkbox marker and any timeout hint removed
+    pub text: String,
+    /// Whether the checkbox is ticked
+    pub checked: bool,
+    /// Leading whitespace width (nested tasks have indent > 0)
+    pub indent: usize,
+    /// Per-task timeout from a `(timeout: 90m)` annotation, in seconds
+    pub timeout_hint: Option<u64>,
+}
+
+/// A parsed implementation plan
+#[derive(Debug, Clone)]
+pub struct Plan {
+    /// Checkbox tasks in document order
+    pub tasks: Vec<PlanTask>,
+}
+
+impl Plan {
+    /// Read and parse a plan file
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Err(HydraError::PlanNotFound(path.to_path_buf()));
+        }
+        let content = fs::read_to_string(path)
+            .map_err(|e| HydraError::io(format!("reading plan {}", path.display()), e))?;
+        Ok(Self::parse(&content))
+    }
+
+    /// Parse plan content
+    pub fn parse(content: &str) -> Self {
+        let mut tasks = Vec::new();
+        let mut in_fence = false;
+
+        for (idx, line) in content.lines().enumerate() {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+                continue;
+            }
+            if in_fence {
+                continue;
+            }
+            if let Some((checked, rest)) = parse_checkbox(trimmed) {
+                let (text, timeout_hint) = extract_timeout_hint(rest);
+                tasks.push(PlanTask {
+                    line: idx + 1,
+                    text,
+                    checked,
+                    indent: line.len() - trimmed.len(),
+                    timeout_hint,
+                });
+            }
+        }
+
+        Self { tasks }
+    }
+
+    /// Number of ticked tasks
+    pub fn checked_count(&self) -> usize {
+        self.tasks.iter().filter(|t| t.checked).count()
+    }
+
+    /// Number of unticked tasks
+    pub fn unchecked_count(&self) -> usize {
+        self.tasks.iter().filter(|t| !t.checked).count()
+    }
+
+    /// First unticked task in document order
+    pub fn next_unchecked(&self) -> Option<&PlanTask> {
+        self.tasks.iter().find(|t| !t.checked)
+    }
+}
+
+/// Markdown files in the project's plans directory, sorted by path
+pub fn discover_plans(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut plans: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "md"))
+        .collect();
+    plans.sort();
+    plans
+}
+
+/// Plan name used for scratchpads, logs and history (file stem, falling
+/// back to `plan`)
+pub fn plan_name(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("plan")
+        .to_string()
+}
+
+/// Parse a `- [ ] text` / `* [x] text` list item, returning (checked, text)
+pub fn parse_checkbox(line: &str) -> Option<(bool, &str)> {
+    let rest = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .or_else(|| line.strip_prefix("+ "))?;
+    let checked = if rest.starts_with("[ ]") {
+        false
+    } else if rest.starts_with("[x]") || rest.starts_with("[X]") {
+        true
+    } else {
+        return None;
+    };
+    Some((checked, rest[3..].trim()))
+}
+
+/// Split a `(timeout: 90m)` annotation out of a task's text
+fn extract_timeout_hint(text: &str) -> (String, Option<u64>) {
+    let Some(start) = text.find("(timeout:") else {
+        return (text.to_string(), None);
+    };
+    let Some(len) = text[start..].find(')') else {
+        return (text.to_string(), None);
+    };
+    let value = text[start + "(timeout:".len()..start + len].trim();
+    let Some(seconds) = parse_duration(value) else {
+        return (text.to_string(), None);
+    };
+    let stripped = format!("{}{}", &text[..start], &text[start + len + 1..]);
+    (
+        stripped.split_whitespace().collect::<Vec<_>>().join(" "),
+        Some(seconds),
+    )
+}
+
+/// Parse a duration like `90m`, `1h30m`, `45s` or `600` (seconds). Zero and
+/// durations too long to count in seconds are rejected.
+pub fn parse_duration(value: &str) -> Option<u64> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    let total = match value.parse::<u64>() {
+        Ok(secs) => secs,
+        Err(_) => {
+            let mut total = 0u64;
+            let mut digits = String::new();
+            for c in value.chars() {
+                if c.is_ascii_digit() {
+                    digits.push(c);
+                    continue;
+                }
+                let n: u64 = digits.parse().ok()?;
+                digits.clear();
+                let unit = match c {
+                    'h' => 3600,
+                    'm' => 60,
+                    's' => 1,
+                    _ => return None,
+                };
+                total = total.checked_add(n.checked_mul(unit)?)?;
+            }
+            if !digits.is_empty() {
+                return None;
+            }
+            total
+        }
+    };
+    (total > 0).then_some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "# Plan\n\
+        \n\
+        ## Tasks\n\
+        - [x] Set up project\n\
+        - [ ] Implement parser (timeout: 90m)\n\
+        \x20 - [ ] Nested detail\n\
+        * [X] Alternative bullet\n\
+        - not a task\n\
+        ```\n\
+        - [ ] example inside fence\n\
+        ```\n\
+        - [ ] Write docs\n";
+
+    #[test]
+    fn test_parse_tasks() {
+        let plan = Plan::parse(SAMPLE);
+        let texts: Vec<&str> = plan.tasks.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "Set up project",
+                "Implement parser",
+                "Nested detail",
+                "Alternative bullet",
+                "Write docs"
+            ]
+        );
+        assert_eq!(plan.checked_count(), 2);
+        assert_eq!(plan.unchecked_count(), 3);
+        assert_eq!(plan.tasks[1].line, 5);
+        assert_eq!(plan.tasks[2].indent, 2);
+        assert_eq!(plan_name(Path::new("plans/sample.md")), "sample");
+    }
+
+    #[test]
+    fn test_next_unchecked_and_hint() {
+        let plan = Plan::parse(SAMPLE);
+        let next = plan.next_unchecked().unwrap();
+        assert_eq!(next.text, "Implement parser");
+        assert_eq!(next.timeout_hint, Some(90 * 60));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90m"), Some(5400));
+        assert_eq!(parse_duration("1h30m"), Some(5400));
+        assert_eq!(parse_duration("45s"), Some(45));
+        assert_eq!(parse_duration("600"), Some(600));
+        assert_eq!(parse_duration("2h"), Some(7200));
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("ten minutes"), None);
+        assert_eq!(parse_duration("10"), Some(10));
+        assert_eq!(parse_duration("5x"), None);
+        assert_eq!(parse_duration("0"), None);
+        assert_eq!(parse_duration("0m0s"), None);
+        assert_eq!(parse_duration("9999999999999999h"), None);
+        assert_eq!(parse_duration("18446744073709551615s1s"), None);
+    }
+
+    #[test]
+    fn test_invalid_timeout_hint_is_left_in_text() {
+        let (text, hint) = extract_timeout_hint("Do it (timeout: soon)");
+        assert_eq!(text, "Do it (timeout: soon)");
+        assert_eq!(hint, None);
+    }
+
+    #[test]
+    fn test_discover_plans() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("b.md"), "- [ ] b\n").unwrap();
+        fs::write(dir.path().join("a.md"), "- [ ] a\n").unwrap();
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+        fs::create_dir(dir.path().join("sub.md")).unwrap();
+
+        let plans = discover_plans(dir.path());
+        let names: Vec<String> = plans.iter().map(|p| plan_name(p)).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert!(discover_plans(&dir.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn test_load_missing_plan() {
+        let err = Plan::load(Path::new("/nonexistent/plan.md")).unwrap_err();
+        assert!(matches!(err, HydraError::PlanNotFound(_)));
+    }
+}
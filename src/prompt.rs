@@ -1,10 +1,14 @@
 use crate::config::Config;
 use crate::error::{HydraError, Result};
+use crate::history;
+use crate::runner::IterationResult;
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
 
 /// Result of prompt resolution
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResolvedPrompt {
     /// The path where the prompt was found
     pub path: PathBuf,
@@ -15,7 +19,7 @@ pub struct ResolvedPrompt {
 }
 
 /// Source of the resolved prompt
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PromptSource {
     /// CLI --prompt flag (highest priority)
     CliOverride,
@@ -127,6 +131,76 @@ pub fn inject_scratchpad_path(prompt_content: &str, scratchpad_path: &std::path:
     )
 }
 
+/// Inject question file path reference into prompt content
+///
+/// Appends a `## Questions` section with the file path to the prompt.
+/// Returns the combined content.
+pub fn inject_question_path(prompt_content: &str, question_path: &std::path::Path) -> String {
+    format!(
+        "{}\n\n## Questions\n\nQuestion file (see QUESTIONS in the instructions): {}",
+        prompt_content.trim_end(),
+        question_path.display()
+    )
+}
+
+/// Outcome of the previous attempt, carried into the next prompt
+#[derive(Debug, Clone)]
+pub struct PreviousIteration {
+    pub result: IterationResult,
+    pub elapsed: Duration,
+}
+
+/// Dynamic state for one iteration's prompt
+#[derive(Debug)]
+pub struct IterationContext<'a> {
+    pub iteration: u32,
+    pub max_iterations: u32,
+    pub timeout_seconds: u64,
+    pub next_task: Option<&'a str>,
+    pub previous: Option<&'a PreviousIteration>,
+}
+
+/// Build the `## This Iteration` section, rebuilt before every iteration so
+/// the agent starts with current state instead of re-deriving it
+pub fn iteration_context_section(ctx: &IterationContext) -> String {
+    let mut lines = vec![
+        format!("- Iteration: {} of {}", ctx.iteration, ctx.max_iterations),
+        format!(
+            "- Time limit: {}; finish and emit a stop sequence before then",
+            history::format_duration(ctx.timeout_seconds)
+        ),
+    ];
+    if let Some(task) = ctx.next_task {
+        lines.push(format!("- First unchecked task in the plan: {}", task));
+    }
+    if let Some(prev) = ctx.previous {
+        let took = history::format_duration(prev.elapsed.as_secs());
+        lines.push(match prev.result {
+            IterationResult::TaskComplete => {
+                format!("- Previous iteration: completed a task in {}", took)
+            }
+            IterationResult::Timeout => format!(
+                "- Previous iteration: hit its time limit after {}. Check the scratchpad \
+                 for its timeout note, resume the interrupted work and keep the scope small.",
+                took
+            ),
+            IterationResult::NoSignal => format!(
+                "- Previous iteration: ended after {} without a stop sequence. Check what \
+                 it actually finished before continuing.",
+                took
+            ),
+            IterationResult::Question => format!(
+                "- Previous iteration: asked a question after {} (answer below)",
+                took
+            ),
+            IterationResult::AllComplete | IterationResult::Terminated => {
+                format!("- Previous iteration: ended after {}", took)
+            }
+        });
+    }
+    format!("## This Iteration\n\n{}", lines.join("\n"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -137,6 +211,37 @@ mod tests {
     // Mutex to serialize tests that change current directory
     static DIR_LOCK: Mutex<()> = Mutex::new(());
 
+    #[test]
+    fn test_iteration_context_section() {
+        let first = iteration_context_section(&IterationContext {
+            iteration: 1,
+            max_iterations: 20,
+            timeout_seconds: 3000,
+            next_task: Some("Add login endpoint"),
+            previous: None,
+        });
+        assert!(first.starts_with("## This Iteration\n\n- Iteration: 1 of 20\n"));
+        assert!(first.contains("Time limit: 50m"));
+        assert!(first.contains("First unchecked task in the plan: Add login endpoint"));
+        assert!(!first.contains("Previous iteration"));
+
+        let previous = PreviousIteration {
+            result: IterationResult::Timeout,
+            elapsed: Duration::from_secs(3000),
+        };
+        let second = iteration_context_section(&IterationContext {
+            iteration: 2,
+            max_iterations: 20,
+            timeout_seconds: 3000,
+            next_task: None,
+            previous: Some(&previous),
+        });
+        assert!(second.contains("Previous iteration: hit its time limit after 50m"));
+        assert!(!second.contains("First unchecked task"));
+        // Never echo the literal stop signals back to the agent
+        assert!(!second.contains("###"));
+    }
+
     #[test]
     fn test_prompt_source_display() {
         assert_eq!(PromptSource::CliOverride.to_string(), "CLI --prompt flag");
@@ -3,7 +3,7 @@ use crate::harness::Harness;
 use crate::signal::{clear_child_pid, set_child_pid};
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
 use crossterm::terminal::{self, disable_raw_mode, enable_raw_mode};
-use portable_pty::{Child, CommandBuilder, PtyPair, PtySize, native_pty_system};
+use portable_pty::{Child, CommandBuilder, ExitStatus, PtyPair, PtySize, native_pty_system};
 use std::fs::{File, OpenOptions};
 use std::io::{self, Read, Write};
 use std::path::Path;
@@ -113,10 +113,12 @@ fn restore_terminal(_verbose: bool) {
 /// Stop signals that Claude outputs to indicate task completion
 const TASK_COMPLETE_SIGNAL: &str = "###TASK_COMPLETE###";
 const ALL_COMPLETE_SIGNAL: &str = "###ALL_TASKS_COMPLETE###";
+const QUESTION_SIGNAL: &str = crate::question::QUESTION_SIGNAL;
 
 /// ASCII byte patterns for raw signal detection (fallback when UTF-8 fails)
 const TASK_COMPLETE_BYTES: &[u8] = b"###TASK_COMPLETE###";
 const ALL_COMPLETE_BYTES: &[u8] = b"###ALL_TASKS_COMPLETE###";
+const QUESTION_BYTES: &[u8] = QUESTION_SIGNAL.as_bytes();
 
 /// Buffer retention size after truncation (16KB to handle split signals)
 const BUFFER_RETENTION_SIZE: usize = 16384;
@@ -153,6 +155,8 @@ pub enum PtyResult {
     Terminated,
     /// Iteration timed out
     Timeout,
+    /// Question signal detected
+    Question,
 }
 
 /// Messages sent from the PTY reader thread
@@ -271,12 +275,6 @@ impl PtyManager {
         Ok(())
     }
 
-    /// Backwards-compatible shim that spawns Claude specifically. Prefer
-    /// [`PtyManager::spawn_harness`] in new code.
-    pub fn spawn_claude(&mut self, prompt_path: &Path) -> Result<()> {
-        self.spawn_harness(Harness::Claude, prompt_path)
-    }
-
     /// Run the I/O loop, handling input/output and watching for signals
     pub fn run_io_loop(
         &mut self,
@@ -502,6 +500,11 @@ impl PtyManager {
                                     "[hydra] Task complete signal detected, terminating Claude process..."
                                 );
                             }
+                            PtyResult::Question => {
+                                println!(
+                                    "[hydra] Question signal detected, terminating Claude process..."
+                                );
+                            }
                             _ => {}
                         }
                         self.terminate_child();
@@ -609,6 +612,12 @@ impl PtyManager {
             }
             return PtyResult::TaskComplete;
         }
+        if bytes_contain_signal(accumulator, QUESTION_BYTES) {
+            if verbose {
+                eprintln!("[hydra:debug] Signal found via raw byte search: QUESTION");
+            }
+            return PtyResult::Question;
+        }
 
         // Strategy 2: Strip ANSI codes and check (handles interspersed escape sequences)
         let clean = strip_ansi_escapes_from_bytes(accumulator);
@@ -625,6 +634,12 @@ impl PtyManager {
             }
             return PtyResult::TaskComplete;
         }
+        if clean.contains(QUESTION_SIGNAL) {
+            if verbose {
+                eprintln!("[hydra:debug] Signal found after ANSI stripping: QUESTION");
+            }
+            return PtyResult::Question;
+        }
 
         PtyResult::NoSignal
     }
@@ -652,6 +667,12 @@ impl PtyManager {
         Ok((reader, writer))
     }
 
+    /// The child's exit status once it has exited. TUI mode polls this: the
+    /// manager keeps the PTY open, so the reader sees no EOF when it exits.
+    pub fn try_wait(&mut self) -> Option<ExitStatus> {
+        self.child.as_mut()?.try_wait().ok().flatten()
+    }
+
     /// Terminate the child process gracefully
     pub fn terminate_child(&self) {
         if let Some(pid) = self.child_pid {
@@ -909,6 +930,10 @@ mod tests {
             manager.check_for_signals_in_bytes(b"###ALL_TASKS_COMPLETE###", false),
             PtyResult::AllComplete
         );
+        assert_eq!(
+            manager.check_for_signals_in_bytes(b"\x1b[1m###QUESTION###\x1b[0m", false),
+            PtyResult::Question
+        );
         assert_eq!(
             manager.check_for_signals_in_bytes(b"no signals here", false),
             PtyResult::NoSignal
@@ -0,0 +1,204 @@
+//! Question channel between the agent and the operator
+//!
+//! Iterations run unattended, so the agent can't ask questions directly.
+//! Instead it writes the question to `.hydra/questions/<plan>.md` and ends
+//! its output with the question signal. Hydra then pauses the loop, shows the
+//! question, records the answer in the scratchpad and injects it into the
+//! next iteration's prompt. Answered questions are archived under
+//! `.hydra/questions/answered/`.
+
+use crate::config::Config;
+use crate::error::{HydraError, Result};
+use chrono::Local;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// Signal the agent emits after writing its question file
+pub const QUESTION_SIGNAL: &str = "###QUESTION###";
+
+/// Pending question file for a session (./.hydra/questions/<name>.md)
+pub fn question_path(session_name: &str) -> PathBuf {
+    Config::questions_dir().join(format!("{}.md", session_name))
+}
+
+/// Read the pending question, if the agent wrote a non-empty one
+pub fn read_question(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let question = content.trim();
+    (!question.is_empty()).then(|| question.to_string())
+}
+
+/// Show the question and read the operator's answer: lines up to the first
+/// empty line. Returns `None` when no answer was given.
+pub fn ask(question: &str) -> Result<Option<String>> {
+    // Bell so an operator in another window notices
+    print!("\x07");
+    println!();
+    println!("─── question from the agent ───");
+    for line in question.lines() {
+        println!("  {}", line);
+    }
+    println!("───────────────────────────────");
+    println!("[hydra] Type your answer, then an empty line (empty answer: agent decides)");
+    io::stdout()
+        .flush()
+        .map_err(|e| HydraError::io("flushing stdout", e))?;
+
+    let mut lines = Vec::new();
+    for line in io::stdin().lock().lines() {
+        let line = line.map_err(|e| HydraError::io("reading answer", e))?;
+        if line.trim().is_empty() {
+            break;
+        }
+        lines.push(line);
+    }
+    let answer = lines.join("\n");
+    Ok((!answer.trim().is_empty()).then_some(answer))
+}
+
+/// Prompt section carrying the answer into the next iteration
+pub fn answer_note(question: &str, answer: Option<&str>) -> String {
+    let answer = answer.unwrap_or(
+        "(no answer given: make a reasonable decision yourself and note it in the scratchpad)",
+    );
+    format!(
+        "## Answer to Your Question\n\n\
+         In the previous iteration you asked:\n\n{}\n\n\
+         The operator answered:\n\n{}",
+        quote(question),
+        quote(answer)
+    )
+}
+
+/// Append the question and answer to the scratchpad
+pub fn record_in_scratchpad(
+    scratchpad_path: Option<&Path>,
+    iteration: u32,
+    question: &str,
+    answer: Option<&str>,
+) {
+    let Some(path) = scratchpad_path else {
+        return;
+    };
+    let entry = format!(
+        "\n[iter {} — Question]\nQ: {}\nA: {}\n",
+        iteration,
+        one_line(question),
+        answer.map_or_else(|| "(no answer, agent decides)".to_string(), one_line)
+    );
+    let appended = OpenOptions::new()
+        .append(true)
+        .open(path)
+        .and_then(|mut f| f.write_all(entry.as_bytes()));
+    if let Err(e) = appended {
+        eprintln!(
+            "[hydra] Warning: Could not record answer in scratchpad: {}",
+            e
+        );
+    }
+}
+
+/// Move an answered question to `answered/`, with the answer appended
+pub fn archive(path: &Path, question: &str, answer: Option<&str>) -> Result<()> {
+    let dir = path.parent().unwrap_or(Path::new(".")).join("answered");
+    fs::create_dir_all(&dir)
+        .map_err(|e| HydraError::io(format!("creating {}", dir.display()), e))?;
+
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("question");
+    let archived = dir.join(format!(
+        "{}-{}.md",
+        stem,
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    let content = format!(
+        "{}\n\n---\n\nAnswer: {}\n",
+        question,
+        answer.unwrap_or("(none)")
+    );
+    fs::write(&archived, content)
+        .map_err(|e| HydraError::io(format!("writing {}", archived.display()), e))?;
+    fs::remove_file(path).map_err(|e| HydraError::io(format!("removing {}", path.display()), e))
+}
+
+/// Join the answer note and a retry note into one prompt section
+pub fn prompt_context(answer_note: Option<&str>, retry_note: Option<&str>) -> Option<String> {
+    match (answer_note, retry_note) {
+        (Some(a), Some(r)) => Some(format!("{}\n\n{}", a, r)),
+        (Some(note), None) | (None, Some(note)) => Some(note.to_string()),
+        (None, None) => None,
+    }
+}
+
+fn one_line(text: &str) -> String {
+    text.lines().map(str::trim).collect::<Vec<_>>().join(" ")
+}
+
+fn quote(text: &str) -> String {
+    text.lines()
+        .map(|l| format!("> {}", l))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read_question() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("plan.md");
+        assert_eq!(read_question(&path), None);
+
+        fs::write(&path, "  \n").unwrap();
+        assert_eq!(read_question(&path), None);
+
+        fs::write(&path, "Use Postgres or SQLite?\n").unwrap();
+        assert_eq!(
+            read_question(&path).as_deref(),
+            Some("Use Postgres or SQLite?")
+        );
+    }
+
+    #[test]
+    fn test_answer_note() {
+        let note = answer_note("Which database?", Some("SQLite"));
+        assert!(note.starts_with("## Answer to Your Question"));
+        assert!(note.contains("> Which database?"));
+        assert!(note.contains("> SQLite"));
+
+        assert!(answer_note("Which database?", None).contains("make a reasonable decision"));
+    }
+
+    #[test]
+    fn test_record_and_archive() {
+        let dir = TempDir::new().unwrap();
+        let scratchpad = dir.path().join("scratchpad.md");
+        fs::write(&scratchpad, "# Scratchpad\n").unwrap();
+        record_in_scratchpad(Some(&scratchpad), 3, "Which\ndatabase?", Some("SQLite"));
+        let content = fs::read_to_string(&scratchpad).unwrap();
+        assert!(content.contains("[iter 3 — Question]\nQ: Which database?\nA: SQLite\n"));
+
+        let question = dir.path().join("plan.md");
+        fs::write(&question, "Which database?").unwrap();
+        archive(&question, "Which database?", Some("SQLite")).unwrap();
+        assert!(!question.exists());
+        let archived: Vec<_> = fs::read_dir(dir.path().join("answered")).unwrap().collect();
+        assert_eq!(archived.len(), 1);
+    }
+
+    #[test]
+    fn test_prompt_context() {
+        assert_eq!(prompt_context(None, None), None);
+        assert_eq!(prompt_context(Some("a"), None).as_deref(), Some("a"));
+        assert_eq!(
+            prompt_context(Some("a"), Some("r")).as_deref(),
+            Some("a\n\nr")
+        );
+    }
+}
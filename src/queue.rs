@@ -0,0 +1,238 @@
+//! Plan queue: several plans run one after another in one invocation
+//!
+//! `hydra a.md b.md` or `hydra --queue plans/` runs each plan as its own
+//! session (own lock, scratchpad, log and control socket) and moves on when
+//! the plan reports all tasks complete. The plans share `--max`: each one gets
+//! the iterations the previous ones left over. Any other ending (stop,
+//! interrupt, budget used up) ends the queue. A combined summary is printed
+//! at the end, followed by the reviews of the completed plans, so an
+//! interactive review never holds up the plans after it.
+
+use crate::cli::Cli;
+use crate::config::Config;
+use crate::error::{HydraError, Result};
+use crate::harness::Harness;
+use crate::history;
+use crate::lint;
+use crate::plan::Plan;
+use crate::retry;
+use crate::runner::RunResult;
+use crate::signal;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// How one plan of the queue ended
+#[derive(Debug)]
+struct QueueEntry {
+    plan: PathBuf,
+    result: RunResult,
+    elapsed: Duration,
+}
+
+/// Run `plans` in order; see the module docs
+pub fn run_queue(cli: &Cli, config: &Config, harness: Harness, plans: &[PathBuf]) -> Result<()> {
+    // Check every plan up front: a mistake in the last plan shouldn't
+    // surface hours into the queue
+    for plan in plans {
+        if cli.no_lint {
+            if !plan.exists() {
+                return Err(HydraError::PlanNotFound(plan.clone()));
+            }
+        } else {
+            lint::report(plan, &lint::lint_plan(plan)?)?;
+        }
+    }
+
+    if cli.dry_run {
+        for (idx, plan) in plans.iter().enumerate() {
+            println!("Queue {}/{}: {}", idx + 1, plans.len(), plan.display());
+            crate::run_session(cli, config, harness, Some(plan), None)?;
+            println!();
+        }
+        return Ok(());
+    }
+
+    // Signal handlers can only be installed once per process, so every
+    // session of the queue shares this flag
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    if let Err(e) = signal::install_handlers(Arc::clone(&stop_flag)) {
+        eprintln!("[hydra] Warning: Failed to install signal handlers: {}", e);
+    }
+
+    let started = Instant::now();
+    let mut remaining = config.max_iterations;
+    let mut entries: Vec<QueueEntry> = Vec::new();
+
+    for (idx, plan) in plans.iter().enumerate() {
+        if remaining == 0 || signal::received() {
+            break;
+        }
+        println!(
+            "[hydra] Queue {}/{}: {} ({} iteration(s) left)",
+            idx + 1,
+            plans.len(),
+            plan.display(),
+            remaining
+        );
+
+        let mut plan_config = config.clone();
+        plan_config.max_iterations = remaining;
+        // The previous session may have left the flag set while tearing
+        // down its last iteration
+        stop_flag.store(false, Ordering::SeqCst);
+
+        let plan_started = Instant::now();
+        let result = match crate::run_session(
+            cli,
+            &plan_config,
+            harness,
+            Some(plan),
+            Some(Arc::clone(&stop_flag)),
+        ) {
+            Ok(Some(result)) => result,
+            Ok(None) => break,
+            Err(e) => {
+                print_summary(plans, &entries, started.elapsed());
+                return Err(e);
+            }
+        };
+
+        remaining = remaining.saturating_sub(result.iterations());
+        let complete = matches!(result, RunResult::AllTasksComplete { .. });
+        entries.push(QueueEntry {
+            plan: plan.clone(),
+            result,
+            elapsed: plan_started.elapsed(),
+        });
+        if !complete {
+            break;
+        }
+    }
+
+    print_summary(plans, &entries, started.elapsed());
+    if !cli.no_review && !signal::received() {
+        for entry in &entries {
+            if matches!(entry.result, RunResult::AllTasksComplete { .. }) {
+                crate::review_plan(cli, config, harness, &entry.plan);
+            }
+        }
+    }
+    match entries.last() {
+        Some(entry) => crate::exit_result(&entry.result),
+        None => Ok(()),
+    }
+}
+
+fn print_summary(plans: &[PathBuf], entries: &[QueueEntry], elapsed: Duration) {
+    println!();
+    for line in summary_lines(plans, entries, elapsed) {
+        println!("{}", line);
+    }
+}
+
+/// Combined summary: one line per plan, with task progress read from disk
+fn summary_lines(plans: &[PathBuf], entries: &[QueueEntry], elapsed: Duration) -> Vec<String> {
+    let iterations: u32 = entries.iter().map(|e| e.result.iterations()).sum();
+    let retries: u32 = entries.iter().map(|e| e.result.retries()).sum();
+    let completed = entries
+        .iter()
+        .filter(|e| matches!(e.result, RunResult::AllTasksComplete { .. }))
+        .count();
+
+    let mut lines = vec![format!(
+        "[hydra] Queue summary: {}/{} plan(s) complete, {} iteration(s){}, {}",
+        completed,
+        plans.len(),
+        iterations,
+        retry::retries_suffix(retries),
+        history::format_duration(elapsed.as_secs())
+    )];
+
+    for plan in plans {
+        let progress = task_progress(plan);
+        lines.push(match entries.iter().find(|e| e.plan == *plan) {
+            Some(entry) => format!(
+                "  {}  {}, {} iteration(s) in {}{}",
+                plan.display(),
+                outcome(&entry.result),
+                entry.result.iterations(),
+                history::format_duration(entry.elapsed.as_secs()),
+                progress
+            ),
+            None => format!("  {}  not started{}", plan.display(), progress),
+        });
+    }
+    lines
+}
+
+fn outcome(result: &RunResult) -> &'static str {
+    match result {
+        RunResult::AllTasksComplete { .. } => "complete",
+        RunResult::MaxIterations { .. } => "iteration budget used up",
+        RunResult::Timeout { .. } => "timed out",
+        RunResult::Stopped { .. } => "stopped",
+        RunResult::Interrupted => "interrupted",
+    }
+}
+
+fn task_progress(plan: &Path) -> String {
+    match Plan::load(plan) {
+        Ok(p) => format!(
+            ", {}/{} tasks done",
+            p.checked_count(),
+            p.checked_count() + p.unchecked_count()
+        ),
+        Err(_) => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_summary_lines() {
+        let dir = TempDir::new().unwrap();
+        let a = dir.path().join("a.md");
+        let b = dir.path().join("b.md");
+        let c = dir.path().join("c.md");
+        fs::write(&a, "- [x] one\n- [x] two\n").unwrap();
+        fs::write(&b, "- [x] one\n- [ ] two\n").unwrap();
+        fs::write(&c, "- [ ] one\n").unwrap();
+
+        let entries = vec![
+            QueueEntry {
+                plan: a.clone(),
+                result: RunResult::AllTasksComplete {
+                    iterations: 2,
+                    retries: 1,
+                },
+                elapsed: Duration::from_secs(600),
+            },
+            QueueEntry {
+                plan: b.clone(),
+                result: RunResult::Stopped {
+                    iterations: 1,
+                    retries: 0,
+                },
+                elapsed: Duration::from_secs(120),
+            },
+        ];
+        let lines = summary_lines(
+            &[a.clone(), b.clone(), c.clone()],
+            &entries,
+            Duration::from_secs(720),
+        );
+        assert_eq!(
+            lines[0],
+            "[hydra] Queue summary: 1/3 plan(s) complete, 3 iteration(s) (1 retry), 12m"
+        );
+        assert!(lines[1].ends_with("complete, 2 iteration(s) in 10m, 2/2 tasks done"));
+        assert!(lines[2].ends_with("stopped, 1 iteration(s) in 2m, 1/2 tasks done"));
+        assert!(lines[3].ends_with("not started, 0/1 tasks done"));
+    }
+}
@@ -0,0 +1,406 @@
+//! Retry policy for failed or timed-out iterations
+//!
+//! An iteration that times out or ends without a stop signal can be re-run
+//! with a note explaining why the previous attempt failed. Retries are
+//! counted separately from the `max_iterations` budget.
+
+use crate::config::Config;
+use crate::runner::IterationResult;
+use crate::step::{self, StepAction};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// How often the backoff sleep checks the stop flag
+const BACKOFF_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Upper bound for the exponential backoff between retries
+const MAX_BACKOFF_SECONDS: u64 = 600;
+
+/// Retry settings resolved from the config
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Number of extra attempts allowed for a failed iteration (0 disables retries)
+    pub retries_per_task: u32,
+    /// Delay before the first retry; doubled for every further retry
+    pub backoff_seconds: u64,
+    /// Factor applied to the timeout on each retry (1.0 keeps it unchanged)
+    pub timeout_multiplier: f64,
+}
+
+impl RetryPolicy {
+    /// Build the policy from the loaded config
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            retries_per_task: config.retries_per_task,
+            backoff_seconds: config.retry_backoff_seconds,
+            timeout_multiplier: config.retry_timeout_multiplier,
+        }
+    }
+
+    /// Whether an iteration result counts as a failed attempt
+    pub fn is_failure(result: &IterationResult) -> bool {
+        matches!(result, IterationResult::Timeout | IterationResult::NoSignal)
+    }
+
+    /// Whether a failed attempt should be retried, given how many retries
+    /// this iteration has already used
+    pub fn should_retry(&self, result: &IterationResult, retries_used: u32) -> bool {
+        Self::is_failure(result) && retries_used < self.retries_per_task
+    }
+
+    /// Delay before the given retry (1-based), doubling each time
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u64.saturating_pow(retry.saturating_sub(1));
+        let secs = self
+            .backoff_seconds
+            .saturating_mul(factor)
+            .min(MAX_BACKOFF_SECONDS.max(self.backoff_seconds));
+        Duration::from_secs(secs)
+    }
+
+    /// Timeout for the given retry (0 = first attempt)
+    pub fn timeout_for(&self, base_seconds: u64, retry: u32) -> u64 {
+        if retry == 0 || self.timeout_multiplier <= 1.0 {
+            return base_seconds;
+        }
+        let scaled = base_seconds as f64 * self.timeout_multiplier.powi(retry as i32);
+        scaled.min(u64::MAX as f64).round() as u64
+    }
+}
+
+/// Prompt section injected into a retried iteration explaining why the
+/// previous attempt failed
+pub fn retry_note(
+    failed: &IterationResult,
+    timeout_seconds: u64,
+    log_path: Option<&str>,
+) -> String {
+    let reason = match failed {
+        IterationResult::Timeout => format!(
+            "it was terminated after reaching the {}s timeout without emitting a stop sequence",
+            timeout_seconds
+        ),
+        _ => "the agent exited without emitting a stop sequence".to_string(),
+    };
+    let mut note = format!(
+        "## Previous Attempt Failed\n\n\
+         This is a retry of the same iteration. The previous attempt failed because {}.\n\
+         Check the plan and scratchpad for partial progress, then finish the task. \
+         Keep the scope small enough to complete within the time limit.",
+        reason
+    );
+    if let Some(path) = log_path {
+        note.push_str(&format!(
+            "\nThe output of the failed attempt is in the session log: {}",
+            path
+        ));
+    }
+    note
+}
+
+/// Short description of a failed attempt for console and log output
+pub fn failure_reason(result: &IterationResult) -> &'static str {
+    match result {
+        IterationResult::Timeout => "timed out",
+        IterationResult::NoSignal => "no stop signal",
+        _ => "not a failure",
+    }
+}
+
+/// Suffix appended to end-of-session summaries, e.g. ` (2 retries)`
+pub fn retries_suffix(retries: u32) -> String {
+    match retries {
+        0 => String::new(),
+        1 => " (1 retry)".to_string(),
+        n => format!(" ({} retries)", n),
+    }
+}
+
+/// Sleep for `delay`, returning early with `false` if `should_stop` is set
+pub fn wait_backoff(delay: Duration, should_stop: &AtomicBool) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < delay {
+        if should_stop.load(Ordering::SeqCst) {
+            return false;
+        }
+        std::thread::sleep(BACKOFF_POLL_INTERVAL.min(delay.saturating_sub(start.elapsed())));
+    }
+    !should_stop.load(Ordering::SeqCst)
+}
+
+/// An attempt at an iteration, as the retry decision sees it
+pub struct Attempt<'a> {
+    pub iteration: u32,
+    pub result: &'a IterationResult,
+    /// Timeout the attempt ran with
+    pub timeout: u64,
+    /// Session log holding the attempt's output
+    pub log_path: Option<&'a str>,
+}
+
+/// What the run loop does after an attempt
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NextStep {
+    /// Act on the attempt's result
+    Finish,
+    /// Run the iteration again, with `Retries::context` in the prompt
+    Retry,
+    /// The operator quit from the step review
+    Quit,
+    /// A stop request arrived while waiting to retry
+    Stopped,
+}
+
+/// Retry bookkeeping shared by the PTY and headless run loops
+#[derive(Debug)]
+pub struct Retries {
+    pub policy: RetryPolicy,
+    /// Automatic retries of the current iteration
+    pub used: u32,
+    /// Retries of the current iteration asked for at the step review; they
+    /// neither use up the automatic retries nor lengthen the timeout
+    pub manual: u32,
+    /// Retries over the whole session
+    pub total: u32,
+    /// Prompt note explaining why the iteration is being re-run
+    pub context: Option<String>,
+}
+
+impl Retries {
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self {
+            policy,
+            used: 0,
+            manual: 0,
+            total: 0,
+            context: None,
+        }
+    }
+
+    /// Start a new iteration
+    pub fn start_iteration(&mut self) {
+        self.used = 0;
+        self.manual = 0;
+        self.context = None;
+    }
+
+    /// Which retry the next attempt is, e.g. `retry 1/2` or `manual retry 1`
+    pub fn label(&self) -> Option<String> {
+        let automatic = format!("retry {}/{}", self.used, self.policy.retries_per_task);
+        let manual = format!("manual retry {}", self.manual);
+        match (self.used, self.manual) {
+            (0, 0) => None,
+            (_, 0) => Some(automatic),
+            (0, _) => Some(manual),
+            _ => Some(format!("{}, {}", automatic, manual)),
+        }
+    }
+
+    /// Timeout for the next attempt at the current iteration
+    pub fn timeout(&self, base_seconds: u64) -> u64 {
+        self.policy.timeout_for(base_seconds, self.used)
+    }
+
+    /// Decide what follows an attempt: the operator's step review, when
+    /// there was one, then the retry policy. Before a retry the stop flag is
+    /// cleared (PTY teardown sets it) and the backoff is waited out; a
+    /// `stop_requested()` before or after the wait, or a Ctrl+C during it,
+    /// ends the session.
+    pub fn next_step(
+        &mut self,
+        attempt: &Attempt,
+        review: Option<StepAction>,
+        should_stop: &AtomicBool,
+        stop_requested: impl Fn() -> bool,
+    ) -> NextStep {
+        match review {
+            Some(StepAction::Skip) => return NextStep::Finish,
+            Some(StepAction::Quit) => return NextStep::Quit,
+            Some(StepAction::Retry) => {
+                should_stop.store(false, Ordering::SeqCst);
+                self.manual += 1;
+                self.total += 1;
+                self.context = Some(step::MANUAL_RETRY_NOTE.to_string());
+                return NextStep::Retry;
+            }
+            Some(StepAction::Continue) | None => {}
+        }
+
+        if !self.policy.should_retry(attempt.result, self.used) {
+            return NextStep::Finish;
+        }
+        if stop_requested() {
+            return NextStep::Stopped;
+        }
+
+        should_stop.store(false, Ordering::SeqCst);
+        self.used += 1;
+        self.total += 1;
+
+        let delay = self.policy.backoff(self.used);
+        println!(
+            "[hydra] Iteration {} failed ({}), retrying in {}s (retry {}/{})",
+            attempt.iteration,
+            failure_reason(attempt.result),
+            delay.as_secs(),
+            self.used,
+            self.policy.retries_per_task
+        );
+        if !wait_backoff(delay, should_stop) || stop_requested() {
+            return NextStep::Stopped;
+        }
+
+        self.context = Some(retry_note(
+            attempt.result,
+            attempt.timeout,
+            attempt.log_path,
+        ));
+        NextStep::Retry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            retries_per_task: retries,
+            backoff_seconds: 10,
+            timeout_multiplier: 1.5,
+        }
+    }
+
+    #[test]
+    fn test_only_timeout_and_no_signal_are_failures() {
+        assert!(RetryPolicy::is_failure(&IterationResult::Timeout));
+        assert!(RetryPolicy::is_failure(&IterationResult::NoSignal));
+        assert!(!RetryPolicy::is_failure(&IterationResult::TaskComplete));
+        assert!(!RetryPolicy::is_failure(&IterationResult::AllComplete));
+        assert!(!RetryPolicy::is_failure(&IterationResult::Terminated));
+    }
+
+    #[test]
+    fn test_should_retry_respects_budget() {
+        let p = policy(2);
+        assert!(p.should_retry(&IterationResult::Timeout, 0));
+        assert!(p.should_retry(&IterationResult::NoSignal, 1));
+        assert!(!p.should_retry(&IterationResult::Timeout, 2));
+        assert!(!p.should_retry(&IterationResult::TaskComplete, 0));
+
+        // Default config disables retries entirely
+        let disabled = RetryPolicy::from_config(&Config::default());
+        assert!(!disabled.should_retry(&IterationResult::Timeout, 0));
+    }
+
+    #[test]
+    fn test_backoff_doubles_and_is_capped() {
+        let p = policy(5);
+        assert_eq!(p.backoff(1), Duration::from_secs(10));
+        assert_eq!(p.backoff(2), Duration::from_secs(20));
+        assert_eq!(p.backoff(3), Duration::from_secs(40));
+        assert_eq!(p.backoff(30), Duration::from_secs(MAX_BACKOFF_SECONDS));
+    }
+
+    #[test]
+    fn test_timeout_for_retry() {
+        let p = policy(3);
+        assert_eq!(p.timeout_for(100, 0), 100);
+        assert_eq!(p.timeout_for(100, 1), 150);
+        assert_eq!(p.timeout_for(100, 2), 225);
+
+        let unchanged = RetryPolicy {
+            timeout_multiplier: 1.0,
+            ..policy(3)
+        };
+        assert_eq!(unchanged.timeout_for(100, 2), 100);
+    }
+
+    #[test]
+    fn test_retry_note_mentions_reason() {
+        let note = retry_note(&IterationResult::Timeout, 600, Some(".hydra/logs/x.log"));
+        assert!(note.starts_with("## Previous Attempt Failed"));
+        assert!(note.contains("600s timeout"));
+        assert!(note.contains(".hydra/logs/x.log"));
+
+        let note = retry_note(&IterationResult::NoSignal, 600, None);
+        assert!(note.contains("without emitting a stop sequence"));
+        assert!(!note.contains("session log"));
+    }
+
+    #[test]
+    fn test_retries_suffix() {
+        assert_eq!(retries_suffix(0), "");
+        assert_eq!(retries_suffix(1), " (1 retry)");
+        assert_eq!(retries_suffix(3), " (3 retries)");
+    }
+
+    #[test]
+    fn test_next_step() {
+        let mut retries = Retries::new(RetryPolicy {
+            backoff_seconds: 0,
+            ..policy(1)
+        });
+        let stop = AtomicBool::new(true);
+        let timeout = IterationResult::Timeout;
+        let attempt = Attempt {
+            iteration: 3,
+            result: &timeout,
+            timeout: 600,
+            log_path: None,
+        };
+        assert_eq!(
+            retries.next_step(&attempt, None, &stop, || false),
+            NextStep::Retry
+        );
+        assert!(!stop.load(Ordering::SeqCst));
+        assert!(retries.context.as_ref().unwrap().contains("600s timeout"));
+
+        // The budget is spent; the operator can still ask for a retry
+        assert_eq!(
+            retries.next_step(&attempt, Some(StepAction::Continue), &stop, || false),
+            NextStep::Finish
+        );
+        assert_eq!(
+            retries.next_step(&attempt, Some(StepAction::Retry), &stop, || false),
+            NextStep::Retry
+        );
+        assert_eq!(retries.context.as_deref(), Some(step::MANUAL_RETRY_NOTE));
+        assert_eq!(retries.total, 2);
+        // Manual retries keep the automatic budget and the timeout
+        assert_eq!(retries.used, 1);
+        assert_eq!(retries.timeout(100), 150);
+        assert_eq!(
+            retries.label().as_deref(),
+            Some("retry 1/1, manual retry 1")
+        );
+
+        retries.start_iteration();
+        assert_eq!(retries.label(), None);
+        assert_eq!(
+            retries.next_step(&attempt, Some(StepAction::Retry), &stop, || false),
+            NextStep::Retry
+        );
+        assert_eq!(retries.label().as_deref(), Some("manual retry 1"));
+        assert_eq!(retries.timeout(100), 100);
+        assert_eq!(
+            retries.next_step(&attempt, Some(StepAction::Skip), &stop, || false),
+            NextStep::Finish
+        );
+        assert_eq!(
+            retries.next_step(&attempt, None, &stop, || true),
+            NextStep::Stopped
+        );
+    }
+
+    #[test]
+    fn test_wait_backoff_stops_early() {
+        let stop = AtomicBool::new(true);
+        let start = Instant::now();
+        assert!(!wait_backoff(Duration::from_secs(5), &stop));
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        let stop = AtomicBool::new(false);
+        assert!(wait_backoff(Duration::from_millis(20), &stop));
+    }
+}
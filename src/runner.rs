@@ -1,14 +1,13 @@
 use crate::config::Config;
 use crate::error::{HydraError, Result};
-use crate::harness::Harness;
-use crate::prompt::ResolvedPrompt;
 use crate::pty::{PtyManager, PtyResult};
+use crate::session_loop::{Mode, SessionLoop};
+use crate::signal;
 use chrono::Local;
 use std::fs::{self, File, OpenOptions};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
 use tempfile::NamedTempFile;
 
 /// Debug log to file (since terminal may be frozen)
@@ -26,9 +25,6 @@ fn debug_log(msg: &str) {
     }
 }
 
-/// Iteration instructions prepended to the prompt
-const ITERATION_INSTRUCTIONS: &str = include_str!("../templates/iteration-instructions.md");
-
 /// Result of a single iteration
 #[derive(Debug, Clone, PartialEq)]
 pub enum IterationResult {
@@ -42,6 +38,22 @@ pub enum IterationResult {
     Terminated,
     /// Iteration timed out without receiving a stop signal
     Timeout,
+    /// Agent wrote a question for the operator and emitted the question signal
+    Question,
+}
+
+impl IterationResult {
+    /// Label used in session logs and duration history
+    pub fn label(&self) -> &'static str {
+        match self {
+            IterationResult::TaskComplete => "TASK_COMPLETE",
+            IterationResult::AllComplete => "ALL_COMPLETE",
+            IterationResult::NoSignal => "NO_SIGNAL",
+            IterationResult::Terminated => "TERMINATED",
+            IterationResult::Timeout => "TIMEOUT",
+            IterationResult::Question => "QUESTION",
+        }
+    }
 }
 
 /// Result of the entire run loop
@@ -49,29 +61,53 @@ pub enum IterationResult {
 #[allow(dead_code)]
 pub enum RunResult {
     /// All tasks completed successfully
-    AllTasksComplete { iterations: u32 },
+    AllTasksComplete { iterations: u32, retries: u32 },
     /// Max iterations reached
-    MaxIterations { iterations: u32 },
+    MaxIterations { iterations: u32, retries: u32 },
     /// Stopped gracefully (SIGTERM or stop file)
-    Stopped { iterations: u32 },
+    Stopped { iterations: u32, retries: u32 },
     /// Interrupted (SIGINT)
     Interrupted,
     /// Session ended due to timeout on last iteration
-    Timeout { iterations: u32 },
+    Timeout { iterations: u32, retries: u32 },
+}
+
+impl RunResult {
+    /// Iterations the session used (not counting retries)
+    pub fn iterations(&self) -> u32 {
+        match self {
+            RunResult::AllTasksComplete { iterations, .. }
+            | RunResult::MaxIterations { iterations, .. }
+            | RunResult::Stopped { iterations, .. }
+            | RunResult::Timeout { iterations, .. } => *iterations,
+            RunResult::Interrupted => 0,
+        }
+    }
+
+    /// Retries the session used
+    pub fn retries(&self) -> u32 {
+        match self {
+            RunResult::AllTasksComplete { retries, .. }
+            | RunResult::MaxIterations { retries, .. }
+            | RunResult::Stopped { retries, .. }
+            | RunResult::Timeout { retries, .. } => *retries,
+            RunResult::Interrupted => 0,
+        }
+    }
 }
 
 /// Session logger for writing output to `.hydra/logs/`
-struct SessionLogger {
+pub(crate) struct SessionLogger {
     /// Path to the log file
-    path: PathBuf,
+    pub(crate) path: PathBuf,
     /// Open file handle for appending
-    file: File,
+    pub(crate) file: File,
 }
 
 impl SessionLogger {
     /// Create a new session logger with timestamp-based filename
     /// If a plan name is provided, it's included in the filename for identification
-    fn new(plan_name: Option<&str>) -> Result<Self> {
+    pub(crate) fn new(plan_name: Option<&str>) -> Result<Self> {
         let logs_dir = Config::logs_dir();
 
         // Create logs directory if it doesn't exist
@@ -101,7 +137,7 @@ impl SessionLogger {
     }
 
     /// Write a message to the log
-    fn log(&mut self, message: &str) -> Result<()> {
+    pub(crate) fn log(&mut self, message: &str) -> Result<()> {
         let timestamp = Local::now().format("%H:%M:%S");
         writeln!(self.file, "[{}] {}", timestamp, message)
             .map_err(|e| HydraError::io("writing to log file", e))?;
@@ -112,7 +148,7 @@ impl SessionLogger {
     }
 
     /// Append raw content to the log (for iteration output)
-    fn append_content(&mut self, content: &str) -> Result<()> {
+    pub(crate) fn append_content(&mut self, content: &str) -> Result<()> {
         write!(self.file, "{}", content)
             .map_err(|e| HydraError::io("writing content to log file", e))?;
         self.file
@@ -122,7 +158,7 @@ impl SessionLogger {
     }
 
     /// Write iteration header to the log
-    fn log_iteration_start(&mut self, iteration: u32, max: u32) -> Result<()> {
+    pub(crate) fn log_iteration_start(&mut self, iteration: u32, max: u32) -> Result<()> {
         let separator = "=".repeat(80);
         self.append_content(&format!("\n{}\n", separator))?;
         self.log(&format!("ITERATION {}/{} START", iteration, max))?;
@@ -131,124 +167,99 @@ impl SessionLogger {
     }
 
     /// Write iteration end to the log
-    fn log_iteration_end(&mut self, iteration: u32, result: &IterationResult) -> Result<()> {
-        let result_str = match result {
-            IterationResult::TaskComplete => "TASK_COMPLETE",
-            IterationResult::AllComplete => "ALL_COMPLETE",
-            IterationResult::NoSignal => "NO_SIGNAL",
-            IterationResult::Terminated => "TERMINATED",
-            IterationResult::Timeout => "TIMEOUT",
-        };
-        self.log(&format!("ITERATION {} END: {}", iteration, result_str))?;
+    pub(crate) fn log_iteration_end(
+        &mut self,
+        iteration: u32,
+        result: &IterationResult,
+    ) -> Result<()> {
+        self.log(&format!("ITERATION {} END: {}", iteration, result.label()))?;
         Ok(())
     }
 }
 
-/// The runner that executes a coding-agent harness in a loop
-pub struct Runner {
-    config: Config,
-    prompt: ResolvedPrompt,
-    should_stop: Arc<AtomicBool>,
-    logger: Option<SessionLogger>,
-    plan_name: Option<String>,
-    scratchpad_path: Option<PathBuf>,
-    harness: Harness,
-}
-
-impl Runner {
-    /// Create a new runner with the given configuration, prompt, and optional plan name
-    pub fn new(
-        config: Config,
-        prompt: ResolvedPrompt,
-        plan_name: Option<String>,
-        scratchpad_path: Option<PathBuf>,
-        harness: Harness,
-    ) -> Self {
-        // Try to create the session logger, but don't fail if it doesn't work
-        let logger = match SessionLogger::new(plan_name.as_deref()) {
-            Ok(l) => Some(l),
-            Err(e) => {
-                eprintln!("[hydra] Warning: Could not create session log: {}", e);
-                None
-            }
-        };
-
-        Self {
-            config,
-            prompt,
-            should_stop: Arc::new(AtomicBool::new(false)),
-            logger,
-            plan_name,
-            scratchpad_path,
-            harness,
-        }
-    }
-
-    /// Get a clone of the stop flag for signal handlers
-    pub fn stop_flag(&self) -> Arc<AtomicBool> {
-        Arc::clone(&self.should_stop)
+/// Append a timeout note to the scratchpad so the next iteration knows to
+/// check the session log (shared with the TUI loop driver)
+pub(crate) fn append_timeout_note(
+    scratchpad_path: &Path,
+    iteration: u32,
+    timeout_seconds: u64,
+    logger: Option<&SessionLogger>,
+) {
+    let log_path = logger
+        .map(|l| l.path.display().to_string())
+        .unwrap_or_else(|| "the session log".to_string());
+    let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
+    let note = format!(
+        "\n## ⚠ Timeout — Iteration {} ({})\n\n\
+         The previous iteration (#{}) was terminated due to timeout ({}s limit).\n\
+         **Next iteration**: Check the logs at `{}` to understand what was in progress \
+         and resume or retry the interrupted work.\n",
+        iteration, timestamp, iteration, timeout_seconds, log_path,
+    );
+    if let Err(e) = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(scratchpad_path)
+        .and_then(|mut f| f.write_all(note.as_bytes()))
+    {
+        eprintln!(
+            "[hydra] Warning: Could not write timeout note to scratchpad: {}",
+            e
+        );
     }
+}
 
-    /// Create a combined prompt file with iteration instructions
-    fn create_combined_prompt(&self) -> Result<NamedTempFile> {
-        let combined = format!("{}\n{}", ITERATION_INSTRUCTIONS, self.prompt.content);
-
-        let mut temp =
-            NamedTempFile::new().map_err(|e| HydraError::io("creating temp prompt file", e))?;
-
-        use std::io::Write;
-        temp.write_all(combined.as_bytes())
-            .map_err(|e| HydraError::io("writing combined prompt", e))?;
+/// Runs each iteration in a PTY, so the operator sees the harness's TUI
+pub struct Pty;
 
-        Ok(temp)
-    }
+/// The runner that executes a coding-agent harness in a loop
+pub type Runner = SessionLoop<Pty>;
 
-    /// Append a timeout note to the scratchpad so the next iteration knows to check logs
-    fn append_timeout_to_scratchpad(&self, iteration: u32) {
-        let Some(ref scratchpad_path) = self.scratchpad_path else {
-            return;
-        };
-        let log_path = self
-            .logger
-            .as_ref()
-            .map(|l| l.path.display().to_string())
-            .unwrap_or_else(|| "the session log".to_string());
-        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
-        let note = format!(
-            "\n## ⚠ Timeout — Iteration {} ({})\n\n\
-             The previous iteration (#{}) was terminated due to timeout ({}s limit).\n\
-             **Next iteration**: Check the logs at `{}` to understand what was in progress \
-             and resume or retry the interrupted work.\n",
-            iteration, timestamp, iteration, self.config.timeout_seconds, log_path,
+impl Mode for Pty {
+    fn banner(session: &mut Runner) {
+        println!("[hydra] Starting automated task runner");
+        println!(
+            "[hydra] Using prompt file: {}",
+            session.prompt.path.display()
         );
-        if let Err(e) = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(scratchpad_path)
-            .and_then(|mut f| f.write_all(note.as_bytes()))
-        {
-            eprintln!(
-                "[hydra] Warning: Could not write timeout note to scratchpad: {}",
-                e
-            );
+        if let Some(ref logger) = session.logger {
+            println!("[hydra] Session log: {}", logger.path.display());
         }
+        println!("[hydra] Claude controls task selection from implementation plan");
+
+        let max = session.config.max_iterations;
+        session.log(&format!("Session started - max iterations: {}", max));
     }
 
-    /// Check if the stop file exists
-    fn check_stop_file(&self) -> bool {
-        let stop_path = PathBuf::from(&self.config.stop_file);
-        if stop_path.exists() {
-            // Remove the stop file
-            let _ = fs::remove_file(&stop_path);
-            true
-        } else {
-            false
+    fn header(iteration: u32, max: u32, label: Option<&str>) {
+        println!();
+        match label {
+            None => println!(
+                "=== Iteration {}/{} === [{}]",
+                iteration,
+                max,
+                Local::now().format("%Y-%m-%d %H:%M:%S")
+            ),
+            Some(label) => println!(
+                "=== Iteration {}/{} ({}) === [{}]",
+                iteration,
+                max,
+                label,
+                Local::now().format("%Y-%m-%d %H:%M:%S")
+            ),
         }
+        println!();
     }
 
-    /// Run a single iteration and return the result
-    fn run_iteration(&mut self, iteration: u32) -> Result<IterationResult> {
-        if self.config.verbose {
+    /// Run a single iteration, handing the harness the prompt as a file
+    fn run_iteration(
+        session: &mut Runner,
+        iteration: u32,
+        timeout_seconds: u64,
+        prompt: &str,
+    ) -> Result<IterationResult> {
+        debug_log(&format!("starting iteration {}", iteration));
+        if session.config.verbose {
             eprintln!("[hydra:debug] Starting iteration {}", iteration);
         }
 
@@ -258,24 +269,24 @@ impl Runner {
             Local::now().format("%H:%M:%S")
         );
 
-        // Create the combined prompt file
-        let prompt_file = self.create_combined_prompt()?;
+        // Write the combined prompt to a file for the harness to read
+        let mut prompt_file =
+            NamedTempFile::new().map_err(|e| HydraError::io("creating temp prompt file", e))?;
+        prompt_file
+            .write_all(prompt.as_bytes())
+            .map_err(|e| HydraError::io("writing combined prompt", e))?;
 
         // Create output file for capturing Claude's output
         let output_file =
             NamedTempFile::new().map_err(|e| HydraError::io("creating output file", e))?;
 
         // Create PTY manager and spawn the configured harness
-        let mut pty = PtyManager::new(Arc::clone(&self.should_stop))?;
-        pty.spawn_harness(self.harness, prompt_file.path())?;
+        let mut pty = PtyManager::new(Arc::clone(&session.should_stop))?;
+        pty.spawn_harness(session.harness, prompt_file.path())?;
 
         // Run the I/O loop (handles stdin, stdout, and signal detection)
         let output_path = output_file.path().to_path_buf();
-        let pty_result = pty.run_io_loop(
-            &output_path,
-            self.config.verbose,
-            self.config.timeout_seconds,
-        )?;
+        let pty_result = pty.run_io_loop(&output_path, session.config.verbose, timeout_seconds)?;
 
         // Convert PtyResult to IterationResult
         let result = match pty_result {
@@ -284,10 +295,19 @@ impl Runner {
             PtyResult::NoSignal => IterationResult::NoSignal,
             PtyResult::Terminated => IterationResult::Terminated,
             PtyResult::Timeout => IterationResult::Timeout,
+            PtyResult::Question => IterationResult::Question,
         };
+        // A SIGTERM may have killed the harness before the I/O loop saw the
+        // stop flag, in which case the loop ends on EOF
+        let result = if result == IterationResult::NoSignal && signal::received() {
+            IterationResult::Terminated
+        } else {
+            result
+        };
+        debug_log(&format!("iteration {} returned {:?}", iteration, result));
 
         // Copy iteration output to session log
-        if let Some(ref mut logger) = self.logger
+        if let Some(ref mut logger) = session.logger
             && let Ok(output_content) = fs::read_to_string(&output_path)
         {
             let _ = logger.append_content(&output_content);
@@ -301,172 +321,12 @@ impl Runner {
 
         Ok(result)
     }
-
-    /// Run the main loop
-    pub fn run(&mut self) -> Result<RunResult> {
-        let max = self.config.max_iterations;
-
-        println!("[hydra] Starting automated task runner");
-        println!("[hydra] Using prompt file: {}", self.prompt.path.display());
-        if let Some(ref logger) = self.logger {
-            println!("[hydra] Session log: {}", logger.path.display());
-        }
-        println!("[hydra] Claude controls task selection from implementation plan");
-
-        // Log session start
-        if let Some(ref mut logger) = self.logger {
-            let _ = logger.log(&format!("Session started - max iterations: {}", max));
-            let _ = logger.log(&format!("Prompt file: {}", self.prompt.path.display()));
-            if let Some(ref plan) = self.plan_name {
-                let _ = logger.log(&format!("Plan: {}", plan));
-            }
-        }
-
-        for iteration in 1..=max {
-            // Check for stop file before each iteration
-            if self.check_stop_file() {
-                println!("[hydra] Stop file detected, exiting gracefully");
-                if let Some(ref mut logger) = self.logger {
-                    let _ = logger.log("Session ended: stop file detected");
-                }
-                return Ok(RunResult::Stopped {
-                    iterations: iteration - 1,
-                });
-            }
-
-            // Check for graceful stop request (SIGTERM)
-            if self.should_stop.load(Ordering::SeqCst) {
-                println!("[hydra] Graceful shutdown complete");
-                if let Some(ref mut logger) = self.logger {
-                    let _ = logger.log("Session ended: graceful shutdown");
-                }
-                return Ok(RunResult::Stopped {
-                    iterations: iteration - 1,
-                });
-            }
-
-            // Display iteration header
-            println!();
-            println!(
-                "=== Iteration {}/{} === [{}]",
-                iteration,
-                max,
-                Local::now().format("%Y-%m-%d %H:%M:%S")
-            );
-            println!();
-
-            // Log iteration start
-            if let Some(ref mut logger) = self.logger {
-                let _ = logger.log_iteration_start(iteration, max);
-            }
-
-            // Run the iteration
-            debug_log(&format!("starting iteration {}", iteration));
-            let result = self.run_iteration(iteration)?;
-            debug_log(&format!("iteration {} returned {:?}", iteration, result));
-
-            // Log iteration end
-            if let Some(ref mut logger) = self.logger {
-                let _ = logger.log_iteration_end(iteration, &result);
-            }
-
-            match result {
-                IterationResult::AllComplete => {
-                    debug_log("all tasks complete");
-                    println!("[hydra] All tasks complete! Total runs: {}", iteration);
-                    if let Some(ref mut logger) = self.logger {
-                        let _ = logger.log(&format!(
-                            "Session ended: all tasks complete after {} iterations",
-                            iteration
-                        ));
-                    }
-                    return Ok(RunResult::AllTasksComplete {
-                        iterations: iteration,
-                    });
-                }
-                IterationResult::Terminated => {
-                    debug_log("terminated");
-                    println!("[hydra] Graceful shutdown complete");
-                    if let Some(ref mut logger) = self.logger {
-                        let _ = logger.log("Session ended: terminated");
-                    }
-                    return Ok(RunResult::Stopped {
-                        iterations: iteration,
-                    });
-                }
-                IterationResult::Timeout => {
-                    debug_log("timeout, writing to scratchpad");
-                    self.append_timeout_to_scratchpad(iteration);
-                    self.should_stop.store(false, Ordering::SeqCst);
-                    if self.config.verbose {
-                        eprintln!("[hydra:debug] Timeout recorded in scratchpad, continuing");
-                    }
-                }
-                IterationResult::TaskComplete | IterationResult::NoSignal => {
-                    debug_log("continuing to next iteration");
-                    // Reset should_stop flag - it may have been set by cleanup() during PTY teardown
-                    // but that doesn't mean we should stop the entire run loop
-                    self.should_stop.store(false, Ordering::SeqCst);
-                    // Continue to next iteration
-                    if self.config.verbose {
-                        eprintln!("[hydra:debug] Continuing to next iteration");
-                    }
-                }
-            }
-        }
-
-        debug_log("max iterations reached, returning");
-        println!("[hydra] Max iterations reached");
-        if let Some(ref mut logger) = self.logger {
-            let _ = logger.log(&format!("Session ended: max iterations ({}) reached", max));
-        }
-        Ok(RunResult::MaxIterations { iterations: max })
-    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::prompt::PromptSource;
-
-    fn test_config() -> Config {
-        Config {
-            max_iterations: 3,
-            verbose: false,
-            stop_file: ".hydra-stop-test".to_string(),
-            timeout_seconds: 3000,
-        }
-    }
-
-    fn test_prompt() -> ResolvedPrompt {
-        ResolvedPrompt {
-            path: PathBuf::from("test-prompt.md"),
-            content: "Test prompt content".to_string(),
-            source: PromptSource::CurrentDir,
-        }
-    }
-
-    #[test]
-    fn test_runner_creation() {
-        let config = test_config();
-        let prompt = test_prompt();
-        let runner = Runner::new(config, prompt, None, None, Harness::Claude);
-
-        assert!(!runner.should_stop.load(Ordering::SeqCst));
-    }
-
-    #[test]
-    fn test_stop_flag() {
-        let config = test_config();
-        let prompt = test_prompt();
-        let runner = Runner::new(config, prompt, None, None, Harness::Claude);
-
-        let flag = runner.stop_flag();
-        assert!(!flag.load(Ordering::SeqCst));
-
-        flag.store(true, Ordering::SeqCst);
-        assert!(flag.load(Ordering::SeqCst));
-    }
+    use crate::instructions;
 
     #[test]
     fn test_iteration_instructions_does_not_contain_literal_signals() {
@@ -475,26 +335,30 @@ mod tests {
         // do not trip hydra's PTY signal scanner with a false positive.
         // See specs/pi-harness.md for the bug history.
         assert!(
-            !ITERATION_INSTRUCTIONS.contains("###TASK_COMPLETE###"),
+            !instructions::DEFAULT_INSTRUCTIONS.contains("###TASK_COMPLETE###"),
             "iteration-instructions.md must NOT contain the literal task-complete signal"
         );
         assert!(
-            !ITERATION_INSTRUCTIONS.contains("###ALL_TASKS_COMPLETE###"),
+            !instructions::DEFAULT_INSTRUCTIONS.contains("###ALL_TASKS_COMPLETE###"),
             "iteration-instructions.md must NOT contain the literal all-complete signal"
         );
+        assert!(
+            !instructions::DEFAULT_INSTRUCTIONS.contains(crate::question::QUESTION_SIGNAL),
+            "iteration-instructions.md must NOT contain the literal question signal"
+        );
         // Sanity check that the template still describes the stop sequences
         // (without the literal joined form) so the agent knows what to emit.
         // The descriptive form splits the keywords across words: "TASK then
         // underscore then COMPLETE", etc.
         assert!(
-            ITERATION_INSTRUCTIONS.contains("TASK")
-                && ITERATION_INSTRUCTIONS.contains("COMPLETE")
-                && ITERATION_INSTRUCTIONS.contains("ALL")
-                && ITERATION_INSTRUCTIONS.contains("TASKS"),
+            instructions::DEFAULT_INSTRUCTIONS.contains("TASK")
+                && instructions::DEFAULT_INSTRUCTIONS.contains("COMPLETE")
+                && instructions::DEFAULT_INSTRUCTIONS.contains("ALL")
+                && instructions::DEFAULT_INSTRUCTIONS.contains("TASKS"),
             "iteration-instructions.md must still describe the stop sequences"
         );
         assert!(
-            ITERATION_INSTRUCTIONS.contains("hash"),
+            instructions::DEFAULT_INSTRUCTIONS.contains("hash"),
             "iteration-instructions.md must describe the hash wrapping"
         );
     }
@@ -0,0 +1,606 @@
+use crate::config::Config;
+use crate::control::ControlState;
+use crate::error::Result;
+use crate::harness::Harness;
+use crate::history::{self, DurationHistory};
+use crate::instructions;
+use crate::lock;
+use crate::plan;
+use crate::prompt::{self, IterationContext, PreviousIteration, ResolvedPrompt};
+use crate::question;
+use crate::retry::{self, Attempt, NextStep, Retries, RetryPolicy};
+use crate::runner::{IterationResult, RunResult, SessionLogger, append_timeout_note};
+use crate::signal;
+use crate::step::{self, IterationSnapshot, IterationSummary, StepAction, StepMode};
+use crate::template::{self, TemplateVars};
+use std::fs;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// How a session runs its iterations: the harness in a PTY (`runner::Pty`)
+/// or in print mode (`headless::Headless`)
+pub trait Mode: Sized {
+    /// Print what the session is about to do and log its start
+    fn banner(session: &mut SessionLoop<Self>);
+
+    /// Print the header of an attempt; `label` names a retry
+    fn header(iteration: u32, max: u32, label: Option<&str>);
+
+    /// Hand `prompt` to the harness and run one attempt at `iteration`
+    fn run_iteration(
+        session: &mut SessionLoop<Self>,
+        iteration: u32,
+        timeout_seconds: u64,
+        prompt: &str,
+    ) -> Result<IterationResult>;
+}
+
+/// The iteration loop shared by the PTY and headless runners: stop checks,
+/// retries, step reviews, questions and the session log
+pub struct SessionLoop<M> {
+    pub(crate) config: Config,
+    pub(crate) prompt: ResolvedPrompt,
+    pub(crate) should_stop: Arc<AtomicBool>,
+    pub(crate) logger: Option<SessionLogger>,
+    pub(crate) harness: Harness,
+    plan_name: Option<String>,
+    plan_path: Option<PathBuf>,
+    scratchpad_path: Option<PathBuf>,
+    retry_policy: RetryPolicy,
+    history: Option<DurationHistory>,
+    control: Option<Arc<ControlState>>,
+    step_mode: Option<StepMode>,
+    /// Where the agent writes questions for the operator
+    question_path: PathBuf,
+    /// Answer to the last question, injected into the next iteration's prompt
+    pending_answer: Option<String>,
+    /// Iteration instructions prepended to every prompt
+    instructions: String,
+    /// Outcome of the last attempt, shown in the next prompt
+    previous: Option<PreviousIteration>,
+    mode: PhantomData<M>,
+}
+
+impl<M: Mode> SessionLoop<M> {
+    /// Create a new runner with the given configuration, prompt, and optional plan name
+    pub fn new(
+        config: Config,
+        prompt: ResolvedPrompt,
+        plan_path: Option<PathBuf>,
+        scratchpad_path: Option<PathBuf>,
+        harness: Harness,
+    ) -> Self {
+        let plan_name = plan_path.as_deref().map(plan::plan_name);
+
+        // Try to create the session logger, but don't fail if it doesn't work
+        let logger = match SessionLogger::new(plan_name.as_deref()) {
+            Ok(l) => Some(l),
+            Err(e) => {
+                eprintln!("[hydra] Warning: Could not create session log: {}", e);
+                None
+            }
+        };
+
+        Self {
+            retry_policy: RetryPolicy::from_config(&config),
+            history: plan_name.as_deref().map(DurationHistory::load),
+            control: None,
+            step_mode: None,
+            question_path: question::question_path(&lock::session_name(plan_path.as_deref())),
+            pending_answer: None,
+            instructions: instructions::DEFAULT_INSTRUCTIONS.to_string(),
+            previous: None,
+            config,
+            prompt,
+            should_stop: Arc::new(AtomicBool::new(false)),
+            logger,
+            plan_name,
+            plan_path,
+            scratchpad_path,
+            harness,
+            mode: PhantomData,
+        }
+    }
+
+    /// Get a clone of the stop flag for signal handlers
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.should_stop)
+    }
+
+    /// Share a stop flag with other sessions (the plans of a queue share the
+    /// one the signal handlers set)
+    pub fn set_stop_flag(&mut self, flag: Arc<AtomicBool>) {
+        self.should_stop = flag;
+    }
+
+    /// Take stop/pause/skip requests from the session's control socket
+    pub fn set_control(&mut self, control: Arc<ControlState>) {
+        self.control = Some(control);
+    }
+
+    /// Replace the built-in iteration instructions (see `instructions::resolve_instructions`)
+    pub fn set_instructions(&mut self, instructions: String) {
+        self.instructions = instructions;
+    }
+
+    /// Pause for review after iterations (`--step` / `--step-on`)
+    pub fn set_step_mode(&mut self, mode: StepMode) {
+        self.step_mode = Some(mode);
+    }
+
+    /// Write a message to the session log, if there is one
+    pub(crate) fn log(&mut self, message: &str) {
+        if let Some(ref mut logger) = self.logger {
+            let _ = logger.log(message);
+        }
+    }
+
+    /// Show what an iteration did and ask the operator how to proceed
+    fn review_iteration(
+        &mut self,
+        before: &IterationSnapshot,
+        iteration: u32,
+        result: &IterationResult,
+        elapsed: Duration,
+    ) -> Result<StepAction> {
+        let summary = IterationSummary::collect(
+            before,
+            self.plan_path.as_deref(),
+            iteration,
+            result,
+            elapsed,
+        );
+        summary.print();
+        self.log(&summary.log_line());
+
+        let action =
+            step::prompt_action(self.plan_path.as_deref(), self.scratchpad_path.as_deref())?;
+        self.log(&format!(
+            "ITERATION {} REVIEW ACTION: {:?}",
+            iteration, action
+        ));
+        Ok(action)
+    }
+
+    /// Show the agent's question, record the answer in the scratchpad and
+    /// keep it for the next iteration's prompt; true when it was answered
+    fn handle_question(&mut self, iteration: u32) -> Result<bool> {
+        let Some(text) = question::read_question(&self.question_path) else {
+            println!(
+                "[hydra] Question signal without a question in {}, continuing",
+                self.question_path.display()
+            );
+            self.log(&format!("ITERATION {} QUESTION: (missing)", iteration));
+            return Ok(false);
+        };
+        self.log(&format!("ITERATION {} QUESTION: {}", iteration, text));
+
+        let answer = question::ask(&text)?;
+        self.log(&format!(
+            "ITERATION {} ANSWER: {}",
+            iteration,
+            answer.as_deref().unwrap_or("(none)")
+        ));
+        question::record_in_scratchpad(
+            self.scratchpad_path.as_deref(),
+            iteration,
+            &text,
+            answer.as_deref(),
+        );
+        if let Err(e) = question::archive(&self.question_path, &text, answer.as_deref()) {
+            eprintln!("[hydra] Warning: Could not archive question: {}", e);
+        }
+        self.pending_answer = Some(question::answer_note(&text, answer.as_deref()));
+        Ok(answer.is_some())
+    }
+
+    /// Whether `hydra stop` asked the loop to end
+    fn control_stop_requested(&self) -> bool {
+        self.control.as_ref().is_some_and(|c| c.stop_requested())
+    }
+
+    /// Hold before the next iteration while `hydra pause` is in effect
+    fn wait_if_paused(&mut self) {
+        let Some(control) = self.control.clone() else {
+            return;
+        };
+        if !control.is_paused() {
+            return;
+        }
+        println!("[hydra] Paused, run `hydra resume-loop` to continue");
+        self.log("Session paused");
+        control.wait_while_paused(&self.should_stop);
+        if !control.stop_requested() && !self.should_stop.load(Ordering::SeqCst) {
+            println!("[hydra] Resumed");
+            self.log("Session resumed");
+        }
+    }
+
+    /// The prompt for an attempt: iteration instructions and the user prompt,
+    /// plus the failure note on retries and the answer to a question
+    fn combined_prompt(
+        &self,
+        iteration: u32,
+        timeout_seconds: u64,
+        context: Option<&str>,
+    ) -> Result<String> {
+        let combined = format!(
+            "{}\n{}",
+            self.instructions,
+            self.render_prompt(iteration, timeout_seconds)?
+        );
+        Ok(match context {
+            Some(note) => format!("{}\n\n{}", combined.trim_end(), note),
+            None => combined,
+        })
+    }
+
+    /// The user prompt with template placeholders filled in, followed by the
+    /// `## This Iteration` section
+    fn render_prompt(&self, iteration: u32, timeout_seconds: u64) -> Result<String> {
+        let vars = TemplateVars::collect(
+            self.plan_path.as_deref(),
+            self.scratchpad_path.as_deref(),
+            Some(&self.question_path),
+            iteration,
+            self.config.max_iterations,
+        );
+        let rendered = template::render_prompt(&self.prompt, &vars)?;
+        let section = prompt::iteration_context_section(&IterationContext {
+            iteration,
+            max_iterations: self.config.max_iterations,
+            timeout_seconds,
+            next_task: vars.next_task.as_deref(),
+            previous: self.previous.as_ref(),
+        });
+        Ok(format!("{}\n\n{}", rendered.trim_end(), section))
+    }
+
+    /// Append a timeout note to the scratchpad so the next iteration knows to check logs
+    fn append_timeout_to_scratchpad(&self, iteration: u32, timeout_seconds: u64) {
+        if let Some(ref scratchpad_path) = self.scratchpad_path {
+            append_timeout_note(
+                scratchpad_path,
+                iteration,
+                timeout_seconds,
+                self.logger.as_ref(),
+            );
+        }
+    }
+
+    /// Check if the stop file exists
+    fn check_stop_file(&self) -> bool {
+        let stop_path = PathBuf::from(&self.config.stop_file);
+        if stop_path.exists() {
+            // Remove the stop file
+            let _ = fs::remove_file(&stop_path);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Print `message` and log why the session ended
+    fn stopped(&mut self, message: &str, reason: &str, iterations: u32, retries: u32) -> RunResult {
+        println!("[hydra] {}", message);
+        self.log(&format!("Session ended: {}", reason));
+        RunResult::Stopped {
+            iterations,
+            retries,
+        }
+    }
+
+    /// Run the main loop
+    pub fn run(&mut self) -> Result<RunResult> {
+        let mut max = self.config.max_iterations;
+
+        M::banner(self);
+        self.log(&format!("Prompt file: {}", self.prompt.path.display()));
+        if let Some(plan) = self.plan_name.clone() {
+            self.log(&format!("Plan: {}", plan));
+        }
+        if self.retry_policy.retries_per_task > 0 {
+            self.log(&format!(
+                "Retry policy: {} retries per task, {}s backoff",
+                self.retry_policy.retries_per_task, self.retry_policy.backoff_seconds
+            ));
+        }
+        if let Some(mode) = self.step_mode.as_ref().map(StepMode::describe) {
+            self.log(&format!("Step mode: pause {}", mode));
+        }
+
+        // Retries are tracked separately from the iteration budget
+        let mut retries = Retries::new(self.retry_policy.clone());
+
+        let mut iteration = 0;
+        while iteration < max {
+            iteration += 1;
+
+            // Hold here while paused via `hydra pause`
+            self.wait_if_paused();
+
+            // Check for stop file before each iteration
+            if self.check_stop_file() {
+                return Ok(self.stopped(
+                    "Stop file detected, exiting gracefully",
+                    "stop file detected",
+                    iteration - 1,
+                    retries.total,
+                ));
+            }
+
+            // Check for `hydra stop` via the control socket
+            if self.control_stop_requested() {
+                return Ok(self.stopped(
+                    "Stop requested, exiting gracefully",
+                    "stop requested",
+                    iteration - 1,
+                    retries.total,
+                ));
+            }
+
+            // Check for graceful stop request (SIGTERM)
+            if self.should_stop.load(Ordering::SeqCst) {
+                return Ok(self.stopped(
+                    "Graceful shutdown complete",
+                    "graceful shutdown",
+                    iteration - 1,
+                    retries.total,
+                ));
+            }
+
+            let estimate = history::estimate_iteration(
+                &self.config,
+                self.plan_path.as_deref(),
+                self.history.as_ref(),
+            );
+            let timeout_line = estimate.announce();
+            self.log(&timeout_line);
+
+            // Carried into every attempt of this iteration, retries included
+            let answer_note = self.pending_answer.take();
+            retries.start_iteration();
+
+            let result = loop {
+                let timeout = retries.timeout(estimate.timeout_seconds);
+                let label = retries.label();
+                M::header(iteration, max, label.as_deref());
+
+                // Log iteration start
+                if let Some(ref mut logger) = self.logger {
+                    let _ = logger.log_iteration_start(iteration, max);
+                    if let Some(ref label) = label {
+                        let _ = logger.log(&format!(
+                            "ITERATION {} {} (timeout {}s)",
+                            iteration,
+                            label.to_uppercase(),
+                            timeout
+                        ));
+                    }
+                }
+
+                // Run the iteration
+                let snapshot = self
+                    .step_mode
+                    .as_ref()
+                    .map(|_| IterationSnapshot::capture(self.plan_path.as_deref()));
+                let started = Instant::now();
+                let context =
+                    question::prompt_context(answer_note.as_deref(), retries.context.as_deref());
+                let prompt = self.combined_prompt(iteration, timeout, context.as_deref())?;
+                let result = M::run_iteration(self, iteration, timeout, &prompt)?;
+                let elapsed = started.elapsed();
+                self.previous = Some(PreviousIteration {
+                    result: result.clone(),
+                    elapsed,
+                });
+                if result != IterationResult::Terminated {
+                    history::record_duration(
+                        self.history.as_mut(),
+                        elapsed,
+                        &result,
+                        estimate.next_task.as_deref(),
+                    );
+                }
+
+                // Log iteration end
+                if let Some(ref mut logger) = self.logger {
+                    let _ = logger.log_iteration_end(iteration, &result);
+                }
+
+                if result == IterationResult::Timeout {
+                    self.append_timeout_to_scratchpad(iteration, timeout);
+                }
+
+                // `hydra skip` killed the harness; move on without retrying
+                if self.control.as_ref().is_some_and(|c| c.take_skip()) {
+                    println!("[hydra] Iteration {} skipped", iteration);
+                    self.log(&format!("ITERATION {} SKIPPED", iteration));
+                    break result;
+                }
+
+                // --step: let the operator review the iteration before moving on
+                let review = match snapshot {
+                    Some(ref snapshot)
+                        if self
+                            .step_mode
+                            .as_ref()
+                            .is_some_and(|m| m.pauses_on(&result)) =>
+                    {
+                        Some(self.review_iteration(snapshot, iteration, &result, elapsed)?)
+                    }
+                    _ => None,
+                };
+
+                let log_path = self.logger.as_ref().map(|l| l.path.display().to_string());
+                let attempt = Attempt {
+                    iteration,
+                    result: &result,
+                    timeout,
+                    log_path: log_path.as_deref(),
+                };
+                match retries.next_step(&attempt, review, &self.should_stop, || {
+                    signal::received() || self.check_stop_file() || self.control_stop_requested()
+                }) {
+                    NextStep::Finish => break result,
+                    NextStep::Retry => {}
+                    NextStep::Quit => {
+                        return Ok(self.stopped(
+                            "Quitting after review",
+                            "quit from step review",
+                            iteration,
+                            retries.total,
+                        ));
+                    }
+                    NextStep::Stopped => {
+                        return Ok(self.stopped(
+                            "Graceful shutdown complete",
+                            "stopped while waiting to retry",
+                            iteration,
+                            retries.total,
+                        ));
+                    }
+                }
+            };
+
+            match result {
+                IterationResult::AllComplete => {
+                    println!(
+                        "[hydra] All tasks complete! Total iterations: {}{}",
+                        iteration,
+                        retry::retries_suffix(retries.total)
+                    );
+                    self.log(&format!(
+                        "Session ended: all tasks complete after {} iterations{}",
+                        iteration,
+                        retry::retries_suffix(retries.total)
+                    ));
+                    return Ok(RunResult::AllTasksComplete {
+                        iterations: iteration,
+                        retries: retries.total,
+                    });
+                }
+                IterationResult::Terminated => {
+                    return Ok(self.stopped(
+                        "Graceful shutdown complete",
+                        "terminated",
+                        iteration,
+                        retries.total,
+                    ));
+                }
+                IterationResult::Timeout => {
+                    self.should_stop.store(false, Ordering::SeqCst);
+                    if self.config.verbose {
+                        eprintln!("[hydra:debug] Timeout recorded in scratchpad, continuing");
+                    }
+                }
+                IterationResult::Question => {
+                    // Reset should_stop flag - it was set during teardown
+                    self.should_stop.store(false, Ordering::SeqCst);
+                    // An answer to a question on the last iteration would
+                    // otherwise never reach the agent
+                    if self.handle_question(iteration)? && iteration == max {
+                        self.config.max_iterations += 1;
+                        max = self.config.max_iterations;
+                        println!(
+                            "[hydra] Question came on the last iteration, running one more for the answer"
+                        );
+                        self.log(&format!(
+                            "Iteration budget raised to {} for the answer",
+                            max
+                        ));
+                    }
+                }
+                IterationResult::TaskComplete | IterationResult::NoSignal => {
+                    // Reset should_stop flag - it may have been set during
+                    // harness teardown, which doesn't mean the run should stop
+                    self.should_stop.store(false, Ordering::SeqCst);
+                    if self.config.verbose {
+                        eprintln!("[hydra:debug] Continuing to next iteration");
+                    }
+                }
+            }
+        }
+
+        println!(
+            "[hydra] Max iterations reached{}",
+            retry::retries_suffix(retries.total)
+        );
+        self.log(&format!(
+            "Session ended: max iterations ({}) reached{}",
+            max,
+            retry::retries_suffix(retries.total)
+        ));
+        Ok(RunResult::MaxIterations {
+            iterations: max,
+            retries: retries.total,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt::PromptSource;
+    use crate::runner::Runner;
+
+    fn test_config() -> Config {
+        Config {
+            max_iterations: 3,
+            verbose: false,
+            stop_file: ".hydra-stop-test".to_string(),
+            timeout_seconds: 3000,
+            ..Config::default()
+        }
+    }
+
+    fn test_prompt() -> ResolvedPrompt {
+        ResolvedPrompt {
+            path: PathBuf::from("test-prompt.md"),
+            content: "Test prompt content".to_string(),
+            source: PromptSource::CurrentDir,
+        }
+    }
+
+    #[test]
+    fn test_runner_creation() {
+        let config = test_config();
+        let prompt = test_prompt();
+        let runner = Runner::new(config, prompt, None, None, Harness::Claude);
+
+        assert!(!runner.should_stop.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_stop_flag() {
+        let config = test_config();
+        let prompt = test_prompt();
+        let runner = Runner::new(config, prompt, None, None, Harness::Claude);
+
+        let flag = runner.stop_flag();
+        assert!(!flag.load(Ordering::SeqCst));
+
+        flag.store(true, Ordering::SeqCst);
+        assert!(flag.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_combined_prompt_includes_retry_note() {
+        let runner = Runner::new(test_config(), test_prompt(), None, None, Harness::Claude);
+
+        let first = runner.combined_prompt(1, 3000, None).unwrap();
+        assert!(first.contains("Test prompt content\n\n## This Iteration"));
+        assert!(first.contains("- Iteration: 1 of "));
+        assert!(!first.contains("Previous Attempt Failed"));
+
+        let note = retry::retry_note(&IterationResult::Timeout, 60, None);
+        let retry = runner.combined_prompt(1, 3000, Some(&note)).unwrap();
+        let prompt_pos = retry.find("Test prompt content").unwrap();
+        let section_pos = retry.find("## This Iteration").unwrap();
+        let note_pos = retry.find("## Previous Attempt Failed").unwrap();
+        assert!(prompt_pos < section_pos && section_pos < note_pos);
+    }
+}
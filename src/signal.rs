@@ -25,6 +25,11 @@ pub fn clear_child_pid() {
     CHILD_PID.store(0, Ordering::SeqCst);
 }
 
+/// Whether SIGINT/SIGTERM has been received in this process
+pub fn received() -> bool {
+    SIGNAL_RECEIVED.load(Ordering::SeqCst) != SIGNAL_NONE
+}
+
 /// Kill the child process group with SIGTERM
 fn kill_child_process_group() {
     let pid = CHILD_PID.load(Ordering::SeqCst);
@@ -34,6 +39,16 @@ fn kill_child_process_group() {
     }
 }
 
+/// Ask the current iteration's harness to exit (SIGTERM to its process
+/// group). Returns false when no iteration is running.
+pub fn terminate_child() -> bool {
+    if CHILD_PID.load(Ordering::SeqCst) <= 0 {
+        return false;
+    }
+    kill_child_process_group();
+    true
+}
+
 /// Kill the child process group with SIGKILL (forceful)
 fn force_kill_child_process_group() {
     let pid = CHILD_PID.load(Ordering::SeqCst);
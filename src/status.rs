@@ -0,0 +1,314 @@
+//! `hydra status` — plan progress and recent sessions for this project
+
+use crate::config::Config;
+use crate::error::{HydraError, Result};
+use crate::lock::{self, LockInfo};
+use crate::plan::{self, PLANS_DIR, Plan};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Number of recent sessions shown
+const RECENT_SESSIONS: usize = 5;
+
+/// Marker written by the runners when a session finishes
+const SESSION_ENDED_MARKER: &str = "Session ended: ";
+
+/// Everything `hydra status` reports
+#[derive(Debug, Serialize)]
+pub struct ProjectStatus {
+    pub plans: Vec<PlanStatus>,
+    pub sessions: Vec<SessionSummary>,
+    pub stop_file: String,
+    pub stop_file_pending: bool,
+    /// Sessions holding a lock in `.hydra/locks/`
+    pub running: Vec<LockInfo>,
+}
+
+/// Progress of one plan
+#[derive(Debug, Serialize)]
+pub struct PlanStatus {
+    pub path: PathBuf,
+    pub name: String,
+    pub checked: usize,
+    pub unchecked: usize,
+    pub scratchpad: Option<ScratchpadStatus>,
+}
+
+/// Size of a plan's scratchpad
+#[derive(Debug, Serialize)]
+pub struct ScratchpadStatus {
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub lines: usize,
+}
+
+/// Summary of a session log
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SessionSummary {
+    pub log: PathBuf,
+    pub plan: Option<String>,
+    /// Start time taken from the log filename (`YYYY-MM-DD HH:MM:SS`)
+    pub started: Option<String>,
+    /// Number of iterations that reached an end marker
+    pub iterations: u32,
+    /// Text after `Session ended:`, or `None` if the session never finished
+    pub outcome: Option<String>,
+}
+
+/// Collect the status of the project in the current directory
+pub fn collect(config: &Config, plan_filter: Option<&Path>) -> Result<ProjectStatus> {
+    let plan_paths = match plan_filter {
+        Some(path) => {
+            if !path.exists() {
+                return Err(HydraError::PlanNotFound(path.to_path_buf()));
+            }
+            vec![path.to_path_buf()]
+        }
+        None => plan::discover_plans(Path::new(PLANS_DIR)),
+    };
+
+    let mut plans = Vec::new();
+    for path in plan_paths {
+        let parsed = Plan::load(&path)?;
+        let name = plan::plan_name(&path);
+        plans.push(PlanStatus {
+            scratchpad: scratchpad_status(&Config::scratchpad_dir().join(format!("{}.md", name))),
+            checked: parsed.checked_count(),
+            unchecked: parsed.unchecked_count(),
+            name,
+            path,
+        });
+    }
+
+    let filter_name = plan_filter.map(plan::plan_name);
+    let sessions = recent_sessions(&Config::logs_dir(), filter_name.as_deref(), RECENT_SESSIONS);
+
+    Ok(ProjectStatus {
+        plans,
+        sessions,
+        stop_file_pending: Path::new(&config.stop_file).exists(),
+        stop_file: config.stop_file.clone(),
+        running: lock::running_sessions(),
+    })
+}
+
+/// Print the project status, as text or JSON
+pub fn status_command(config: &Config, plan_filter: Option<&Path>, json: bool) -> Result<()> {
+    let status = collect(config, plan_filter)?;
+
+    if json {
+        let out = serde_json::to_string_pretty(&status).map_err(|e| {
+            HydraError::io("serializing status", std::io::Error::other(e.to_string()))
+        })?;
+        println!("{}", out);
+        return Ok(());
+    }
+
+    println!("Plans:");
+    if status.plans.is_empty() {
+        println!("  (no plans found in {}/)", PLANS_DIR);
+    }
+    for p in &status.plans {
+        let scratchpad = match &p.scratchpad {
+            Some(s) => format!("scratchpad {} ({} lines)", format_bytes(s.bytes), s.lines),
+            None => "no scratchpad".to_string(),
+        };
+        println!(
+            "  {}  {}/{} done, {} remaining, {}",
+            p.path.display(),
+            p.checked,
+            p.checked + p.unchecked,
+            p.unchecked,
+            scratchpad
+        );
+    }
+
+    println!();
+    println!("Recent sessions:");
+    if status.sessions.is_empty() {
+        println!("  (none in {})", Config::logs_dir().display());
+    }
+    for s in &status.sessions {
+        println!(
+            "  {}  {}  {} iteration(s)  {}",
+            s.started.as_deref().unwrap_or("unknown time"),
+            s.plan.as_deref().unwrap_or("(no plan)"),
+            s.iterations,
+            s.outcome.as_deref().unwrap_or("did not finish")
+        );
+    }
+
+    println!();
+    if status.stop_file_pending {
+        println!("Stop file: pending ({})", status.stop_file);
+    } else {
+        println!("Stop file: none");
+    }
+    if status.running.is_empty() {
+        println!("Hydra process: not running");
+    }
+    for session in &status.running {
+        println!(
+            "Hydra process: running {} (PID {}, started {}{})",
+            session.name(),
+            session.pid,
+            session.started_at,
+            if session.tui { ", in hydra tui" } else { "" }
+        );
+    }
+
+    Ok(())
+}
+
+fn scratchpad_status(path: &Path) -> Option<ScratchpadStatus> {
+    let content = fs::read_to_string(path).ok()?;
+    Some(ScratchpadStatus {
+        path: path.to_path_buf(),
+        bytes: content.len() as u64,
+        lines: content.lines().count(),
+    })
+}
+
+/// Most recent session logs (newest first), optionally for one plan only
+fn recent_sessions(logs_dir: &Path, plan: Option<&str>, limit: usize) -> Vec<SessionSummary> {
+    let Ok(entries) = fs::read_dir(logs_dir) else {
+        return Vec::new();
+    };
+    let mut logs: Vec<(PathBuf, Option<String>, Option<NaiveDateTime>)> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "log"))
+        .map(|p| {
+            let (name, started) = parse_log_filename(&p);
+            (p, name, started)
+        })
+        .filter(|(_, name, _)| plan.is_none() || name.as_deref() == plan)
+        .collect();
+    logs.sort_by_key(|(_, _, started)| std::cmp::Reverse(*started));
+
+    logs.into_iter()
+        .take(limit)
+        .map(|(path, name, started)| {
+            let content = fs::read_to_string(&path).unwrap_or_default();
+            let (iterations, outcome) = summarize_log(&content);
+            SessionSummary {
+                log: path,
+                plan: name,
+                started: started.map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string()),
+                iterations,
+                outcome,
+            }
+        })
+        .collect()
+}
+
+/// Split `<plan>-YYYYMMDD-HHMMSS.log` into plan name and start time.
+/// Logs without a plan are named `hydra-YYYYMMDD-HHMMSS.log`.
+fn parse_log_filename(path: &Path) -> (Option<String>, Option<NaiveDateTime>) {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    // "-YYYYMMDD-HHMMSS" is 16 bytes
+    if stem.len() <= 16 || !stem.is_char_boundary(stem.len() - 16) {
+        return (None, None);
+    }
+    let (name, stamp) = stem.split_at(stem.len() - 16);
+    let Ok(started) = NaiveDateTime::parse_from_str(&stamp[1..], "%Y%m%d-%H%M%S") else {
+        return (None, None);
+    };
+    let name = (name != "hydra").then(|| name.to_string());
+    (name, Some(started))
+}
+
+/// Count finished iterations and extract the session outcome from a log
+fn summarize_log(content: &str) -> (u32, Option<String>) {
+    let mut iterations = 0;
+    let mut outcome = None;
+    for line in content.lines() {
+        // Log lines look like "[HH:MM:SS] message"
+        let message = line.split_once("] ").map_or(line, |(_, m)| m);
+        if message.starts_with("ITERATION ") && message.contains(" END: ") {
+            iterations += 1;
+        } else if let Some(rest) = message.strip_prefix(SESSION_ENDED_MARKER) {
+            outcome = Some(rest.to_string());
+        }
+    }
+    (iterations, outcome)
+}
+
+/// Human-readable byte count
+fn format_bytes(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_log_filename() {
+        let (name, started) = parse_log_filename(Path::new("my-plan-20260101-093000.log"));
+        assert_eq!(name.as_deref(), Some("my-plan"));
+        assert_eq!(
+            started.unwrap().format("%Y-%m-%d %H:%M").to_string(),
+            "2026-01-01 09:30"
+        );
+
+        let (name, started) = parse_log_filename(Path::new("hydra-20260101-093000.log"));
+        assert_eq!(name, None);
+        assert!(started.is_some());
+
+        assert_eq!(parse_log_filename(Path::new("random.log")), (None, None));
+    }
+
+    #[test]
+    fn test_summarize_log() {
+        let log = "[10:00:00] Session started - max iterations: 5\n\
+                   [10:00:01] ITERATION 1/5 START\n\
+                   assistant output mentioning ITERATION 9 END: nothing\n\
+                   [10:05:00] ITERATION 1 END: TASK_COMPLETE\n\
+                   [10:10:00] ITERATION 2 END: ALL_COMPLETE\n\
+                   [10:10:00] Session ended: all tasks complete after 2 iterations\n";
+        let (iterations, outcome) = summarize_log(log);
+        assert_eq!(iterations, 2);
+        assert_eq!(
+            outcome.as_deref(),
+            Some("all tasks complete after 2 iterations")
+        );
+
+        assert_eq!(summarize_log("[10:00:00] Session started\n"), (0, None));
+    }
+
+    #[test]
+    fn test_recent_sessions_newest_first_and_filtered() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("a-20260101-100000.log"),
+            "[10:00:00] Session ended: stop file detected\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("b-20260102-100000.log"), "").unwrap();
+        fs::write(dir.path().join("a-20260103-100000.log"), "").unwrap();
+
+        let all = recent_sessions(dir.path(), None, 10);
+        let plans: Vec<_> = all.iter().map(|s| s.plan.as_deref().unwrap()).collect();
+        assert_eq!(plans, vec!["a", "b", "a"]);
+
+        let only_a = recent_sessions(dir.path(), Some("a"), 10);
+        assert_eq!(only_a.len(), 2);
+        assert_eq!(only_a[1].outcome.as_deref(), Some("stop file detected"));
+
+        assert_eq!(recent_sessions(dir.path(), None, 1).len(), 1);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(2048), "2.0 KB");
+    }
+}
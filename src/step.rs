@@ -0,0 +1,440 @@
+//! Step mode: pause after iterations for human review
+//!
+//! With `--step`, the runners stop after each iteration, print a summary
+//! (result, duration, files changed, plan tasks flipped) and wait for a key:
+//! continue, retry, edit the plan or scratchpad in `$EDITOR`, skip or quit.
+//! `--step-on timeout,no-signal` limits the pause to those results.
+
+use crate::error::{HydraError, Result};
+use crate::history;
+use crate::plan::{Plan, PlanTask};
+use crate::runner::IterationResult;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+/// Prompt section injected when the operator asks for an iteration to be re-run
+pub const MANUAL_RETRY_NOTE: &str = "## Previous Attempt Rejected\n\n\
+     This is a retry of the same iteration. The operator reviewed the previous \
+     attempt and asked for it to be run again. Check the plan and scratchpad for \
+     notes they may have left, then redo the task.";
+
+/// Iteration results step mode can be limited to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepTrigger {
+    Timeout,
+    NoSignal,
+}
+
+impl StepTrigger {
+    /// Parse a `--step-on` value
+    fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "timeout" => Some(StepTrigger::Timeout),
+            "no-signal" => Some(StepTrigger::NoSignal),
+            _ => None,
+        }
+    }
+}
+
+/// When step mode pauses
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StepMode {
+    /// Results that trigger a pause; empty means after every iteration
+    triggers: Vec<StepTrigger>,
+}
+
+impl StepMode {
+    /// Build from `--step-on` (comma-separated `timeout`, `no-signal`); `None`
+    /// pauses after every iteration
+    pub fn new(step_on: Option<&str>) -> Result<Self> {
+        let Some(list) = step_on else {
+            return Ok(Self::default());
+        };
+        let mut triggers = Vec::new();
+        for item in list.split(',').filter(|s| !s.trim().is_empty()) {
+            let trigger = StepTrigger::parse(item).ok_or_else(|| {
+                HydraError::InvalidStepOn(format!("unknown value '{}'", item.trim()))
+            })?;
+            if !triggers.contains(&trigger) {
+                triggers.push(trigger);
+            }
+        }
+        // An empty list would silently mean "after every iteration"
+        if triggers.is_empty() {
+            return Err(HydraError::InvalidStepOn("no values given".to_string()));
+        }
+        Ok(Self { triggers })
+    }
+
+    /// Whether to pause after an iteration with this result. Results that
+    /// end the session never pause.
+    pub fn pauses_on(&self, result: &IterationResult) -> bool {
+        let trigger = match result {
+            IterationResult::TaskComplete => None,
+            IterationResult::NoSignal => Some(StepTrigger::NoSignal),
+            IterationResult::Timeout => Some(StepTrigger::Timeout),
+            // Questions get their own prompt; the others end the session
+            IterationResult::Question
+            | IterationResult::AllComplete
+            | IterationResult::Terminated => return false,
+        };
+        self.triggers.is_empty() || trigger.is_some_and(|t| self.triggers.contains(&t))
+    }
+
+    /// Description for dry-run and the session log
+    pub fn describe(&self) -> String {
+        if self.triggers.is_empty() {
+            return "after every iteration".to_string();
+        }
+        let names: Vec<&str> = self
+            .triggers
+            .iter()
+            .map(|t| match t {
+                StepTrigger::Timeout => "timeout",
+                StepTrigger::NoSignal => "no-signal",
+            })
+            .collect();
+        format!("on {}", names.join(", "))
+    }
+}
+
+/// What the operator chose at the step prompt
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepAction {
+    /// Carry on as without step mode (automatic retries still apply)
+    Continue,
+    /// Run the same iteration again
+    Retry,
+    /// Move on to the next iteration without an automatic retry
+    Skip,
+    /// End the session
+    Quit,
+}
+
+/// Repository and plan state captured before an iteration starts
+#[derive(Debug)]
+pub struct IterationSnapshot {
+    git_head: Option<String>,
+    /// Files already modified before the iteration, with their mtimes
+    dirty: Vec<(String, Option<SystemTime>)>,
+    tasks: Vec<PlanTask>,
+}
+
+impl IterationSnapshot {
+    /// Record the current commit, modified files and plan checkboxes
+    pub fn capture(plan_path: Option<&Path>) -> Self {
+        let git_head = git_output(&["rev-parse", "HEAD"]).map(|s| s.trim().to_string());
+        let dirty = git_head
+            .as_deref()
+            .and_then(changed_files)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|f| {
+                let mtime = modified_time(&f);
+                (f, mtime)
+            })
+            .collect();
+        Self {
+            git_head,
+            dirty,
+            tasks: load_tasks(plan_path),
+        }
+    }
+
+    /// Files changed since the snapshot: new changes relative to the starting
+    /// commit, plus already-modified files that were written again
+    fn files_changed_since(&self) -> Option<Vec<String>> {
+        let files = changed_files(self.git_head.as_deref()?)?;
+        Some(
+            files
+                .into_iter()
+                .filter(|f| match self.dirty.iter().find(|(d, _)| d == f) {
+                    Some((_, mtime)) => modified_time(f) != *mtime,
+                    None => true,
+                })
+                .collect(),
+        )
+    }
+}
+
+/// What happened during one iteration, shown at the step prompt
+#[derive(Debug)]
+pub struct IterationSummary {
+    pub iteration: u32,
+    pub result: &'static str,
+    pub elapsed: Duration,
+    /// Files written during the iteration (`None` outside a git repository)
+    pub files_changed: Option<Vec<String>>,
+    /// Tasks whose checkbox changed, with their new state
+    pub tasks_flipped: Vec<(bool, String)>,
+}
+
+impl IterationSummary {
+    /// Compare the current state against the snapshot taken before the iteration
+    pub fn collect(
+        before: &IterationSnapshot,
+        plan_path: Option<&Path>,
+        iteration: u32,
+        result: &IterationResult,
+        elapsed: Duration,
+    ) -> Self {
+        Self {
+            iteration,
+            result: result.label(),
+            elapsed,
+            files_changed: before.files_changed_since(),
+            tasks_flipped: flipped_tasks(&before.tasks, &load_tasks(plan_path)),
+        }
+    }
+
+    /// Print the summary block
+    pub fn print(&self) {
+        println!();
+        println!(
+            "─── iteration {} review: {} in {} ───",
+            self.iteration,
+            self.result,
+            history::format_duration(self.elapsed.as_secs())
+        );
+        match &self.files_changed {
+            None => println!("  Files changed: (not a git repository)"),
+            Some(files) if files.is_empty() => println!("  Files changed: none"),
+            Some(files) => {
+                println!("  Files changed: {}", files.len());
+                for file in files {
+                    println!("    {}", file);
+                }
+            }
+        }
+        if self.tasks_flipped.is_empty() {
+            println!("  Tasks flipped: none");
+        } else {
+            println!("  Tasks flipped:");
+            for (checked, text) in &self.tasks_flipped {
+                println!("    [{}] {}", if *checked { "x" } else { " " }, text);
+            }
+        }
+    }
+
+    /// One-line form for the session log
+    pub fn log_line(&self) -> String {
+        format!(
+            "ITERATION {} REVIEW: {} in {}s, {} file(s) changed, {} task(s) flipped",
+            self.iteration,
+            self.result,
+            self.elapsed.as_secs(),
+            self.files_changed.as_ref().map_or(0, Vec::len),
+            self.tasks_flipped.len()
+        )
+    }
+}
+
+/// Ask the operator what to do next. Editing the plan or scratchpad returns
+/// to the prompt afterwards.
+pub fn prompt_action(
+    plan_path: Option<&Path>,
+    scratchpad_path: Option<&Path>,
+) -> Result<StepAction> {
+    loop {
+        let mut menu = String::from("[hydra] [c]ontinue  [r]etry  [s]kip  [q]uit");
+        if plan_path.is_some() {
+            menu.push_str("  [p] edit plan");
+        }
+        if scratchpad_path.is_some() {
+            menu.push_str("  [e] edit scratchpad");
+        }
+        print!("{} ", menu);
+        io::stdout()
+            .flush()
+            .map_err(|e| HydraError::io("flushing stdout", e))?;
+
+        let key = read_key()?;
+        println!();
+        match key {
+            'c' | '\n' => return Ok(StepAction::Continue),
+            'r' => return Ok(StepAction::Retry),
+            's' => return Ok(StepAction::Skip),
+            'q' => return Ok(StepAction::Quit),
+            'p' => {
+                if let Some(path) = plan_path {
+                    open_in_editor(path)?;
+                }
+            }
+            'e' => {
+                if let Some(path) = scratchpad_path {
+                    open_in_editor(path)?;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Read a single key (lowercased; Enter is `\n`, Ctrl+C is `q`). Without a
+/// terminal a whole line is read instead and end of input means quit.
+fn read_key() -> Result<char> {
+    if !io::stdin().is_terminal() {
+        let mut line = String::new();
+        let read = io::stdin()
+            .lock()
+            .read_line(&mut line)
+            .map_err(|e| HydraError::io("reading step choice", e))?;
+        if read == 0 {
+            return Ok('q');
+        }
+        return Ok(line
+            .trim()
+            .chars()
+            .next()
+            .map_or('\n', |c| c.to_ascii_lowercase()));
+    }
+
+    enable_raw_mode()
+        .map_err(|e| HydraError::io("enabling raw mode", io::Error::other(e.to_string())))?;
+    let key = loop {
+        let event = match event::read() {
+            Ok(event) => event,
+            Err(e) => {
+                let _ = disable_raw_mode();
+                return Err(HydraError::io(
+                    "reading key",
+                    io::Error::other(e.to_string()),
+                ));
+            }
+        };
+        let Event::Key(key) = event else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break 'q',
+            KeyCode::Char(c) => break c.to_ascii_lowercase(),
+            KeyCode::Enter => break '\n',
+            _ => {}
+        }
+    };
+    let _ = disable_raw_mode();
+    Ok(key)
+}
+
+/// Open a file in `$VISUAL` / `$EDITOR` (default `vi`) and wait for it to exit
+fn open_in_editor(path: &Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // Allow editors with arguments, e.g. EDITOR="code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .map_err(|e| HydraError::io(format!("launching editor {}", program), e))?;
+    if !status.success() {
+        eprintln!("[hydra] Warning: {} exited with {}", program, status);
+    }
+    Ok(())
+}
+
+fn load_tasks(plan_path: Option<&Path>) -> Vec<PlanTask> {
+    plan_path
+        .and_then(|p| Plan::load(p).ok())
+        .map(|p| p.tasks)
+        .unwrap_or_default()
+}
+
+/// Run git and return stdout, or `None` if git fails or isn't available
+fn git_output(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Tracked files changed since `base` plus untracked files, sorted. Hydra's
+/// own state in `.hydra/` is left out.
+fn changed_files(base: &str) -> Option<Vec<String>> {
+    let tracked = git_output(&["diff", "--name-only", base])?;
+    let untracked = git_output(&["ls-files", "--others", "--exclude-standard"])?;
+    let mut files: Vec<String> = tracked
+        .lines()
+        .chain(untracked.lines())
+        .filter(|l| !l.is_empty() && !l.starts_with(".hydra/"))
+        .map(str::to_string)
+        .collect();
+    files.sort();
+    files.dedup();
+    Some(files)
+}
+
+/// Tasks whose checkbox state changed, matched by text
+fn flipped_tasks(before: &[PlanTask], after: &[PlanTask]) -> Vec<(bool, String)> {
+    let mut unmatched: Vec<&PlanTask> = before.iter().collect();
+    let mut flipped = Vec::new();
+    for task in after {
+        let Some(pos) = unmatched.iter().position(|t| t.text == task.text) else {
+            continue;
+        };
+        let old = unmatched.remove(pos);
+        if old.checked != task.checked {
+            flipped.push((task.checked, task.text.clone()));
+        }
+    }
+    flipped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_mode_pauses_on() {
+        let every = StepMode::new(None).unwrap();
+        assert!(every.pauses_on(&IterationResult::TaskComplete));
+        assert!(every.pauses_on(&IterationResult::Timeout));
+        assert!(!every.pauses_on(&IterationResult::AllComplete));
+        assert!(!every.pauses_on(&IterationResult::Terminated));
+
+        let timeouts = StepMode::new(Some("timeout")).unwrap();
+        assert!(timeouts.pauses_on(&IterationResult::Timeout));
+        assert!(!timeouts.pauses_on(&IterationResult::NoSignal));
+        assert!(!timeouts.pauses_on(&IterationResult::TaskComplete));
+    }
+
+    #[test]
+    fn test_step_on_parsing() {
+        let mode = StepMode::new(Some("no-signal, timeout,timeout")).unwrap();
+        assert_eq!(mode.describe(), "on no-signal, timeout");
+        assert_eq!(
+            StepMode::new(None).unwrap().describe(),
+            "after every iteration"
+        );
+        assert!(StepMode::new(Some("task-complete")).is_err());
+        assert!(StepMode::new(Some("")).is_err());
+        assert!(StepMode::new(Some(" , ")).is_err());
+    }
+
+    #[test]
+    fn test_flipped_tasks() {
+        let before = Plan::parse("- [ ] one\n- [ ] two\n- [x] three\n").tasks;
+        let after = Plan::parse("- [x] one\n- [ ] two\n- [ ] three\n- [ ] four\n").tasks;
+        assert_eq!(
+            flipped_tasks(&before, &after),
+            vec![(true, "one".to_string()), (false, "three".to_string())]
+        );
+        assert!(flipped_tasks(&before, &before).is_empty());
+    }
+}
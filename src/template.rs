@@ -0,0 +1,382 @@
+//! Prompt templates
+//!
+//! Prompt files may use `{{variable}}` placeholders and
+//! `{{#if variable}}…{{else}}…{{/if}}` blocks (`{{#if !variable}}` negates).
+//! Templates are rendered before every iteration, so values like
+//! `{{iteration}}` and `{{next_task}}` stay current. A variable is true in a
+//! condition when it has a non-empty value.
+//!
+//! Variables: `plan_path`, `scratchpad_path`, `question_path`, `iteration`,
+//! `max_iterations`, `next_task`, `git_branch` and `env.NAME` for any
+//! environment variable.
+//!
+//! Only these names are placeholders. Any other `{{` (a Handlebars or Jinja
+//! snippet, a Rust format string) is left as literal text, as are
+//! `{{else}}` and `{{/if}}` outside an `{{#if}}` block.
+
+use crate::error::{HydraError, Result};
+use crate::plan::Plan;
+use crate::prompt::ResolvedPrompt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Variables other than `env.*`
+const VARIABLES: &[&str] = &[
+    "plan_path",
+    "scratchpad_path",
+    "question_path",
+    "iteration",
+    "max_iterations",
+    "next_task",
+    "git_branch",
+];
+
+/// Values available to a template for one iteration
+#[derive(Debug, Clone, Default)]
+pub struct TemplateVars {
+    pub plan_path: Option<PathBuf>,
+    pub scratchpad_path: Option<PathBuf>,
+    pub question_path: Option<PathBuf>,
+    pub iteration: u32,
+    pub max_iterations: u32,
+    pub next_task: Option<String>,
+    pub git_branch: Option<String>,
+}
+
+impl TemplateVars {
+    /// Gather values for an iteration: the next task is read from the plan
+    /// and the branch from git
+    pub fn collect(
+        plan_path: Option<&Path>,
+        scratchpad_path: Option<&Path>,
+        question_path: Option<&Path>,
+        iteration: u32,
+        max_iterations: u32,
+    ) -> Self {
+        Self {
+            plan_path: plan_path.map(Path::to_path_buf),
+            scratchpad_path: scratchpad_path.map(Path::to_path_buf),
+            question_path: question_path.map(Path::to_path_buf),
+            iteration,
+            max_iterations,
+            next_task: plan_path
+                .and_then(|p| Plan::load(p).ok())
+                .and_then(|plan| plan.next_unchecked().map(|t| t.text.clone())),
+            git_branch: git_branch(),
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<String> {
+        if let Some(var) = name.strip_prefix("env.") {
+            return std::env::var(var).ok();
+        }
+        match name {
+            "plan_path" => self.plan_path.as_ref().map(|p| p.display().to_string()),
+            "scratchpad_path" => self
+                .scratchpad_path
+                .as_ref()
+                .map(|p| p.display().to_string()),
+            "question_path" => self.question_path.as_ref().map(|p| p.display().to_string()),
+            "iteration" => Some(self.iteration.to_string()),
+            "max_iterations" => Some(self.max_iterations.to_string()),
+            "next_task" => self.next_task.clone(),
+            "git_branch" => self.git_branch.clone(),
+            _ => None,
+        }
+    }
+}
+
+/// Whether prompt content uses template syntax: a placeholder or block,
+/// or a block that fails to parse
+pub fn is_template(content: &str) -> bool {
+    content.contains("{{")
+        && parse(content).map_or(true, |nodes| {
+            nodes.iter().any(|n| !matches!(n, Node::Text(_)))
+        })
+}
+
+/// Whether a template refers to `name`, in a placeholder or a condition.
+/// Content that fails to parse refers to nothing; rendering reports the error.
+pub fn references(content: &str, name: &str) -> bool {
+    if !is_template(content) {
+        return false;
+    }
+    parse(content).is_ok_and(|nodes| nodes_reference(&nodes, name))
+}
+
+/// Render a resolved prompt; content without template syntax is returned as is
+pub fn render_prompt(prompt: &ResolvedPrompt, vars: &TemplateVars) -> Result<String> {
+    if !is_template(&prompt.content) {
+        return Ok(prompt.content.clone());
+    }
+    render(&prompt.content, vars).map_err(|message| HydraError::PromptTemplate {
+        path: prompt.path.clone(),
+        message,
+    })
+}
+
+/// Render a template; the error describes the first unclosed block
+pub fn render(content: &str, vars: &TemplateVars) -> std::result::Result<String, String> {
+    let nodes = parse(content)?;
+    let mut out = String::with_capacity(content.len());
+    render_nodes(&nodes, vars, &mut out);
+    Ok(out)
+}
+
+#[derive(Debug, PartialEq)]
+enum Node {
+    Text(String),
+    Var(String),
+    If {
+        name: String,
+        negate: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+/// Tag found between `{{` and `}}`
+enum Tag<'a> {
+    Var(&'a str),
+    If(&'a str, bool),
+    Else,
+    EndIf,
+}
+
+fn parse(content: &str) -> std::result::Result<Vec<Node>, String> {
+    let mut rest = content;
+    let mut line = 1;
+    let (nodes, _) = parse_block(&mut rest, &mut line, false)?;
+    Ok(nodes)
+}
+
+/// Parse until the end of input or, inside an `{{#if}}` block, an `else` /
+/// `/if` tag, which is returned. `line` tracks the current line for error
+/// messages.
+fn parse_block(
+    rest: &mut &str,
+    line: &mut usize,
+    in_if: bool,
+) -> std::result::Result<(Vec<Node>, Option<&'static str>), String> {
+    let mut nodes = Vec::new();
+    let mut text = String::new();
+    loop {
+        let Some(start) = rest.find("{{") else {
+            text.push_str(rest);
+            *rest = "";
+            push_text(&mut nodes, &mut text);
+            return Ok((nodes, None));
+        };
+        let before = &rest[..start];
+        *line += before.matches('\n').count();
+        text.push_str(before);
+        let after = &rest[start + 2..];
+        let tag = after.find("}}").and_then(|end| {
+            parse_tag(after[..end].trim(), in_if).map(|tag| (tag, &after[..end], end))
+        });
+        let Some((tag, inner, end)) = tag else {
+            // Not one of ours: keep the braces and look for a tag after them
+            text.push_str("{{");
+            *rest = after;
+            continue;
+        };
+        push_text(&mut nodes, &mut text);
+        *line += inner.matches('\n').count();
+        *rest = &after[end + 2..];
+
+        match tag {
+            Tag::Var(name) => nodes.push(Node::Var(name.to_string())),
+            Tag::Else => return Ok((nodes, Some("else"))),
+            Tag::EndIf => return Ok((nodes, Some("/if"))),
+            Tag::If(name, negate) => {
+                let unclosed = format!("line {}: {{{{#if {}}}}} is not closed", line, name);
+                let (then, end) = parse_block(rest, line, true)?;
+                let otherwise = match end {
+                    Some("/if") => Vec::new(),
+                    Some(_) => match parse_block(rest, line, true)? {
+                        (nodes, Some("/if")) => nodes,
+                        _ => return Err(unclosed),
+                    },
+                    None => return Err(unclosed),
+                };
+                nodes.push(Node::If {
+                    name: name.to_string(),
+                    negate,
+                    then,
+                    otherwise,
+                });
+            }
+        }
+    }
+}
+
+fn push_text(nodes: &mut Vec<Node>, text: &mut String) {
+    if !text.is_empty() {
+        nodes.push(Node::Text(std::mem::take(text)));
+    }
+}
+
+/// The tag between `{{` and `}}`, or `None` when it isn't template syntax
+fn parse_tag(inner: &str, in_if: bool) -> Option<Tag<'_>> {
+    match inner {
+        "else" if in_if => return Some(Tag::Else),
+        "/if" if in_if => return Some(Tag::EndIf),
+        _ => {}
+    }
+    if let Some(cond) = inner.strip_prefix("#if") {
+        let cond = cond.trim();
+        let (name, negate) = match cond.strip_prefix('!') {
+            Some(name) => (name.trim(), true),
+            None => (cond, false),
+        };
+        return is_variable(name).then_some(Tag::If(name, negate));
+    }
+    is_variable(inner).then_some(Tag::Var(inner))
+}
+
+fn is_variable(name: &str) -> bool {
+    VARIABLES.contains(&name)
+        || name
+            .strip_prefix("env.")
+            .is_some_and(|var| !var.is_empty() && !var.contains(char::is_whitespace))
+}
+
+fn render_nodes(nodes: &[Node], vars: &TemplateVars, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var(name) => out.push_str(&vars.get(name).unwrap_or_default()),
+            Node::If {
+                name,
+                negate,
+                then,
+                otherwise,
+            } => {
+                let set = vars.get(name).is_some_and(|v| !v.is_empty());
+                let branch = if set != *negate { then } else { otherwise };
+                render_nodes(branch, vars, out);
+            }
+        }
+    }
+}
+
+fn nodes_reference(nodes: &[Node], name: &str) -> bool {
+    nodes.iter().any(|node| match node {
+        Node::Text(_) => false,
+        Node::Var(var) => var == name,
+        Node::If {
+            name: cond,
+            then,
+            otherwise,
+            ..
+        } => cond == name || nodes_reference(then, name) || nodes_reference(otherwise, name),
+    })
+}
+
+/// Current git branch, if the working directory is in a repository
+fn git_branch() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .output()
+        .ok()?;
+    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !branch.is_empty()).then_some(branch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> TemplateVars {
+        TemplateVars {
+            plan_path: Some(PathBuf::from("plans/api.md")),
+            iteration: 3,
+            max_iterations: 20,
+            next_task: Some("Add login endpoint".to_string()),
+            ..TemplateVars::default()
+        }
+    }
+
+    #[test]
+    fn test_render_variables() {
+        let out = render(
+            "Plan {{plan_path}}, iteration {{ iteration }}/{{max_iterations}}: {{next_task}}",
+            &vars(),
+        )
+        .unwrap();
+        assert_eq!(out, "Plan plans/api.md, iteration 3/20: Add login endpoint");
+
+        // Unset values render empty
+        assert_eq!(render("[{{scratchpad_path}}]", &vars()).unwrap(), "[]");
+    }
+
+    #[test]
+    fn test_render_conditionals() {
+        let template = "{{#if scratchpad_path}}notes{{else}}no notes{{/if}}\
+                        {{#if next_task}} / next{{/if}}\
+                        {{#if !scratchpad_path}} / none{{/if}}";
+        assert_eq!(render(template, &vars()).unwrap(), "no notes / next / none");
+
+        let mut with_notes = vars();
+        with_notes.scratchpad_path = Some(PathBuf::from("s.md"));
+        assert_eq!(render(template, &with_notes).unwrap(), "notes / next");
+
+        // Nested blocks
+        let nested = "{{#if plan_path}}A{{#if next_task}}B{{else}}C{{/if}}{{/if}}";
+        assert_eq!(render(nested, &vars()).unwrap(), "AB");
+    }
+
+    #[test]
+    fn test_render_env() {
+        // PATH is set in any test environment
+        let out = render(
+            "{{#if env.PATH}}yes{{/if}}{{env.HYDRA_SURELY_UNSET_VAR}}",
+            &vars(),
+        );
+        assert_eq!(out.unwrap(), "yes");
+    }
+
+    #[test]
+    fn test_render_errors() {
+        assert!(
+            render("a\n{{#if plan_path}}x", &vars())
+                .unwrap_err()
+                .starts_with("line 2:")
+        );
+        assert!(
+            render("{{#if next_task}}x{{else}}y", &vars())
+                .unwrap_err()
+                .contains("is not closed")
+        );
+    }
+
+    #[test]
+    fn test_other_braces_are_literal() {
+        for literal in [
+            "Render {{ foo }} with Jinja",
+            "{{#if user}}Hi {{name}}{{else}}Bye{{/if}}",
+            "println!(\"{{}}\", x); {{plan}} {{iteration",
+            "{{{{raw}}}}",
+        ] {
+            assert_eq!(render(literal, &vars()).unwrap(), literal);
+            assert!(!is_template(literal));
+        }
+        assert_eq!(
+            render("{{ foo {{iteration}} }}", &vars()).unwrap(),
+            "{{ foo 3 }}"
+        );
+        assert!(is_template("{{ foo }} {{iteration}}"));
+    }
+
+    #[test]
+    fn test_references() {
+        assert!(references("See {{plan_path}}", "plan_path"));
+        assert!(references(
+            "{{#if scratchpad_path}}x{{/if}}",
+            "scratchpad_path"
+        ));
+        assert!(!references("See {{plan_path}}", "scratchpad_path"));
+        assert!(!references("plain prompt, plan_path", "plan_path"));
+        assert!(!is_template("plain prompt"));
+    }
+}
@@ -0,0 +1,212 @@
+//! Tab activity and attention for TUI mode
+//!
+//! A tab off screen needs attention when its agent exited with an error,
+//! looks like it waits for input, has been idle for `tui_idle_minutes`, or
+//! just had output since it was last on screen. The tab bar shows a badge for
+//! the most pressing of these, `tui_notify` rings the bell or sends a desktop
+//! notification when a tab comes to need more than a look, and a key jumps
+//! to the next tab needing attention.
+
+use crate::tui::protocol::TabInfo;
+use crate::tui::tab::TabStatus;
+use serde::{Deserialize, Serialize};
+
+/// Why a tab needs attention, least pressing first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Attention {
+    /// Output since the tab was last on screen
+    Output,
+    /// No output for `tui_idle_minutes`
+    Idle,
+    /// A question, or a screen that looks like a prompt
+    Waiting,
+    /// An agent exited with an error since the tab was last on screen
+    Error,
+}
+
+impl Attention {
+    /// Tab bar badge
+    pub fn badge(self) -> &'static str {
+        match self {
+            Attention::Output => "•",
+            Attention::Idle => "z",
+            Attention::Waiting => "?",
+            Attention::Error => "!",
+        }
+    }
+
+    /// What happened, e.g. `idle for 12 min`
+    pub fn describe(self, info: &TabInfo) -> String {
+        match self {
+            Attention::Output => "new output".to_string(),
+            Attention::Idle => format!("idle for {} min", info.idle_seconds / 60),
+            Attention::Waiting => "waiting for input".to_string(),
+            Attention::Error => info
+                .exit_error
+                .clone()
+                .unwrap_or_else(|| "exited with an error".to_string()),
+        }
+    }
+}
+
+/// How the TUI tells about a tab that comes to need attention
+/// (`tui_notify`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Notify {
+    #[default]
+    Off,
+    /// Terminal bell
+    Bell,
+    /// Desktop notification through the terminal (OSC 9)
+    Desktop,
+}
+
+impl Notify {
+    /// Bytes for the terminal that notify about `message`
+    pub fn sequence(self, message: &str) -> Option<String> {
+        match self {
+            Notify::Off => None,
+            Notify::Bell => Some("\x07".to_string()),
+            Notify::Desktop => {
+                // Control characters would end the sequence early
+                let message: String = message.chars().filter(|c| !c.is_control()).collect();
+                Some(format!("\x1b]9;{}\x07", message))
+            }
+        }
+    }
+}
+
+/// What a client has seen of a tab
+#[derive(Debug, Default)]
+pub struct Activity {
+    /// Output arrived while the tab was off screen
+    unseen_output: bool,
+    /// `failed_exits` when the tab was last on screen
+    seen_failures: u32,
+    /// Attention last notified about, while it lasts
+    notified: Option<Attention>,
+    /// The next output is a replay of the screen, not new output
+    pub replaying: bool,
+}
+
+impl Activity {
+    /// Output arrived for the tab
+    pub fn output(&mut self) {
+        if self.replaying {
+            self.replaying = false;
+        } else {
+            self.unseen_output = true;
+        }
+    }
+
+    /// The tab is on screen
+    pub fn viewed(&mut self, info: &TabInfo) {
+        self.unseen_output = false;
+        self.seen_failures = info.failed_exits;
+        self.notified = None;
+    }
+
+    /// Why the tab needs attention, the most pressing reason
+    pub fn attention(&self, info: &TabInfo, idle_minutes: u64) -> Option<Attention> {
+        let running = info.status == TabStatus::Running;
+        if info.failed_exits > self.seen_failures {
+            Some(Attention::Error)
+        } else if info.status == TabStatus::Question || (running && info.waiting) {
+            Some(Attention::Waiting)
+        } else if running && idle_minutes > 0 && info.idle_seconds >= idle_minutes * 60 {
+            Some(Attention::Idle)
+        } else if self.unseen_output {
+            Some(Attention::Output)
+        } else {
+            None
+        }
+    }
+
+    /// Note the tab's attention; true when it rose to something more than
+    /// output, worth a notification
+    pub fn rose(&mut self, attention: Option<Attention>) -> bool {
+        let level = attention.filter(|a| *a > Attention::Output);
+        let rose = level > self.notified;
+        self.notified = level;
+        rose
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::Harness;
+
+    fn info() -> TabInfo {
+        TabInfo {
+            key: 1,
+            status: TabStatus::Running,
+            harness: Harness::Claude,
+            plan_path: None,
+            scratchpad_path: None,
+            iteration: 1,
+            max_iterations: 20,
+            elapsed_seconds: 0,
+            timeout_seconds: 600,
+            rows: 24,
+            cols: 80,
+            idle_seconds: 0,
+            waiting: false,
+            failed_exits: 0,
+            exit_error: None,
+            question: None,
+        }
+    }
+
+    #[test]
+    fn test_attention_by_priority() {
+        let mut activity = Activity::default();
+        let mut info = info();
+        assert_eq!(activity.attention(&info, 5), None);
+
+        // A replay isn't new output
+        activity.replaying = true;
+        activity.output();
+        assert_eq!(activity.attention(&info, 5), None);
+        activity.output();
+        assert_eq!(activity.attention(&info, 5), Some(Attention::Output));
+
+        info.idle_seconds = 300;
+        assert_eq!(activity.attention(&info, 5), Some(Attention::Idle));
+        assert_eq!(Attention::Idle.describe(&info), "idle for 5 min");
+        assert_eq!(activity.attention(&info, 0), Some(Attention::Output));
+        info.waiting = true;
+        assert_eq!(activity.attention(&info, 5), Some(Attention::Waiting));
+        info.failed_exits = 1;
+        info.exit_error = Some("exited with code 2".to_string());
+        assert_eq!(activity.attention(&info, 5), Some(Attention::Error));
+        assert_eq!(Attention::Error.describe(&info), "exited with code 2");
+
+        // Seeing the tab clears what happened, not what it is doing
+        activity.viewed(&info);
+        assert_eq!(activity.attention(&info, 5), Some(Attention::Waiting));
+        info.status = TabStatus::AllComplete;
+        assert_eq!(activity.attention(&info, 5), None);
+    }
+
+    #[test]
+    fn test_notify_when_attention_rises() {
+        let mut activity = Activity::default();
+        assert!(!activity.rose(Some(Attention::Output)));
+        assert!(activity.rose(Some(Attention::Idle)));
+        assert!(!activity.rose(Some(Attention::Idle)));
+        assert!(activity.rose(Some(Attention::Error)));
+        assert!(!activity.rose(Some(Attention::Waiting)));
+        // Once it passed, the same attention notifies again
+        assert!(!activity.rose(None));
+        assert!(activity.rose(Some(Attention::Waiting)));
+
+        assert_eq!(Notify::Off.sequence("x"), None);
+        assert_eq!(Notify::Bell.sequence("x").unwrap(), "\x07");
+        assert_eq!(
+            Notify::Desktop.sequence("tab 2\x07 waits").unwrap(),
+            "\x1b]9;tab 2 waits\x07"
+        );
+    }
+}
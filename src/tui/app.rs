@@ -1,395 +1,1030 @@
 //! Application state for TUI mode
 //!
-//! Manages tabs, each containing a PTY instance running Claude.
+//! The client side of `hydra tui`: mirrors the daemon's tabs (see
+//! `daemon.rs`), each with its own vt100 parser fed the tab's output, and
+//! sends keyboard input and tab requests back. While split, the panes' tabs
+//! have their screens sized to their pane (see `pane.rs`).
 
 use crate::config::Config;
 use crate::error::{HydraError, Result};
+use crate::harness::Harness;
+use crate::lint;
+use crate::plan::PLANS_DIR;
 use crate::prompt::ResolvedPrompt;
-use crate::pty::{PtyManager, PtyResult};
-use std::io::{Read, Write};
-use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::{self, Receiver, TryRecvError};
-use std::thread::{self, JoinHandle};
-use tempfile::NamedTempFile;
-
-/// Maximum number of tabs allowed
-pub const MAX_TABS: usize = 7;
-
-/// Status of a tab's Claude process
-#[derive(Debug, Clone, PartialEq)]
-pub enum TabStatus {
-    /// Claude is actively running
-    Running,
-    /// Claude completed with TaskComplete signal
-    TaskComplete,
-    /// Claude completed with AllTasksComplete signal
-    AllComplete,
-    /// Claude process was stopped/killed
-    Stopped,
-}
+use crate::tui::activity::{Activity, Attention};
+use crate::tui::copy::{self, CopyMode};
+use crate::tui::driver::TabSession;
+use crate::tui::pane::{self, Arrangement, MAX_PANES, Panes};
+use crate::tui::picker::Picker;
+use crate::tui::protocol::{self, Connection, Event, Frame, PaneSize, Request, TabInfo};
+use crate::tui::sidebar::Sidebar;
+use crate::tui::tab;
+use ratatui::layout::Rect;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
 
-/// A single tab containing a PTY session
-pub struct Tab {
-    /// Tab number (1-9)
-    pub id: u8,
-    /// vt100 terminal parser for interpreting escape sequences
+/// A daemon tab as this client shows it
+pub struct TabView {
+    /// Tab number, its position in the tab bar
+    pub id: usize,
+    /// vt100 terminal parser fed the tab's output; its scrollback offset is
+    /// the tab's scroll position (0 = following output)
     pub parser: vt100::Parser,
-    /// Current status of the tab
-    pub status: TabStatus,
-    /// PTY manager for this tab (None after PTY closes)
-    pty_manager: Option<PtyManager>,
-    /// PTY writer for sending input
-    pty_writer: Option<Box<dyn Write + Send>>,
-    /// Receiver for PTY output from reader thread
-    pty_rx: Option<Receiver<PtyMessage>>,
-    /// Reader thread handle
-    _reader_thread: Option<JoinHandle<()>>,
-    /// Stop flag for this tab's PTY
-    stop_flag: Arc<AtomicBool>,
+    /// Status, plan and iteration, as the daemon last sent them
+    pub info: TabInfo,
+    /// What this client has seen of the tab
+    pub activity: Activity,
 }
 
-/// Messages from PTY reader thread
-enum PtyMessage {
-    Data(Vec<u8>),
-    Closed,
+impl TabView {
+    /// Lines the view is scrolled back from live output (0 = following)
+    pub fn scroll_offset(&self) -> usize {
+        self.parser.screen().scrollback()
+    }
+
+    /// Scroll back into the scrollback (clamped to its length)
+    pub fn scroll_up(&mut self, lines: usize) {
+        let offset = self.scroll_offset() + lines;
+        self.parser.screen_mut().set_scrollback(offset);
+    }
+
+    /// Scroll towards live output
+    pub fn scroll_down(&mut self, lines: usize) {
+        let offset = self.scroll_offset().saturating_sub(lines);
+        self.parser.screen_mut().set_scrollback(offset);
+    }
 }
 
-impl Tab {
-    /// Create a new tab and spawn Claude with specified terminal dimensions
-    fn new(id: u8, prompt: &ResolvedPrompt, rows: u16, cols: u16) -> Result<Self> {
-        let stop_flag = Arc::new(AtomicBool::new(false));
-        // Use specified size so Claude sees correct terminal dimensions
-        let mut pty_manager = PtyManager::new_with_size(Arc::clone(&stop_flag), rows, cols)?;
-
-        // Create temp file with prompt content
-        let mut prompt_file =
-            NamedTempFile::new().map_err(|e| HydraError::io("creating temp prompt file", e))?;
-        prompt_file
-            .write_all(prompt.content.as_bytes())
-            .map_err(|e| HydraError::io("writing prompt content", e))?;
-
-        // Spawn Claude in PTY
-        pty_manager.spawn_claude(prompt_file.path())?;
-
-        // Get PTY reader and writer
-        let (pty_reader, pty_writer) = pty_manager.take_reader_writer()?;
-
-        // Spawn reader thread
-        let (tx, rx) = mpsc::channel();
-        let reader_stop_flag = Arc::clone(&stop_flag);
-        let reader_thread = thread::spawn(move || {
-            Self::reader_thread(pty_reader, tx, reader_stop_flag);
-        });
+/// An answer being typed to a tab's question
+pub struct AnswerInput {
+    /// Key of the tab asking
+    pub key: u64,
+    pub question: String,
+    pub text: String,
+}
 
-        Ok(Self {
-            id,
-            // Initialize vt100 parser with same dimensions
-            parser: vt100::Parser::new(rows, cols, 0),
-            status: TabStatus::Running,
-            pty_manager: Some(pty_manager),
-            pty_writer: Some(pty_writer),
-            pty_rx: Some(rx),
-            _reader_thread: Some(reader_thread),
-            stop_flag,
-        })
-    }
-
-    /// PTY reader thread - reads output and sends to main thread
-    fn reader_thread(
-        mut reader: Box<dyn Read + Send>,
-        tx: mpsc::Sender<PtyMessage>,
-        stop_flag: Arc<AtomicBool>,
-    ) {
-        let mut buf = [0u8; 4096];
+/// Plain text of the scrollback followed by the screen, one string per
+/// line, and the index of the first line in view at the scroll position
+fn text_lines(parser: &mut vt100::Parser) -> (Vec<String>, usize) {
+    let offset = parser.screen().scrollback();
+    let cols = parser.screen().size().1;
+    let mut lines = Vec::new();
+    let history = tab::walk_scrollback(parser, |screen, count| {
+        lines.extend(screen.rows(0, cols).take(count));
+    });
+    lines.extend(parser.screen().rows(0, cols));
+    parser.screen_mut().set_scrollback(offset);
+    (lines, history - offset.min(history))
+}
 
-        loop {
-            if stop_flag.load(Ordering::SeqCst) {
-                break;
-            }
+/// An error message on one line, for the picker or a notice
+fn one_line(message: &str) -> String {
+    message
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
-            match reader.read(&mut buf) {
-                Ok(0) => {
-                    let _ = tx.send(PtyMessage::Closed);
-                    break;
-                }
-                Ok(n) => {
-                    if tx.send(PtyMessage::Data(buf[..n].to_vec())).is_err() {
-                        break;
+/// Main application state
+pub struct App {
+    /// All tabs, in tab bar order
+    pub tabs: Vec<TabView>,
+    /// Currently active tab index
+    pub active_tab_index: usize,
+    /// Shared configuration
+    config: Config,
+    /// Prompt new tabs start from, before composition
+    prompt: ResolvedPrompt,
+    /// Iteration instructions for every tab
+    instructions: String,
+    /// Harness the picker starts with (`--harness` or `.hydra/harness.json`)
+    harness: Harness,
+    /// Lint the plans picked before opening their tab (not `--no-lint`)
+    lint: bool,
+    /// New-tab picker, while open (Ctrl+O)
+    pub picker: Option<Picker>,
+    /// Answer prompt for the active tab's question, while open
+    pub answer: Option<AnswerInput>,
+    /// Copy mode over the active tab, while on (F10)
+    pub copy: Option<CopyMode>,
+    /// One-line message shown until the next key, e.g. what a yank did
+    pub notice: Option<String>,
+    /// Progress sidebar, while shown (F12)
+    pub sidebar: Option<Sidebar>,
+    /// The prefix key was pressed; the next key completes a binding
+    pub prefix_pending: bool,
+    /// Keybinding help overlay, while shown
+    pub help: bool,
+    /// Tabs shown at once, while split
+    pub panes: Option<Panes>,
+    /// Tab selected in the overview, while it's shown
+    pub overview: Option<usize>,
+    /// Current content area dimensions (rows, cols)
+    content_size: (u16, u16),
+    /// Content size and pane sizes last sent to the daemon
+    sent_layout: ((u16, u16), Vec<PaneSize>),
+    /// Connection to the daemon
+    connection: Connection<Event>,
+    /// Switch to the next tab this client opens
+    focus_opened: bool,
+    /// Tab to switch to once the daemon lists it
+    focus: Option<u64>,
+    /// Whether any tab was shown, so closing the last one ends the TUI
+    had_tabs: bool,
+}
+
+impl App {
+    /// Attach to the daemon on `connection`, with specified content area
+    /// dimensions (rows, cols)
+    pub fn new(
+        config: Config,
+        prompt: ResolvedPrompt,
+        instructions: String,
+        harness: Harness,
+        lint: bool,
+        connection: Connection<Event>,
+        (rows, cols): (u16, u16),
+    ) -> Self {
+        let app = Self {
+            tabs: Vec::new(),
+            active_tab_index: 0,
+            config,
+            prompt,
+            instructions,
+            harness,
+            lint,
+            picker: None,
+            answer: None,
+            copy: None,
+            notice: None,
+            sidebar: None,
+            prefix_pending: false,
+            help: false,
+            panes: None,
+            overview: None,
+            content_size: (rows, cols),
+            sent_layout: ((rows, cols), Vec::new()),
+            connection,
+            focus_opened: false,
+            focus: None,
+            had_tabs: false,
+        };
+        app.send(protocol::encode_message(&Request::Attach { rows, cols }));
+        app
+    }
+
+    /// Send a frame to the daemon. A lost daemon shows up in `poll`.
+    fn send(&self, frame: Vec<u8>) {
+        let _ = self.connection.send(frame);
+    }
+
+    fn request(&self, request: &Request) {
+        self.send(protocol::encode_message(request));
+    }
+
+    /// Ask the daemon for a tab running `session`, switching to it once open
+    pub fn new_tab(&mut self, session: TabSession) {
+        self.focus_opened = true;
+        self.request(&Request::OpenTab {
+            config: Box::new(self.config.clone()),
+            session: Box::new(session),
+        });
+    }
+
+    /// Handle everything the daemon sent since the last call
+    pub fn poll(&mut self) -> Result<()> {
+        loop {
+            let frame = self
+                .connection
+                .try_recv()
+                .map_err(|e| HydraError::io("reading from hydra daemon", e))?;
+            match frame {
+                None => break,
+                Some(Frame::Data { key, data }) => {
+                    if let Some(tab) = self.tabs.iter_mut().find(|t| t.info.key == key) {
+                        tab.parser.process(&data);
+                        tab.activity.output();
                     }
                 }
-                Err(_) => {
-                    let _ = tx.send(PtyMessage::Closed);
-                    break;
-                }
+                Some(Frame::Message(event)) => self.handle_event(event),
             }
         }
+        self.update_activity();
+        Ok(())
     }
 
-    /// Poll for PTY output (non-blocking)
-    fn poll_output(&mut self) -> Result<()> {
-        if self.status != TabStatus::Running {
-            return Ok(());
-        }
+    /// Whether the tab at `index` is on screen: active, or in a pane
+    fn on_screen(&self, index: usize) -> bool {
+        index == self.active_tab_index
+            || self
+                .panes
+                .as_ref()
+                .is_some_and(|p| p.keys.contains(&self.tabs[index].info.key))
+    }
 
-        if self.pty_rx.is_none() {
-            return Ok(());
+    /// Why the tab at `index` needs attention; tabs on screen don't
+    pub fn attention(&self, index: usize) -> Option<Attention> {
+        if self.on_screen(index) {
+            return None;
         }
+        let tab = &self.tabs[index];
+        tab.activity
+            .attention(&tab.info, self.config.tui_idle_minutes)
+    }
 
-        // Collect messages first to avoid borrow issues
-        let mut messages = Vec::new();
-        let mut should_break = false;
+    /// Mark the tabs on screen as seen, and notify (`tui_notify`) about tabs
+    /// that came to need attention
+    fn update_activity(&mut self) {
+        for index in 0..self.tabs.len() {
+            let attention = self.attention(index);
+            let on_screen = self.on_screen(index);
+            let tab = &mut self.tabs[index];
+            if on_screen {
+                tab.activity.viewed(&tab.info);
+            }
+            if !tab.activity.rose(attention) {
+                continue;
+            }
+            let message = format!(
+                "hydra: tab {}{} - {}",
+                tab.id,
+                tab.info
+                    .plan_path
+                    .as_deref()
+                    .map(|p| format!(" {}", crate::plan::plan_name(p)))
+                    .unwrap_or_default(),
+                attention.map(|a| a.describe(&tab.info)).unwrap_or_default()
+            );
+            if let Some(sequence) = self.config.tui_notify.sequence(&message) {
+                let mut stdout = io::stdout();
+                let _ = stdout
+                    .write_all(sequence.as_bytes())
+                    .and_then(|_| stdout.flush());
+            }
+        }
+    }
 
-        if let Some(ref rx) = self.pty_rx {
-            loop {
-                match rx.try_recv() {
-                    Ok(msg) => messages.push(msg),
-                    Err(TryRecvError::Empty) => {
-                        should_break = true;
-                        break;
-                    }
-                    Err(TryRecvError::Disconnected) => {
-                        messages.push(PtyMessage::Closed);
-                        break;
-                    }
-                }
+    /// Switch to the tab needing attention most, the first one after the
+    /// active tab among equals
+    pub fn next_attention(&mut self) {
+        let count = self.tabs.len();
+        let mut best: Option<(Attention, usize)> = None;
+        for index in (1..count).map(|i| (self.active_tab_index + i) % count) {
+            if let Some(attention) = self.attention(index)
+                && best.is_none_or(|(b, _)| attention > b)
+            {
+                best = Some((attention, index));
             }
         }
+        match best {
+            Some((_, index)) => self.switch_to_tab(index + 1),
+            None => self.notice = Some("No tab needs attention".to_string()),
+        }
+    }
 
-        // Process messages
-        for msg in messages {
-            match msg {
-                PtyMessage::Data(data) => {
-                    // Feed data through vt100 parser to interpret escape sequences
-                    self.parser.process(&data);
-
-                    // Check for stop signals
-                    if let Some(result) = self.check_for_signals() {
-                        self.status = match result {
-                            PtyResult::TaskComplete => TabStatus::TaskComplete,
-                            PtyResult::AllComplete => TabStatus::AllComplete,
-                            _ => TabStatus::Stopped,
-                        };
-                        self.stop_pty();
-                    }
+    fn handle_event(&mut self, event: Event) {
+        match event {
+            Event::Resized { key, rows, cols } => {
+                // The tab's replay follows
+                if let Some(tab) = self.tabs.iter_mut().find(|t| t.info.key == key) {
+                    tab.parser = vt100::Parser::new(rows, cols, self.config.tui_scrollback);
+                    tab.activity.replaying = true;
                 }
-                PtyMessage::Closed => {
-                    if self.status == TabStatus::Running {
-                        self.status = TabStatus::Stopped;
-                    }
+            }
+            Event::Tabs(infos) => self.sync_tabs(infos),
+            Event::Opened { key } => {
+                if self.focus_opened {
+                    self.focus_opened = false;
+                    self.focus = Some(key);
+                    self.picker = None;
+                }
+            }
+            Event::Error(message) => {
+                let message = one_line(&message);
+                // A tab the picker asked for failed to open
+                match self.picker {
+                    Some(ref mut picker) if self.focus_opened => picker.error = Some(message),
+                    _ => self.notice = Some(message),
                 }
+                self.focus_opened = false;
             }
+            Event::Listing { .. } => {}
         }
-
-        let _ = should_break; // suppress unused warning
-        Ok(())
     }
 
-    /// Check screen contents for stop signals
-    fn check_for_signals(&self) -> Option<PtyResult> {
-        const TASK_COMPLETE: &str = "###TASK_COMPLETE###";
-        const ALL_COMPLETE: &str = "###ALL_TASKS_COMPLETE###";
-
-        // Get plain text contents from the vt100 screen
-        let contents = self.parser.screen().contents();
+    /// Follow the daemon's tab list, keeping the active tab active
+    fn sync_tabs(&mut self, infos: Vec<TabInfo>) {
+        let active = self.active_tab().map(|t| t.info.key);
+        let mut old = std::mem::take(&mut self.tabs);
+        for (i, info) in infos.into_iter().enumerate() {
+            let (parser, activity) = match old.iter().position(|t| t.info.key == info.key) {
+                Some(index) => {
+                    let view = old.swap_remove(index);
+                    (view.parser, view.activity)
+                }
+                None => {
+                    // What a tab needed before this client saw it isn't news
+                    let mut activity = Activity::default();
+                    activity.rose(activity.attention(&info, self.config.tui_idle_minutes));
+                    (
+                        vt100::Parser::new(info.rows, info.cols, self.config.tui_scrollback),
+                        activity,
+                    )
+                }
+            };
+            self.tabs.push(TabView {
+                id: i + 1,
+                parser,
+                info,
+                activity,
+            });
+        }
 
-        if contents.contains(ALL_COMPLETE) {
-            return Some(PtyResult::AllComplete);
+        // Closed tabs leave their panes, and the overview
+        if let Some(mut panes) = self.panes.take() {
+            panes
+                .keys
+                .retain(|key| self.tabs.iter().any(|t| t.info.key == *key));
+            self.panes = (panes.keys.len() > 1).then_some(panes);
         }
-        if contents.contains(TASK_COMPLETE) {
-            return Some(PtyResult::TaskComplete);
+        if let Some(ref mut selected) = self.overview {
+            *selected = (*selected).min(self.tabs.len().saturating_sub(1));
         }
-        None
-    }
 
-    /// Send input to the PTY
-    fn send_input(&mut self, data: &[u8]) -> Result<()> {
-        if let Some(ref mut writer) = self.pty_writer {
-            writer
-                .write_all(data)
-                .map_err(|e| HydraError::io("writing to PTY", e))?;
-            writer
-                .flush()
-                .map_err(|e| HydraError::io("flushing PTY", e))?;
+        self.had_tabs |= !self.tabs.is_empty();
+        let key = self.focus.or(active);
+        let shown = self.panes.as_ref().map(|p| p.keys[0]);
+        if let Some(index) = self.tabs.iter().position(|t| Some(t.info.key) == key) {
+            self.activate(index, active);
+            if self.focus == key {
+                self.focus = None;
+            }
+        } else if let Some(index) = self.tabs.iter().position(|t| Some(t.info.key) == shown) {
+            // The focused pane's tab closed; another pane takes focus
+            self.active_tab_index = index;
+        } else if self.active_tab_index >= self.tabs.len() {
+            self.active_tab_index = self.tabs.len().saturating_sub(1);
         }
-        Ok(())
+        self.send_layout();
     }
 
-    /// Stop this tab's Claude process
-    fn stop_pty(&mut self) {
-        self.stop_flag.store(true, Ordering::SeqCst);
-        if let Some(ref pty) = self.pty_manager {
-            pty.terminate_child();
+    /// Make the tab at `index` active. While split, a tab not shown yet
+    /// takes the pane of `from`, the tab that had focus.
+    fn activate(&mut self, index: usize, from: Option<u64>) {
+        let Some(key) = self.tabs.get(index).map(|t| t.info.key) else {
+            return;
+        };
+        self.active_tab_index = index;
+        let Some(ref mut panes) = self.panes else {
+            return;
+        };
+        if !panes.keys.contains(&key) {
+            match panes.keys.iter().position(|k| Some(*k) == from) {
+                Some(pane) => panes.keys[pane] = key,
+                None if panes.keys.len() < MAX_PANES => panes.keys.push(key),
+                None => panes.keys[MAX_PANES - 1] = key,
+            }
+            self.send_layout();
         }
-        self.pty_writer = None;
-        self.pty_rx = None;
     }
 
-    /// Kill this tab's Claude process (Ctrl+C behavior)
-    pub fn kill(&mut self) {
-        self.stop_pty();
-        self.status = TabStatus::Stopped;
+    /// Show another tab next to the active one, in a new pane that takes
+    /// focus: the next tab not shown yet
+    pub fn split(&mut self, arrangement: Arrangement) {
+        let Some(active) = self.active_tab().map(|t| t.info.key) else {
+            return;
+        };
+        let mut panes = self.panes.take().unwrap_or(Panes {
+            arrangement,
+            keys: vec![active],
+        });
+        panes.arrangement = arrangement;
+        let count = self.tabs.len();
+        let next = (1..count)
+            .map(|i| (self.active_tab_index + i) % count)
+            .find(|&i| !panes.keys.contains(&self.tabs[i].info.key));
+        if panes.keys.len() >= MAX_PANES {
+            self.notice = Some(format!("At most {} tabs fit on screen", MAX_PANES));
+        } else if let Some(index) = next {
+            let pane = panes.keys.iter().position(|k| *k == active).unwrap_or(0);
+            panes.keys.insert(pane + 1, self.tabs[index].info.key);
+            self.active_tab_index = index;
+        } else {
+            self.notice = Some("No other tab to show".to_string());
+        }
+        self.panes = (panes.keys.len() > 1).then_some(panes);
+        self.send_layout();
     }
 
-    /// Resize the vt100 parser and PTY to match new terminal dimensions
-    pub fn resize(&mut self, rows: u16, cols: u16) {
-        self.parser.screen_mut().set_size(rows, cols);
-        // Also resize the PTY so Claude knows the new terminal size
-        if let Some(ref pty) = self.pty_manager {
-            let _ = pty.resize(rows, cols);
+    /// Switch the panes to the next arrangement
+    pub fn next_layout(&mut self) {
+        if let Some(ref mut panes) = self.panes {
+            panes.arrangement = panes.arrangement.next();
+            self.send_layout();
         }
     }
-}
 
-impl Drop for Tab {
-    fn drop(&mut self) {
-        self.stop_pty();
+    /// Move focus to the next (or previous) pane
+    pub fn focus_pane(&mut self, forward: bool) {
+        let Some(ref panes) = self.panes else {
+            return;
+        };
+        let count = panes.keys.len();
+        let active = self.active_tab().map(|t| t.info.key);
+        let pane = panes
+            .keys
+            .iter()
+            .position(|k| Some(*k) == active)
+            .unwrap_or(0);
+        let next = if forward {
+            (pane + 1) % count
+        } else {
+            (pane + count - 1) % count
+        };
+        let key = panes.keys[next];
+        if let Some(index) = self.tabs.iter().position(|t| t.info.key == key) {
+            self.active_tab_index = index;
+        }
     }
-}
 
-/// Main application state
-pub struct App {
-    /// All tabs
-    pub tabs: Vec<Tab>,
-    /// Currently active tab index
-    pub active_tab_index: usize,
-    /// Shared configuration
-    config: Config,
-    /// Shared prompt (used when creating new tabs)
-    prompt: ResolvedPrompt,
-    /// Current content area dimensions (rows, cols)
-    content_size: (u16, u16),
-}
+    /// Go back to showing only the active tab
+    pub fn unsplit(&mut self) {
+        self.panes = None;
+        self.send_layout();
+    }
 
-impl App {
-    /// Create a new App and spawn the initial tab with specified content area dimensions
-    pub fn new(config: Config, prompt: ResolvedPrompt, rows: u16, cols: u16) -> Result<Self> {
-        let mut app = Self {
-            tabs: Vec::with_capacity(MAX_TABS),
-            active_tab_index: 0,
-            config,
-            prompt,
-            content_size: (rows, cols),
+    /// Content size of each pane, inside its borders; empty unless split
+    pub fn pane_sizes(&self) -> Vec<PaneSize> {
+        let Some(ref panes) = self.panes else {
+            return Vec::new();
         };
+        // Each pane has its own borders where the content area has one
+        let (rows, cols) = self.content_size;
+        let area = Rect::new(0, 0, cols.saturating_add(2), rows.saturating_add(2));
+        panes
+            .keys
+            .iter()
+            .zip(panes.areas(area))
+            .map(|(key, area)| PaneSize {
+                key: *key,
+                rows: area.height.saturating_sub(2).max(1),
+                cols: area.width.saturating_sub(2).max(1),
+            })
+            .collect()
+    }
 
-        // Spawn initial tab
-        app.new_tab()?;
+    /// Size the active tab is shown at: its pane's, or the content area's
+    fn view_size(&self) -> (u16, u16) {
+        let active = self.active_tab().map(|t| t.info.key);
+        self.pane_sizes()
+            .into_iter()
+            .find(|p| Some(p.key) == active)
+            .map_or(self.content_size, |p| (p.rows, p.cols))
+    }
 
-        Ok(app)
+    /// Tell the daemon the content and pane sizes when they changed; it
+    /// resizes the tabs and sends their screens again
+    fn send_layout(&mut self) {
+        let layout = (self.content_size, self.pane_sizes());
+        if layout == self.sent_layout {
+            return;
+        }
+        let (rows, cols) = self.content_size;
+        let panes = layout.1.clone();
+        self.sent_layout = layout;
+        let height = usize::from(self.view_size().0);
+        if let Some(ref mut copy) = self.copy {
+            copy.set_height(height);
+        }
+        self.request(&Request::Resize { rows, cols, panes });
     }
 
-    /// Create a new tab (if under limit)
-    pub fn new_tab(&mut self) -> Result<bool> {
-        if self.tabs.len() >= MAX_TABS {
-            return Ok(false);
+    /// Show the overview with the active tab selected
+    pub fn open_overview(&mut self) {
+        if !self.tabs.is_empty() {
+            self.overview = Some(self.active_tab_index);
         }
+    }
 
-        let id = (self.tabs.len() + 1) as u8;
-        let (rows, cols) = self.content_size;
-        let tab = Tab::new(id, &self.prompt, rows, cols)?;
-        self.tabs.push(tab);
-        self.active_tab_index = self.tabs.len() - 1;
+    /// Move the overview's selection `dx` thumbnails across and `dy` down
+    pub fn move_overview(&mut self, dx: isize, dy: isize) {
+        let Some(selected) = self.overview else {
+            return;
+        };
+        let columns = pane::overview_columns(self.tabs.len()) as isize;
+        let target = selected as isize + dx + dy * columns;
+        if (0..self.tabs.len() as isize).contains(&target) {
+            self.overview = Some(target as usize);
+        }
+    }
 
-        Ok(true)
+    /// Switch to the tab selected in the overview and close it
+    pub fn confirm_overview(&mut self) {
+        if let Some(selected) = self.overview.take() {
+            self.switch_to_tab(selected + 1);
+        }
     }
 
-    /// Close the currently active tab
-    pub fn close_active_tab(&mut self) {
-        if self.tabs.is_empty() {
+    /// Open the new-tab picker
+    pub fn open_picker(&mut self) {
+        self.picker = Some(Picker::load(
+            Path::new(PLANS_DIR),
+            &self.prompt,
+            self.harness,
+        ));
+    }
+
+    /// Open a tab for the picker's choice. Problems with the choice (lint
+    /// errors, a plan or template error, or the plan running elsewhere) are
+    /// shown in the picker instead of ending the TUI; it closes once the tab
+    /// is open.
+    pub fn confirm_picker(&mut self) {
+        let Some(ref picker) = self.picker else {
             return;
+        };
+        let linted = match picker.plan() {
+            Some(plan) if self.lint => {
+                lint::lint_plan(plan).and_then(|issues| lint::check(plan, &issues))
+            }
+            _ => Ok(()),
+        };
+        let prepared = linted.and_then(|()| {
+            TabSession::prepare(
+                &self.config,
+                picker.harness,
+                picker.prompt().clone(),
+                &self.instructions,
+                picker.plan(),
+            )
+        });
+        match prepared {
+            Ok(session) => self.new_tab(session),
+            Err(e) => {
+                if let Some(ref mut picker) = self.picker {
+                    picker.error = Some(one_line(&e.to_string()));
+                }
+            }
         }
+    }
 
-        // Remove the tab (Drop will clean up PTY)
-        self.tabs.remove(self.active_tab_index);
+    /// Open the answer prompt for the active tab's question
+    pub fn open_answer(&mut self) {
+        let asking = self
+            .active_tab()
+            .and_then(|t| Some((t.info.key, t.info.question.clone()?)));
+        match asking {
+            Some((key, question)) => {
+                self.answer = Some(AnswerInput {
+                    key,
+                    question,
+                    text: String::new(),
+                })
+            }
+            None => self.notice = Some("No question in this tab".to_string()),
+        }
+    }
 
-        // Renumber remaining tabs
-        for (i, tab) in self.tabs.iter_mut().enumerate() {
-            tab.id = (i + 1) as u8;
+    /// Send the typed answer, resuming the tab's loop; an empty answer
+    /// leaves the decision to the agent
+    pub fn confirm_answer(&mut self) {
+        if let Some(input) = self.answer.take() {
+            let text = input.text.trim();
+            self.request(&Request::Answer {
+                key: input.key,
+                answer: (!text.is_empty()).then(|| text.to_string()),
+            });
         }
+    }
 
-        // Adjust active index
-        if !self.tabs.is_empty() && self.active_tab_index >= self.tabs.len() {
-            self.active_tab_index = self.tabs.len() - 1;
+    /// Close the currently active tab, stopping its agent
+    pub fn close_active_tab(&mut self) {
+        if let Some(tab) = self.active_tab() {
+            self.request(&Request::CloseTab { key: tab.info.key });
         }
     }
 
-    /// Switch to tab by number (1-9)
-    pub fn switch_to_tab(&mut self, num: u8) {
-        let index = (num - 1) as usize;
-        if index < self.tabs.len() {
-            self.active_tab_index = index;
+    /// Switch to tab by number (from 1)
+    pub fn switch_to_tab(&mut self, num: usize) {
+        if (1..=self.tabs.len()).contains(&num) {
+            let from = self.active_tab().map(|t| t.info.key);
+            self.activate(num - 1, from);
         }
     }
 
     /// Cycle to next tab
     pub fn next_tab(&mut self) {
         if !self.tabs.is_empty() {
-            self.active_tab_index = (self.active_tab_index + 1) % self.tabs.len();
+            let from = self.active_tab().map(|t| t.info.key);
+            self.activate((self.active_tab_index + 1) % self.tabs.len(), from);
         }
     }
 
     /// Cycle to previous tab
     pub fn prev_tab(&mut self) {
         if !self.tabs.is_empty() {
-            self.active_tab_index = if self.active_tab_index == 0 {
+            let from = self.active_tab().map(|t| t.info.key);
+            let index = if self.active_tab_index == 0 {
                 self.tabs.len() - 1
             } else {
                 self.active_tab_index - 1
             };
+            self.activate(index, from);
         }
     }
 
     /// Get the active tab (if any)
-    pub fn active_tab(&self) -> Option<&Tab> {
+    pub fn active_tab(&self) -> Option<&TabView> {
         self.tabs.get(self.active_tab_index)
     }
 
     /// Get the active tab mutably (if any)
-    pub fn active_tab_mut(&mut self) -> Option<&mut Tab> {
+    pub fn active_tab_mut(&mut self) -> Option<&mut TabView> {
         self.tabs.get_mut(self.active_tab_index)
     }
 
-    /// Check if there are no tabs
-    pub fn is_empty(&self) -> bool {
-        self.tabs.is_empty()
+    /// Check if every tab this client saw has been closed
+    pub fn all_closed(&self) -> bool {
+        self.had_tabs && self.tabs.is_empty()
     }
 
-    /// Poll all PTYs for output
-    pub fn poll_pty_output(&mut self) -> Result<()> {
-        for tab in &mut self.tabs {
-            tab.poll_output()?;
-        }
-        Ok(())
+    /// Show or hide the progress sidebar
+    pub fn toggle_sidebar(&mut self) {
+        self.sidebar = match self.sidebar {
+            Some(_) => None,
+            None => Some(Sidebar::default()),
+        };
     }
 
-    /// Send input to the active tab's PTY
-    pub fn send_input(&mut self, data: &[u8]) -> Result<()> {
+    /// Point the sidebar (if shown) at the active tab's plan and scratchpad
+    /// and pick up changes to them
+    pub fn refresh_sidebar(&mut self) {
+        let Some(ref mut sidebar) = self.sidebar else {
+            return;
+        };
+        let tab = self.tabs.get(self.active_tab_index);
+        sidebar.refresh(
+            tab.and_then(|t| t.info.plan_path.as_deref()),
+            tab.and_then(|t| t.info.scratchpad_path.as_deref()),
+        );
+    }
+
+    /// Send input to the active tab's PTY; typing returns the view to live
+    /// output
+    pub fn send_input(&mut self, data: &[u8]) {
         if let Some(tab) = self.active_tab_mut() {
-            tab.send_input(data)?;
+            tab.scroll_down(usize::MAX);
+            let key = tab.info.key;
+            self.send(protocol::encode_data(key, data));
         }
-        Ok(())
+    }
+
+    /// Start copy mode on the active tab's text, from its current view. The
+    /// cursor starts on the terminal cursor when following output.
+    pub fn enter_copy_mode(&mut self) {
+        let height = usize::from(self.view_size().0);
+        let Some(tab) = self.active_tab_mut() else {
+            return;
+        };
+        let following = tab.scroll_offset() == 0;
+        let (row, col) = tab.parser.screen().cursor_position();
+        let (lines, top) = text_lines(&mut tab.parser);
+        let cursor = if following {
+            (top + usize::from(row), usize::from(col))
+        } else {
+            (top + height - 1, 0)
+        };
+        self.copy = Some(CopyMode::new(lines, height, top, cursor));
+    }
+
+    /// Yank copy mode's selection, to the clipboard with OSC 52 or to
+    /// `.hydra/yank.txt`, and leave copy mode. The outcome is shown as a
+    /// notice.
+    pub fn yank(&mut self, to_file: bool) {
+        let Some(copy) = self.copy.take() else {
+            return;
+        };
+        let text = copy.selected_text();
+        let lines = text.lines().count().max(1);
+        let written = if to_file {
+            let path = Config::yank_path();
+            fs::create_dir_all(Config::local_hydra_dir())
+                .and_then(|_| fs::write(&path, format!("{}\n", text)))
+                .map(|_| format!("Saved {} line(s) to {}", lines, path.display()))
+        } else {
+            let mut stdout = io::stdout();
+            stdout
+                .write_all(copy::osc52(&text).as_bytes())
+                .and_then(|_| stdout.flush())
+                .map(|_| format!("Copied {} line(s) to the clipboard (OSC 52)", lines))
+        };
+        self.notice = Some(written.unwrap_or_else(|e| format!("Yank failed: {}", e)));
+    }
+
+    /// Scroll the active tab back by `lines` (or move copy mode's cursor)
+    pub fn scroll_up(&mut self, lines: usize) {
+        if let Some(ref mut copy) = self.copy {
+            copy.up(lines);
+        } else if let Some(tab) = self.active_tab_mut() {
+            tab.scroll_up(lines);
+        }
+    }
+
+    /// Scroll the active tab towards live output by `lines` (or move copy
+    /// mode's cursor)
+    pub fn scroll_down(&mut self, lines: usize) {
+        if let Some(ref mut copy) = self.copy {
+            copy.down(lines);
+        } else if let Some(tab) = self.active_tab_mut() {
+            tab.scroll_down(lines);
+        }
+    }
+
+    /// Lines a page scroll moves: the active tab's height, less one line
+    /// of overlap
+    pub fn page_lines(&self) -> usize {
+        usize::from(self.view_size().0.saturating_sub(1).max(1))
     }
 
     /// Kill the active tab's Claude process (Ctrl+C behavior)
     pub fn kill_active_tab(&mut self) {
-        if let Some(tab) = self.active_tab_mut() {
-            tab.kill();
+        if let Some(tab) = self.active_tab() {
+            self.request(&Request::KillTab { key: tab.info.key });
         }
     }
 
     /// Get config reference
-    #[allow(dead_code)]
     pub fn config(&self) -> &Config {
         &self.config
     }
 
-    /// Resize all tabs' vt100 parsers and PTYs to match new terminal dimensions
+    /// Have the daemon resize all tabs' PTYs to match new terminal
+    /// dimensions; the parsers follow when it sends the resized screens
     pub fn resize_all(&mut self, rows: u16, cols: u16) {
-        // Store size for new tabs
         self.content_size = (rows, cols);
-        // Resize existing tabs
-        for tab in &mut self.tabs {
-            tab.resize(rows, cols);
+        self.send_layout();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_lines_include_scrollback() {
+        let mut parser = vt100::Parser::new(2, 10, 100);
+        parser.process(b"a\r\nb\r\nc\r\nd");
+        let (lines, top) = text_lines(&mut parser);
+        assert_eq!(lines, ["a", "b", "c", "d"]);
+        assert_eq!(top, 2);
+
+        parser.screen_mut().set_scrollback(1);
+        assert_eq!(text_lines(&mut parser).1, 1);
+        assert_eq!(parser.screen().scrollback(), 1);
+    }
+
+    fn info(key: u64) -> TabInfo {
+        TabInfo {
+            key,
+            status: crate::tui::tab::TabStatus::Running,
+            harness: Harness::Claude,
+            plan_path: None,
+            scratchpad_path: None,
+            iteration: 1,
+            max_iterations: 20,
+            elapsed_seconds: 0,
+            timeout_seconds: 600,
+            rows: 3,
+            cols: 10,
+            idle_seconds: 0,
+            waiting: false,
+            failed_exits: 0,
+            exit_error: None,
+            question: None,
+        }
+    }
+
+    fn app() -> (App, Connection<Request>) {
+        let (client, daemon) = std::os::unix::net::UnixStream::pair().unwrap();
+        let prompt = ResolvedPrompt {
+            path: "prompt.md".into(),
+            content: String::new(),
+            source: crate::prompt::PromptSource::CliOverride,
+        };
+        let app = App::new(
+            Config::default(),
+            prompt,
+            String::new(),
+            Harness::Claude,
+            true,
+            Connection::new(client).unwrap(),
+            (3, 10),
+        );
+        (app, Connection::new(daemon).unwrap())
+    }
+
+    #[test]
+    fn test_tabs_follow_daemon() {
+        let (mut app, _daemon) = app();
+        assert!(!app.all_closed());
+
+        app.handle_event(Event::Tabs(vec![info(1), info(2)]));
+        app.tabs[0].parser.process(b"kept");
+        app.switch_to_tab(2);
+
+        // A tab this client opened takes focus once listed
+        app.focus_opened = true;
+        app.handle_event(Event::Opened { key: 5 });
+        app.handle_event(Event::Tabs(vec![info(1), info(2), info(5)]));
+        assert_eq!(app.active_tab().unwrap().info.key, 5);
+
+        // Closing tabs renumbers the rest and keeps parsers by key
+        app.handle_event(Event::Tabs(vec![info(1), info(5)]));
+        assert_eq!(app.active_tab().unwrap().id, 2);
+        assert_eq!(app.tabs[0].parser.screen().contents(), "kept");
+
+        app.handle_event(Event::Resized {
+            key: 1,
+            rows: 4,
+            cols: 12,
+        });
+        assert_eq!(app.tabs[0].parser.screen().size(), (4, 12));
+        assert_eq!(app.tabs[0].parser.screen().contents(), "");
+        assert_eq!(app.tabs[1].parser.screen().size(), (3, 10));
+
+        app.handle_event(Event::Tabs(Vec::new()));
+        assert!(app.all_closed());
+    }
+
+    /// The panes and content size the app last asked the daemon for
+    fn last_resize(daemon: &Connection<Request>) -> Option<(u16, u16, Vec<PaneSize>)> {
+        let mut last = None;
+        while let Ok(Some(frame)) = daemon.try_recv() {
+            if let Frame::Message(Request::Resize { rows, cols, panes }) = frame {
+                last = Some((rows, cols, panes));
+            }
+        }
+        last
+    }
+
+    #[test]
+    fn test_split_panes_follow_focus() {
+        let (mut app, daemon) = app();
+        app.handle_event(Event::Tabs(vec![info(1), info(2), info(3)]));
+        app.resize_all(20, 98);
+
+        // Splitting shows the next tab beside the active one, focused
+        app.split(Arrangement::Columns);
+        assert_eq!(app.panes.as_ref().unwrap().keys, [1, 2]);
+        assert_eq!(app.active_tab().unwrap().info.key, 2);
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        let pane = |key| PaneSize {
+            key,
+            rows: 20,
+            cols: 48,
+        };
+        assert_eq!(last_resize(&daemon), Some((20, 98, vec![pane(1), pane(2)])));
+        assert_eq!(app.page_lines(), 19);
+
+        // A tab switched to takes the focused pane; one shown takes focus
+        app.switch_to_tab(3);
+        assert_eq!(app.panes.as_ref().unwrap().keys, [1, 3]);
+        app.focus_pane(true);
+        assert_eq!(app.active_tab().unwrap().info.key, 1);
+        app.split(Arrangement::Rows);
+        assert_eq!(app.panes.as_ref().unwrap().keys, [1, 2, 3]);
+        app.split(Arrangement::Rows);
+        assert!(app.notice.is_some());
+
+        // Closed tabs leave their panes; one pane left is no split
+        app.handle_event(Event::Tabs(vec![info(1), info(3)]));
+        assert_eq!(app.panes.as_ref().unwrap().keys, [1, 3]);
+        assert_eq!(app.active_tab().unwrap().info.key, 1);
+        app.handle_event(Event::Tabs(vec![info(3)]));
+        assert!(app.panes.is_none());
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert_eq!(last_resize(&daemon), Some((20, 98, Vec::new())));
+    }
+
+    #[test]
+    fn test_picker_waits_for_its_tab() {
+        let (mut app, _daemon) = app();
+        app.open_picker();
+        app.focus_opened = true;
+        app.handle_event(Event::Error(
+            "hydra is already running a\n\nStop it".to_string(),
+        ));
+        let picker = app.picker.as_ref().unwrap();
+        assert_eq!(
+            picker.error.as_deref(),
+            Some("hydra is already running a Stop it")
+        );
+
+        app.focus_opened = true;
+        app.handle_event(Event::Opened { key: 1 });
+        assert!(app.picker.is_none());
+    }
+
+    #[test]
+    fn test_picker_lints_its_plan() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let plan = dir.path().join("plan.md");
+        std::fs::write(&plan, "# Plan\n\nJust prose.\n").unwrap();
+        let (mut app, _daemon) = app();
+        app.open_picker();
+        let picker = app.picker.as_mut().unwrap();
+        picker.plans = vec![crate::tui::picker::PlanChoice {
+            path: Some(plan.clone()),
+            label: "plan.md".to_string(),
+        }];
+        picker.selected_plan = 0;
+
+        app.confirm_picker();
+        let error = app.picker.as_ref().unwrap().error.clone().unwrap();
+        assert!(error.starts_with(&format!("Plan {} has 1 lint error(s) Fix", plan.display())));
+    }
+
+    #[test]
+    fn test_answer_goes_to_asking_tab() {
+        let (mut app, daemon) = app();
+        let mut asking = info(2);
+        asking.status = crate::tui::tab::TabStatus::Question;
+        asking.question = Some("Which database?".to_string());
+        app.handle_event(Event::Tabs(vec![info(1), asking]));
+
+        app.open_answer();
+        assert!(app.answer.is_none());
+        assert_eq!(app.notice.as_deref(), Some("No question in this tab"));
+
+        app.switch_to_tab(2);
+        app.open_answer();
+        app.answer.as_mut().unwrap().text = " SQLite ".to_string();
+        app.confirm_answer();
+        assert!(app.answer.is_none());
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        let mut sent = None;
+        while let Ok(Some(frame)) = daemon.try_recv() {
+            if let Frame::Message(Request::Answer { key, answer }) = frame {
+                sent = Some((key, answer));
+            }
         }
+        assert_eq!(sent, Some((2, Some("SQLite".to_string()))));
+    }
+
+    #[test]
+    fn test_overview_selects_tab() {
+        let (mut app, _daemon) = app();
+        app.handle_event(Event::Tabs((1..=5).map(info).collect()));
+        app.open_overview();
+        assert_eq!(app.overview, Some(0));
+        // Three columns for five tabs: down goes to the fourth
+        app.move_overview(0, 1);
+        app.move_overview(1, 0);
+        app.move_overview(0, 1);
+        assert_eq!(app.overview, Some(4));
+        app.confirm_overview();
+        assert!(app.overview.is_none());
+        assert_eq!(app.active_tab().unwrap().id, 5);
+    }
+
+    #[test]
+    fn test_attention_and_next_attention() {
+        let (mut app, _daemon) = app();
+        app.handle_event(Event::Tabs((1..=4).map(info).collect()));
+        // The screens replayed on attach are not new output
+        for key in 1..=4 {
+            app.handle_event(Event::Resized {
+                key,
+                rows: 3,
+                cols: 10,
+            });
+            app.tabs[key as usize - 1].activity.output();
+        }
+        app.update_activity();
+        assert!((0..4).all(|i| app.attention(i).is_none()));
+
+        app.tabs[1].activity.output();
+        let mut waiting = info(4);
+        waiting.waiting = true;
+        app.handle_event(Event::Tabs(vec![info(1), info(2), info(3), waiting]));
+        assert_eq!(app.attention(0), None);
+        assert_eq!(app.attention(1), Some(Attention::Output));
+        assert_eq!(app.attention(3), Some(Attention::Waiting));
+
+        // The most pressing first, then the rest
+        app.next_attention();
+        assert_eq!(app.active_tab().unwrap().id, 4);
+        app.update_activity();
+        app.next_attention();
+        assert_eq!(app.active_tab().unwrap().id, 2);
+        app.update_activity();
+        assert_eq!(app.attention(1), None);
+        app.next_attention();
+        assert_eq!(app.active_tab().unwrap().id, 4);
+        app.switch_to_tab(1);
+        app.handle_event(Event::Tabs((1..=4).map(info).collect()));
+        app.next_attention();
+        assert_eq!(app.notice.as_deref(), Some("No tab needs attention"));
     }
 }
@@ -0,0 +1,587 @@
+//! Copy mode (F10)
+//!
+//! A vi-style cursor over a snapshot of the active tab's text, its
+//! scrollback followed by the screen, taken when copy mode starts; the
+//! agent's output keeps arriving underneath. `/` and `?` search as you type
+//! and highlight every match, `v` and `V` select, and the selection is
+//! yanked to the system clipboard with OSC 52 (`y`) or saved to
+//! `.hydra/yank.txt` (`s`).
+
+use std::ops::Range;
+
+/// (line, column) in characters
+type Pos = (usize, usize);
+
+/// What `v` and `V` select
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SelectionKind {
+    /// From the anchor to the cursor, character by character
+    Char,
+    /// Whole lines from the anchor's to the cursor's
+    Line,
+}
+
+/// A search whose query is being typed
+#[derive(Debug)]
+pub struct Search {
+    pub query: String,
+    pub forward: bool,
+    /// Where the cursor was when the search started
+    origin: Pos,
+}
+
+/// Copy mode state; see the module docs
+#[derive(Debug)]
+pub struct CopyMode {
+    /// The snapshot, one `Vec<char>` per line
+    lines: Vec<Vec<char>>,
+    pub cursor: Pos,
+    /// First line in view
+    pub top: usize,
+    /// Lines in view
+    height: usize,
+    /// Selection kind and anchor, while selecting
+    pub selection: Option<(SelectionKind, Pos)>,
+    /// The search prompt, while open
+    pub search: Option<Search>,
+    /// Last confirmed query and direction, for `n` and `N`
+    last_search: Option<(String, bool)>,
+}
+
+impl CopyMode {
+    /// Start on `lines` with `height` lines in view from `top`, the cursor
+    /// at `cursor`
+    pub fn new(lines: Vec<String>, height: usize, top: usize, cursor: Pos) -> Self {
+        let mut lines: Vec<Vec<char>> = lines.iter().map(|l| l.chars().collect()).collect();
+        if lines.is_empty() {
+            lines.push(Vec::new());
+        }
+        let mut copy = Self {
+            lines,
+            cursor,
+            top,
+            height: height.max(1),
+            selection: None,
+            search: None,
+            last_search: None,
+        };
+        copy.cursor.0 = copy.cursor.0.min(copy.lines.len() - 1);
+        copy.clamp_column();
+        copy.scroll_to_cursor();
+        copy
+    }
+
+    /// The snapshot's line count
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn line(&self, line: usize) -> &[char] {
+        self.lines.get(line).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Follow a resize of the content area
+    pub fn set_height(&mut self, height: usize) {
+        self.height = height.max(1);
+        self.scroll_to_cursor();
+    }
+
+    /// Lines a half-page motion moves
+    fn half_page(&self) -> usize {
+        (self.height / 2).max(1)
+    }
+
+    fn clamp_column(&mut self) {
+        let len = self.lines[self.cursor.0].len();
+        self.cursor.1 = self.cursor.1.min(len.saturating_sub(1));
+    }
+
+    /// Scroll just enough to bring the cursor into view
+    fn scroll_to_cursor(&mut self) {
+        if self.cursor.0 < self.top {
+            self.top = self.cursor.0;
+        } else if self.cursor.0 >= self.top + self.height {
+            self.top = self.cursor.0 + 1 - self.height;
+        }
+    }
+
+    fn move_to(&mut self, pos: Pos) {
+        self.cursor = (pos.0.min(self.lines.len() - 1), pos.1);
+        self.clamp_column();
+        self.scroll_to_cursor();
+    }
+
+    pub fn left(&mut self) {
+        self.move_to((self.cursor.0, self.cursor.1.saturating_sub(1)));
+    }
+
+    pub fn right(&mut self) {
+        self.move_to((self.cursor.0, self.cursor.1 + 1));
+    }
+
+    pub fn up(&mut self, lines: usize) {
+        self.move_to((self.cursor.0.saturating_sub(lines), self.cursor.1));
+    }
+
+    pub fn down(&mut self, lines: usize) {
+        self.move_to((self.cursor.0.saturating_add(lines), self.cursor.1));
+    }
+
+    pub fn half_page_up(&mut self) {
+        self.up(self.half_page());
+    }
+
+    pub fn half_page_down(&mut self) {
+        self.down(self.half_page());
+    }
+
+    pub fn page_up(&mut self) {
+        self.up(self.height);
+    }
+
+    pub fn page_down(&mut self) {
+        self.down(self.height);
+    }
+
+    /// `0`
+    pub fn line_start(&mut self) {
+        self.cursor.1 = 0;
+    }
+
+    /// `^`
+    pub fn first_non_blank(&mut self) {
+        let line = &self.lines[self.cursor.0];
+        self.cursor.1 = line.iter().position(|c| !c.is_whitespace()).unwrap_or(0);
+    }
+
+    /// `$`
+    pub fn line_end(&mut self) {
+        self.move_to((self.cursor.0, usize::MAX));
+    }
+
+    /// `g`
+    pub fn first_line(&mut self) {
+        self.move_to((0, 0));
+    }
+
+    /// `G`
+    pub fn last_line(&mut self) {
+        self.move_to((usize::MAX, 0));
+    }
+
+    fn class_at(&self, (line, col): Pos) -> CharClass {
+        self.lines[line]
+            .get(col)
+            .map_or(CharClass::Blank, |c| CharClass::of(*c))
+    }
+
+    fn next_pos(&self, (line, col): Pos) -> Option<Pos> {
+        if col + 1 < self.lines[line].len() {
+            Some((line, col + 1))
+        } else if line + 1 < self.lines.len() {
+            Some((line + 1, 0))
+        } else {
+            None
+        }
+    }
+
+    fn prev_pos(&self, (line, col): Pos) -> Option<Pos> {
+        if col > 0 {
+            Some((line, col - 1))
+        } else if line > 0 {
+            Some((line - 1, self.lines[line - 1].len().saturating_sub(1)))
+        } else {
+            None
+        }
+    }
+
+    /// `w`: start of the next word, across lines
+    pub fn word_forward(&mut self) {
+        let mut pos = self.cursor;
+        let class = self.class_at(pos);
+        while let Some(next) = self.next_pos(pos) {
+            let new_line = next.0 != pos.0;
+            pos = next;
+            if new_line || self.class_at(pos) != class {
+                break;
+            }
+        }
+        while self.class_at(pos) == CharClass::Blank {
+            match self.next_pos(pos) {
+                Some(next) => pos = next,
+                None => break,
+            }
+        }
+        self.move_to(pos);
+    }
+
+    /// `b`: start of this or the previous word, across lines
+    pub fn word_back(&mut self) {
+        let mut pos = self.cursor;
+        while let Some(prev) = self.prev_pos(pos) {
+            pos = prev;
+            if self.class_at(pos) != CharClass::Blank {
+                break;
+            }
+        }
+        let class = self.class_at(pos);
+        while let Some(prev) = self.prev_pos(pos) {
+            if prev.0 != pos.0 || self.class_at(prev) != class {
+                break;
+            }
+            pos = prev;
+        }
+        self.move_to(pos);
+    }
+
+    /// `v` or `V`: start a selection at the cursor, or end it (pressing the
+    /// other key switches kinds)
+    pub fn toggle_selection(&mut self, kind: SelectionKind) {
+        self.selection = match self.selection {
+            Some((current, _)) if current == kind => None,
+            Some((_, anchor)) => Some((kind, anchor)),
+            None => Some((kind, self.cursor)),
+        };
+    }
+
+    /// Selection bounds, in order
+    fn selection_range(&self) -> Option<(SelectionKind, Pos, Pos)> {
+        self.selection.map(|(kind, anchor)| {
+            let (start, end) = if anchor <= self.cursor {
+                (anchor, self.cursor)
+            } else {
+                (self.cursor, anchor)
+            };
+            (kind, start, end)
+        })
+    }
+
+    pub fn is_selected(&self, line: usize, col: usize) -> bool {
+        match self.selection_range() {
+            Some((SelectionKind::Line, start, end)) => (start.0..=end.0).contains(&line),
+            Some((SelectionKind::Char, start, end)) => (start..=end).contains(&(line, col)),
+            None => false,
+        }
+    }
+
+    /// Text to yank: the selection, or the cursor's line when nothing is
+    /// selected
+    pub fn selected_text(&self) -> String {
+        let (kind, start, end) =
+            self.selection_range()
+                .unwrap_or((SelectionKind::Line, self.cursor, self.cursor));
+        let text: Vec<String> = (start.0..=end.0)
+            .map(|line| {
+                let chars = &self.lines[line];
+                let from = match kind {
+                    SelectionKind::Char if line == start.0 => start.1.min(chars.len()),
+                    _ => 0,
+                };
+                let to = match kind {
+                    SelectionKind::Char if line == end.0 => (end.1 + 1).min(chars.len()),
+                    _ => chars.len(),
+                };
+                let piece: String = chars[from..to.max(from)].iter().collect();
+                piece.trim_end().to_string()
+            })
+            .collect();
+        text.join("\n")
+    }
+
+    /// `/` (forward) or `?` (backward): open the search prompt
+    pub fn start_search(&mut self, forward: bool) {
+        self.search = Some(Search {
+            query: String::new(),
+            forward,
+            origin: self.cursor,
+        });
+    }
+
+    /// Type into the search prompt, moving to the first match as you go
+    pub fn search_push(&mut self, c: char) {
+        if let Some(ref mut search) = self.search {
+            search.query.push(c);
+        }
+        self.search_update();
+    }
+
+    pub fn search_pop(&mut self) {
+        if let Some(ref mut search) = self.search {
+            search.query.pop();
+        }
+        self.search_update();
+    }
+
+    fn search_update(&mut self) {
+        let Some(ref search) = self.search else {
+            return;
+        };
+        let origin = search.origin;
+        let hit = self.find(&search.query, search.forward, origin);
+        self.move_to(hit.unwrap_or(origin));
+    }
+
+    /// Enter: keep the cursor on the match and remember the query for `n`
+    pub fn confirm_search(&mut self) {
+        if let Some(search) = self.search.take()
+            && !search.query.is_empty()
+        {
+            self.last_search = Some((search.query, search.forward));
+        }
+    }
+
+    /// Esc: close the prompt and go back to where the search started
+    pub fn cancel_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            self.move_to(search.origin);
+        }
+    }
+
+    /// `n` (`reverse` false) or `N`: the next match of the last search in
+    /// its direction, or the other way. Returns false when there is none.
+    pub fn next_match(&mut self, reverse: bool) -> bool {
+        let Some((ref query, forward)) = self.last_search else {
+            return false;
+        };
+        match self.find(query, forward != reverse, self.cursor) {
+            Some(hit) => {
+                self.move_to(hit);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The query matches are highlighted for: the one being typed, or the
+    /// last one confirmed
+    fn highlight_query(&self) -> Option<&str> {
+        match self.search {
+            Some(ref search) => Some(search.query.as_str()),
+            None => self.last_search.as_ref().map(|(q, _)| q.as_str()),
+        }
+        .filter(|q| !q.is_empty())
+    }
+
+    /// Columns of `line` to highlight as search matches
+    pub fn highlights(&self, line: usize) -> Vec<Range<usize>> {
+        let Some(query) = self.highlight_query() else {
+            return Vec::new();
+        };
+        let needle: Vec<char> = query.chars().collect();
+        let ignore_case = ignore_case(query);
+        find_in_line(self.line(line), &needle, ignore_case)
+            .into_iter()
+            .map(|start| start..start + needle.len())
+            .collect()
+    }
+
+    /// Whether the query being typed has no match
+    pub fn search_failed(&self) -> bool {
+        self.search.as_ref().is_some_and(|s| {
+            !s.query.is_empty() && self.find(&s.query, s.forward, s.origin).is_none()
+        })
+    }
+
+    /// The first match of `query` after (or before) `from`, wrapping around
+    /// the snapshot. Smart case: the search ignores case unless the query
+    /// has an uppercase letter.
+    fn find(&self, query: &str, forward: bool, from: Pos) -> Option<Pos> {
+        if query.is_empty() {
+            return None;
+        }
+        let needle: Vec<char> = query.chars().collect();
+        let ignore_case = ignore_case(query);
+        let count = self.lines.len();
+
+        // From's own line comes first (past from) and last (up to from)
+        for step in 0..=count {
+            let line = if forward {
+                (from.0 + step) % count
+            } else {
+                (from.0 + count - step % count) % count
+            };
+            let hits = find_in_line(&self.lines[line], &needle, ignore_case);
+            let hit = match (step, forward) {
+                (0, true) => hits.into_iter().find(|c| *c > from.1),
+                (0, false) => hits.into_iter().rev().find(|c| *c < from.1),
+                (s, true) if s == count => hits.into_iter().find(|c| *c <= from.1),
+                (s, false) if s == count => hits.into_iter().rev().find(|c| *c >= from.1),
+                (_, true) => hits.first().copied(),
+                (_, false) => hits.last().copied(),
+            };
+            if let Some(col) = hit {
+                return Some((line, col));
+            }
+        }
+        None
+    }
+}
+
+/// Characters `w` and `b` treat alike
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CharClass {
+    Blank,
+    Word,
+    Punctuation,
+}
+
+impl CharClass {
+    fn of(c: char) -> Self {
+        if c.is_whitespace() {
+            CharClass::Blank
+        } else if c.is_alphanumeric() || c == '_' {
+            CharClass::Word
+        } else {
+            CharClass::Punctuation
+        }
+    }
+}
+
+/// Smart case: ignore case unless the query has an uppercase letter
+fn ignore_case(query: &str) -> bool {
+    !query.chars().any(char::is_uppercase)
+}
+
+/// Start columns of the non-overlapping matches of `needle` in `line`
+fn find_in_line(line: &[char], needle: &[char], ignore_case: bool) -> Vec<usize> {
+    let fold = |c: char| {
+        if ignore_case {
+            c.to_lowercase().next().unwrap_or(c)
+        } else {
+            c
+        }
+    };
+    let mut hits = Vec::new();
+    if needle.is_empty() {
+        return hits;
+    }
+    let mut col = 0;
+    while col + needle.len() <= line.len() {
+        let matched = line[col..col + needle.len()]
+            .iter()
+            .zip(needle)
+            .all(|(a, b)| fold(*a) == fold(*b));
+        if matched {
+            hits.push(col);
+            col += needle.len();
+        } else {
+            col += 1;
+        }
+    }
+    hits
+}
+
+/// OSC 52 sequence that asks the terminal to put `text` on the clipboard
+pub fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64(text.as_bytes()))
+}
+
+/// Standard base64 with padding, for OSC 52
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn copy(lines: &[&str]) -> CopyMode {
+        CopyMode::new(lines.iter().map(|l| l.to_string()).collect(), 2, 0, (0, 0))
+    }
+
+    #[test]
+    fn test_motions_and_selection() {
+        let mut copy = copy(&["error: foo.rs failed", "", "  next line", "last"]);
+        copy.word_forward();
+        assert_eq!(copy.cursor, (0, 5));
+        copy.word_forward();
+        assert_eq!(copy.cursor, (0, 7));
+        copy.line_end();
+        copy.word_forward();
+        assert_eq!(copy.cursor, (2, 2));
+        copy.word_back();
+        assert_eq!(copy.cursor, (0, 14));
+
+        copy.down(2);
+        assert_eq!(copy.cursor, (2, 10));
+        assert_eq!(copy.top, 1);
+        copy.last_line();
+        assert_eq!((copy.cursor, copy.top), ((3, 0), 2));
+        copy.first_line();
+        assert_eq!(copy.top, 0);
+
+        // Nothing selected yanks the cursor's line
+        assert_eq!(copy.selected_text(), "error: foo.rs failed");
+        copy.right();
+        copy.toggle_selection(SelectionKind::Char);
+        copy.down(2);
+        copy.first_non_blank();
+        copy.right();
+        assert!(copy.is_selected(1, 40));
+        assert!(!copy.is_selected(2, 4));
+        assert_eq!(copy.selected_text(), "rror: foo.rs failed\n\n  ne");
+        copy.toggle_selection(SelectionKind::Line);
+        assert_eq!(copy.selected_text(), "error: foo.rs failed\n\n  next line");
+        copy.toggle_selection(SelectionKind::Line);
+        assert_eq!(copy.selection, None);
+    }
+
+    #[test]
+    fn test_incremental_search() {
+        let mut copy = copy(&["Error one", "fine", "an error two"]);
+        copy.start_search(true);
+        copy.search_push('e');
+        assert_eq!(copy.cursor, (0, 8));
+        copy.search_push('r');
+        assert_eq!(copy.cursor, (2, 3));
+        assert_eq!(copy.highlights(0), vec![0..2]);
+        copy.search_push('x');
+        assert!(copy.search_failed());
+        assert_eq!(copy.cursor, (0, 0));
+        copy.search_pop();
+        copy.confirm_search();
+        assert_eq!(copy.cursor, (2, 3));
+
+        // n wraps around, N goes back
+        assert!(copy.next_match(false));
+        assert_eq!(copy.cursor, (0, 0));
+        assert!(copy.next_match(true));
+        assert_eq!(copy.cursor, (2, 3));
+
+        // An uppercase letter makes the search case sensitive
+        copy.start_search(false);
+        for c in "Err".chars() {
+            copy.search_push(c);
+        }
+        assert_eq!(copy.cursor, (0, 0));
+        assert!(copy.highlights(2).is_empty());
+        copy.cancel_search();
+        assert_eq!(copy.cursor, (2, 3));
+    }
+
+    #[test]
+    fn test_osc52() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(osc52("hi"), "\x1b]52;c;aGk=\x07");
+    }
+}
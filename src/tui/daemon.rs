@@ -0,0 +1,482 @@
+//! TUI daemon (`hydra daemon`)
+//!
+//! Owns the tabs, their PTYs and their iteration loops, so agents keep
+//! running when the terminal showing them closes. `hydra tui` starts the
+//! daemon in the background when none is running and attaches to it over
+//! `.hydra/daemon.sock`; every attached client gets each tab's screen and
+//! scrollback on attach and its output from then on. The daemon exits once
+//! it has no tabs and no clients.
+
+use crate::config::Config;
+use crate::error::{HydraError, Result};
+use crate::tui::protocol::{self, Connection, Event, Frame, PaneSize, Request, TabInfo};
+use crate::tui::tab::{self, Tab};
+use std::fs::{self, OpenOptions};
+use std::io::{self, ErrorKind};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long the daemon waits for its first tab before giving up
+const STARTUP_GRACE: Duration = Duration::from_secs(10);
+
+/// How long `hydra tui` waits for a daemon it started to listen
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pause between loop passes when nothing happened
+const IDLE_SLEEP: Duration = Duration::from_millis(10);
+
+/// An accepted connection
+struct Client {
+    connection: Connection<Request>,
+    /// Gets tab updates and output (`hydra ls` connections don't)
+    attached: bool,
+}
+
+/// Daemon state; see the module docs
+pub struct Daemon {
+    listener: UnixListener,
+    path: PathBuf,
+    tabs: Vec<Tab>,
+    clients: Vec<Client>,
+    next_key: u64,
+    /// Content size of the most recent client to attach or resize, for
+    /// tabs it doesn't show in a pane
+    size: (u16, u16),
+    /// Tab infos last sent, to send again only on change
+    sent_tabs: Vec<TabInfo>,
+    started: Instant,
+}
+
+impl Daemon {
+    /// Listen on `path`, replacing a socket left behind by a daemon that
+    /// died; fails when a daemon is listening there
+    pub fn bind(path: &Path) -> Result<Self> {
+        if UnixStream::connect(path).is_ok() {
+            return Err(HydraError::io(
+                format!("starting daemon on {}", path.display()),
+                io::Error::new(
+                    ErrorKind::AddrInUse,
+                    "a hydra daemon is already running in this directory",
+                ),
+            ));
+        }
+        if path.exists() {
+            let _ = fs::remove_file(path);
+        }
+        let listener = UnixListener::bind(path)
+            .map_err(|e| HydraError::io(format!("binding {}", path.display()), e))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| HydraError::io("configuring daemon socket", e))?;
+
+        Ok(Self {
+            listener,
+            path: path.to_path_buf(),
+            tabs: Vec::new(),
+            clients: Vec::new(),
+            next_key: 1,
+            size: (24, 80),
+            sent_tabs: Vec::new(),
+            started: Instant::now(),
+        })
+    }
+
+    /// Serve until there are no tabs and no clients left
+    pub fn run(&mut self) {
+        while self.step() {
+            thread::sleep(IDLE_SLEEP);
+        }
+    }
+
+    /// One pass: accept connections, handle requests, pass tab output on.
+    /// A tab failing stops that tab only. Returns false when the daemon
+    /// should exit.
+    fn step(&mut self) -> bool {
+        self.accept();
+
+        let mut index = 0;
+        while index < self.clients.len() {
+            match self.clients[index].connection.try_recv() {
+                Ok(Some(Frame::Message(request))) => self.handle(index, request),
+                Ok(Some(Frame::Data { key, data })) => {
+                    if let Some(tab) = self.tabs.iter_mut().find(|t| t.key == key) {
+                        // A tab whose agent is gone has nowhere to send it
+                        let _ = tab.send_input(&data);
+                    }
+                }
+                Ok(None) => index += 1,
+                Err(_) => {
+                    self.clients.remove(index);
+                }
+            }
+        }
+
+        // Tab changes go out before the output of a tab just opened
+        self.broadcast_tabs();
+        for tab in &mut self.tabs {
+            if let Err(e) = tab.poll_output() {
+                stop_failed(tab, &e);
+            }
+        }
+        self.flush_output(None);
+        self.broadcast_tabs();
+
+        let idle = self.tabs.is_empty() && self.clients.is_empty();
+        !(idle && (self.next_key > 1 || self.started.elapsed() > STARTUP_GRACE))
+    }
+
+    fn accept(&mut self) {
+        while let Ok((stream, _)) = self.listener.accept() {
+            // The listener is non-blocking; the connection's threads block
+            if stream.set_nonblocking(false).is_err() {
+                continue;
+            }
+            if let Ok(connection) = Connection::new(stream) {
+                self.clients.push(Client {
+                    connection,
+                    attached: false,
+                });
+            }
+        }
+    }
+
+    fn handle(&mut self, index: usize, request: Request) {
+        let reply = match request {
+            Request::Attach { rows, cols } => {
+                // Clients already attached get the tabs that change size;
+                // this one gets all of them
+                self.resize(rows, cols, &[]);
+                self.clients[index].attached = true;
+                let keys: Vec<u64> = self.tabs.iter().map(|t| t.key).collect();
+                self.send_screens(Some(index), &keys);
+                None
+            }
+            Request::Resize { rows, cols, panes } => {
+                self.resize(rows, cols, &panes);
+                None
+            }
+            Request::OpenTab { config, session } => Some(self.open_tab(&config, &session)),
+            Request::CloseTab { key } => {
+                // Dropping the tab stops its agent
+                self.tabs.retain(|t| t.key != key);
+                None
+            }
+            Request::KillTab { key } => {
+                if let Some(tab) = self.tabs.iter_mut().find(|t| t.key == key) {
+                    tab.kill();
+                }
+                None
+            }
+            Request::Answer { key, answer } => {
+                let tab = self.tabs.iter_mut().find(|t| t.key == key);
+                tab.and_then(|tab| {
+                    let e = tab.answer(answer.as_deref()).err()?;
+                    stop_failed(tab, &e);
+                    Some(Event::Error(e.to_string()))
+                })
+            }
+            Request::List => Some(Event::Listing {
+                pid: std::process::id(),
+                clients: self.clients.iter().filter(|c| c.attached).count(),
+                tabs: self.tabs.iter().map(Tab::info).collect(),
+            }),
+        };
+        if let Some(event) = reply {
+            let _ = self.clients[index]
+                .connection
+                .send(protocol::encode_message(&event));
+        }
+    }
+
+    fn open_tab(&mut self, config: &Config, session: &crate::tui::TabSession) -> Event {
+        let key = self.next_key;
+        let (rows, cols) = self.size;
+        match Tab::new(key, config, session, rows, cols) {
+            Ok(tab) => {
+                self.next_key += 1;
+                self.tabs.push(tab);
+                Event::Opened { key }
+            }
+            Err(e) => Event::Error(e.to_string()),
+        }
+    }
+
+    /// Give the tabs in `panes` their pane's size and the others
+    /// `rows` x `cols`. Attached clients get the screens of the tabs whose
+    /// size changed again, at the new size.
+    fn resize(&mut self, rows: u16, cols: u16, panes: &[PaneSize]) {
+        self.size = (rows, cols);
+        let mut resized = Vec::new();
+        for tab in &mut self.tabs {
+            let size = panes
+                .iter()
+                .find(|p| p.key == tab.key)
+                .map_or((rows, cols), |p| (p.rows, p.cols));
+            if tab.size() != size {
+                tab.resize(size.0, size.1);
+                resized.push(tab.key);
+            }
+        }
+        if !resized.is_empty() {
+            self.send_screens(None, &resized);
+        }
+    }
+
+    /// Send the tab list and the size and a replay of the tabs with `keys`
+    /// to one attached client, or all of them
+    fn send_screens(&mut self, only: Option<usize>, keys: &[u64]) {
+        let mut frames = vec![protocol::encode_message(&Event::Tabs(
+            self.tabs.iter().map(Tab::info).collect(),
+        ))];
+        if only.is_some() {
+            self.flush_output(only);
+        }
+        for tab in self.tabs.iter_mut().filter(|t| keys.contains(&t.key)) {
+            // Output not yet passed on is part of the replay
+            tab.take_output();
+            let (rows, cols) = tab.size();
+            frames.push(protocol::encode_message(&Event::Resized {
+                key: tab.key,
+                rows,
+                cols,
+            }));
+            frames.push(protocol::encode_data(
+                tab.key,
+                &tab::replay(&mut tab.parser),
+            ));
+        }
+        for (i, client) in self.clients.iter().enumerate() {
+            if client.attached && only.is_none_or(|o| o == i) {
+                for frame in &frames {
+                    let _ = client.connection.send(frame.clone());
+                }
+            }
+        }
+    }
+
+    /// Pass the tabs' new output on to attached clients, but `skip`. Dead
+    /// clients are dropped when their connection reports it.
+    fn flush_output(&mut self, skip: Option<usize>) {
+        for tab in &mut self.tabs {
+            let output = tab.take_output();
+            if output.is_empty() {
+                continue;
+            }
+            let frame = protocol::encode_data(tab.key, &output);
+            for (i, client) in self.clients.iter().enumerate() {
+                if client.attached && skip != Some(i) {
+                    let _ = client.connection.send(frame.clone());
+                }
+            }
+        }
+    }
+
+    /// Send the tab list to attached clients if it changed
+    fn broadcast_tabs(&mut self) {
+        let infos: Vec<TabInfo> = self.tabs.iter().map(Tab::info).collect();
+        if infos == self.sent_tabs {
+            return;
+        }
+        let frame = protocol::encode_message(&Event::Tabs(infos.clone()));
+        for client in self.clients.iter().filter(|c| c.attached) {
+            let _ = client.connection.send(frame.clone());
+        }
+        self.sent_tabs = infos;
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Stop a tab whose loop failed, noting why in `.hydra/daemon.log`; the
+/// other tabs keep running
+fn stop_failed(tab: &mut Tab, error: &HydraError) {
+    let plan = tab
+        .driver
+        .plan_path()
+        .map(crate::plan::plan_name)
+        .unwrap_or_else(|| "(no plan)".to_string());
+    eprintln!("[hydra] Tab {} ({}) stopped: {}", tab.key, plan, error);
+    tab.fail(error);
+}
+
+/// Entry point for `hydra daemon`
+pub fn run_daemon() -> Result<()> {
+    fs::create_dir_all(Config::local_hydra_dir())
+        .map_err(|e| HydraError::io("creating .hydra directory", e))?;
+    Daemon::bind(&Config::daemon_socket_path())?.run();
+    Ok(())
+}
+
+/// Connect to this directory's daemon
+pub fn connect() -> io::Result<UnixStream> {
+    UnixStream::connect(Config::daemon_socket_path())
+}
+
+/// Start `hydra daemon` in the background, in its own process group so the
+/// terminal's signals don't reach it, and connect once it listens. Its
+/// errors go to `.hydra/daemon.log`.
+pub fn spawn() -> Result<UnixStream> {
+    fs::create_dir_all(Config::local_hydra_dir())
+        .map_err(|e| HydraError::io("creating .hydra directory", e))?;
+    let exe = std::env::current_exe().map_err(|e| HydraError::io("finding hydra binary", e))?;
+    let log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(Config::daemon_log_path())
+        .map_err(|e| HydraError::io("opening .hydra/daemon.log", e))?;
+    Command::new(exe)
+        .arg("daemon")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(log)
+        .process_group(0)
+        .spawn()
+        .map_err(|e| HydraError::io("starting hydra daemon", e))?;
+
+    let started = Instant::now();
+    loop {
+        match connect() {
+            Ok(stream) => return Ok(stream),
+            Err(e) if started.elapsed() > CONNECT_TIMEOUT => {
+                return Err(HydraError::io(
+                    "connecting to hydra daemon (see .hydra/daemon.log)",
+                    e,
+                ));
+            }
+            Err(_) => thread::sleep(Duration::from_millis(50)),
+        }
+    }
+}
+
+/// Entry point for `hydra ls`: list the daemon's tabs
+pub fn ls_command() -> Result<()> {
+    let Ok(stream) = connect() else {
+        println!("No hydra daemon running in this directory");
+        return Ok(());
+    };
+    let connection: Connection<Event> =
+        Connection::new(stream).map_err(|e| HydraError::io("connecting to hydra daemon", e))?;
+    connection
+        .send(protocol::encode_message(&Request::List))
+        .map_err(|e| HydraError::io("sending to hydra daemon", e))?;
+
+    loop {
+        match connection.recv() {
+            Ok(Frame::Message(Event::Listing { pid, clients, tabs })) => {
+                println!(
+                    "hydra daemon (PID {}): {} tab(s), {} client(s) attached",
+                    pid,
+                    tabs.len(),
+                    clients
+                );
+                for (i, tab) in tabs.iter().enumerate() {
+                    println!("  {}", describe_tab(i + 1, tab));
+                }
+                return Ok(());
+            }
+            Ok(_) => {}
+            Err(e) => return Err(HydraError::io("reading from hydra daemon", e)),
+        }
+    }
+}
+
+/// One `hydra ls` line, e.g. `1 auth (claude) iteration 3/20 - running`
+fn describe_tab(number: usize, tab: &TabInfo) -> String {
+    let plan = tab
+        .plan_path
+        .as_deref()
+        .map(crate::plan::plan_name)
+        .unwrap_or_else(|| "(no plan)".to_string());
+    format!(
+        "{} {} ({}) iteration {}/{} - {}",
+        number,
+        plan,
+        tab.harness,
+        tab.iteration,
+        tab.max_iterations,
+        tab.status.describe()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::Harness;
+    use crate::tui::tab::TabStatus;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_daemon_lists_and_exits_when_idle() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("daemon.sock");
+        let mut daemon = Daemon::bind(&path).unwrap();
+        assert!(Daemon::bind(&path).is_err());
+
+        let client: Connection<Event> =
+            Connection::new(UnixStream::connect(&path).unwrap()).unwrap();
+        client
+            .send(protocol::encode_message(&Request::List))
+            .unwrap();
+        let mut listing = None;
+        for _ in 0..200 {
+            assert!(daemon.step());
+            if let Some(Frame::Message(Event::Listing { tabs, clients, .. })) =
+                client.try_recv().unwrap()
+            {
+                listing = Some((tabs.len(), clients));
+                break;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(listing, Some((0, 0)));
+
+        // Without tabs or clients, and past startup, the daemon is done
+        drop(client);
+        daemon.next_key = 2;
+        let mut running = true;
+        for _ in 0..200 {
+            running = daemon.step();
+            if !running {
+                break;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert!(!running);
+        drop(daemon);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_describe_tab() {
+        let tab = TabInfo {
+            key: 4,
+            status: TabStatus::Running,
+            harness: Harness::Pi,
+            plan_path: Some(PathBuf::from("plans/auth.md")),
+            scratchpad_path: None,
+            iteration: 3,
+            max_iterations: 20,
+            elapsed_seconds: 0,
+            timeout_seconds: 600,
+            rows: 24,
+            cols: 80,
+            idle_seconds: 0,
+            waiting: false,
+            failed_exits: 0,
+            exit_error: None,
+            question: None,
+        };
+        assert_eq!(
+            describe_tab(1, &tab),
+            "1 auth (pi) iteration 3/20 - running"
+        );
+    }
+}
@@ -0,0 +1,439 @@
+//! Iteration loop for a TUI tab
+//!
+//! The TUI event loop can't block in `Runner::run`, so each tab owns a
+//! `LoopDriver` that keeps the same bookkeeping step by step: the iteration
+//! counter, the per-iteration timeout, timeout notes in the scratchpad, the
+//! duration history and the session log. The tab spawns the agent with the
+//! prompt from `start_iteration` and reports how it ended to
+//! `finish_iteration`, which says whether to spawn the next one. A question
+//! from the agent pauses the loop until `answer` resumes it.
+
+use crate::config::Config;
+use crate::error::{HydraError, Result};
+use crate::harness::Harness;
+use crate::history::{self, DurationHistory};
+use crate::include;
+use crate::lock;
+use crate::plan;
+use crate::prompt::{self, IterationContext, PreviousIteration, ResolvedPrompt};
+use crate::question;
+use crate::runner::{self, IterationResult, SessionLogger};
+use crate::template::{self, TemplateVars};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tempfile::NamedTempFile;
+
+/// What a tab runs: the harness, the prompt and iteration instructions, and
+/// the plan with its scratchpad (see `prepare_session_prompt`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TabSession {
+    pub harness: Harness,
+    pub prompt: ResolvedPrompt,
+    pub instructions: String,
+    pub plan_path: Option<PathBuf>,
+    pub scratchpad_path: Option<PathBuf>,
+}
+
+impl TabSession {
+    /// Set up a tab for `plan` (or none) with the given, not yet composed,
+    /// prompt: includes and fragments are expanded, the plan, scratchpad and
+    /// question file are placed, and the first iteration's rendering is
+    /// checked so template errors surface before the tab starts
+    pub fn prepare(
+        config: &Config,
+        harness: Harness,
+        mut prompt: ResolvedPrompt,
+        instructions: &str,
+        plan: Option<&Path>,
+    ) -> Result<Self> {
+        include::compose_prompt(&mut prompt, &config.prompt_fragments)?;
+        let scratchpad_path = crate::prepare_session_prompt(&mut prompt, plan)?;
+        template::render_prompt(
+            &prompt,
+            &TemplateVars::collect(plan, scratchpad_path.as_deref(), None, 1, 1),
+        )?;
+        Ok(Self {
+            harness,
+            prompt,
+            instructions: instructions.to_string(),
+            plan_path: plan.map(Path::to_path_buf),
+            scratchpad_path,
+        })
+    }
+}
+
+/// Drives one tab's iterations; see the module docs
+pub struct LoopDriver {
+    config: Config,
+    session: TabSession,
+    question_path: PathBuf,
+    logger: Option<SessionLogger>,
+    history: Option<DurationHistory>,
+    /// Current iteration (0 before the first one starts)
+    iteration: u32,
+    /// Timeout chosen for the current iteration
+    timeout_seconds: u64,
+    /// First unchecked task when the iteration started
+    next_task: Option<String>,
+    started: Instant,
+    /// Outcome of the last iteration, shown in the next prompt
+    previous: Option<PreviousIteration>,
+    /// Raw PTY output of the current iteration, copied to the log at the end
+    output: Vec<u8>,
+    /// Question the loop is paused on
+    question: Option<String>,
+    /// Answer to the last question, for the next prompt
+    pending_answer: Option<String>,
+}
+
+impl LoopDriver {
+    pub fn new(config: Config, session: TabSession) -> Self {
+        let plan_name = session.plan_path.as_deref().map(plan::plan_name);
+        // The TUI owns the terminal, so a missing log is not reported
+        let mut logger = SessionLogger::new(plan_name.as_deref()).ok();
+        if let Some(ref mut logger) = logger {
+            let _ = logger.log(&format!(
+                "Session started (tui) - max iterations: {}",
+                config.max_iterations
+            ));
+            let _ = logger.log(&format!("Harness: {}", session.harness));
+            let _ = logger.log(&format!("Prompt file: {}", session.prompt.path.display()));
+            if let Some(ref plan) = plan_name {
+                let _ = logger.log(&format!("Plan: {}", plan));
+            }
+        }
+
+        Self {
+            question_path: question::question_path(&lock::session_name(
+                session.plan_path.as_deref(),
+            )),
+            history: plan_name.as_deref().map(DurationHistory::load),
+            logger,
+            iteration: 0,
+            timeout_seconds: config.timeout_seconds,
+            next_task: None,
+            started: Instant::now(),
+            previous: None,
+            output: Vec::new(),
+            question: None,
+            pending_answer: None,
+            config,
+            session,
+        }
+    }
+
+    /// Current iteration (0 before the first one starts)
+    pub fn iteration(&self) -> u32 {
+        self.iteration
+    }
+
+    pub fn max_iterations(&self) -> u32 {
+        self.config.max_iterations
+    }
+
+    /// The harness this tab spawns
+    pub fn harness(&self) -> Harness {
+        self.session.harness
+    }
+
+    /// The plan this tab works on, if any
+    pub fn plan_path(&self) -> Option<&Path> {
+        self.session.plan_path.as_deref()
+    }
+
+    /// The tab's scratchpad, if it has a plan
+    pub fn scratchpad_path(&self) -> Option<&Path> {
+        self.session.scratchpad_path.as_deref()
+    }
+
+    /// Question the loop is paused on, if any
+    pub fn question(&self) -> Option<&str> {
+        self.question.as_deref()
+    }
+
+    /// Time since the current iteration started
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Timeout chosen for the current iteration
+    pub fn timeout_seconds(&self) -> u64 {
+        self.timeout_seconds
+    }
+
+    /// Begin the next iteration and return its combined prompt file, which
+    /// must outlive the spawn
+    pub fn start_iteration(&mut self) -> Result<NamedTempFile> {
+        self.iteration += 1;
+        let estimate = history::estimate_iteration(
+            &self.config,
+            self.session.plan_path.as_deref(),
+            self.history.as_ref(),
+        );
+        self.timeout_seconds = estimate.timeout_seconds;
+        self.next_task = estimate.next_task;
+        self.output.clear();
+
+        if let Some(ref mut logger) = self.logger {
+            let _ = logger.log_iteration_start(self.iteration, self.config.max_iterations);
+            let _ = logger.log(&format!(
+                "Iteration timeout: {}s ({})",
+                estimate.timeout_seconds, estimate.source
+            ));
+        }
+
+        let mut combined = format!("{}\n{}", self.session.instructions, self.render_prompt()?);
+        if let Some(note) = self.pending_answer.take() {
+            combined = format!("{}\n\n{}", combined.trim_end(), note);
+        }
+        let mut file =
+            NamedTempFile::new().map_err(|e| HydraError::io("creating temp prompt file", e))?;
+        file.write_all(combined.as_bytes())
+            .map_err(|e| HydraError::io("writing combined prompt", e))?;
+
+        self.started = Instant::now();
+        Ok(file)
+    }
+
+    /// The user prompt rendered for this iteration, followed by the
+    /// `## This Iteration` section
+    fn render_prompt(&self) -> Result<String> {
+        let vars = TemplateVars::collect(
+            self.session.plan_path.as_deref(),
+            self.session.scratchpad_path.as_deref(),
+            Some(&self.question_path),
+            self.iteration,
+            self.config.max_iterations,
+        );
+        let rendered = template::render_prompt(&self.session.prompt, &vars)?;
+        let section = prompt::iteration_context_section(&IterationContext {
+            iteration: self.iteration,
+            max_iterations: self.config.max_iterations,
+            timeout_seconds: self.timeout_seconds,
+            next_task: vars.next_task.as_deref(),
+            previous: self.previous.as_ref(),
+        });
+        Ok(format!("{}\n\n{}", rendered.trim_end(), section))
+    }
+
+    /// Keep PTY output for the session log
+    pub fn record_output(&mut self, data: &[u8]) {
+        self.output.extend_from_slice(data);
+    }
+
+    /// Whether the current iteration has run past its timeout
+    pub fn timed_out(&self) -> bool {
+        self.started.elapsed() >= Duration::from_secs(self.timeout_seconds)
+    }
+
+    /// Close the current iteration. Returns true when the loop goes on with
+    /// another iteration, as `Runner::run` would.
+    pub fn finish_iteration(&mut self, result: &IterationResult) -> bool {
+        let elapsed = self.started.elapsed();
+        self.previous = Some(PreviousIteration {
+            result: result.clone(),
+            elapsed,
+        });
+        if *result != IterationResult::Terminated
+            && let Some(ref mut history) = self.history
+        {
+            let _ = history.record(elapsed.as_secs(), result, self.next_task.as_deref());
+        }
+
+        if let Some(ref mut logger) = self.logger {
+            let _ = logger.append_content(&String::from_utf8_lossy(&self.output));
+            let _ = logger.log_iteration_end(self.iteration, result);
+        }
+        self.output.clear();
+
+        if *result == IterationResult::Timeout
+            && let Some(ref scratchpad) = self.session.scratchpad_path
+        {
+            runner::append_timeout_note(
+                scratchpad,
+                self.iteration,
+                self.timeout_seconds,
+                self.logger.as_ref(),
+            );
+        }
+
+        let ended = match result {
+            IterationResult::AllComplete => Some(format!(
+                "all tasks complete after {} iterations",
+                self.iteration
+            )),
+            IterationResult::Terminated => Some("terminated".to_string()),
+            IterationResult::Question => {
+                // The TUI has no prompt to answer on; the loop pauses until
+                // the operator answers (see `answer`)
+                self.question = question::read_question(&self.question_path);
+                let text = self.question.as_deref().unwrap_or("(missing)");
+                self.log(&format!("ITERATION {} QUESTION: {}", self.iteration, text));
+                if self.question.is_some() {
+                    return false;
+                }
+                self.out_of_iterations()
+            }
+            IterationResult::TaskComplete
+            | IterationResult::NoSignal
+            | IterationResult::Timeout => self.out_of_iterations(),
+        };
+        self.go_on(ended)
+    }
+
+    /// Answer the question the loop is paused on, as `Runner` does at its
+    /// prompt: the answer goes to the scratchpad and the next prompt and the
+    /// question file is archived. `None` leaves the decision to the agent.
+    /// Returns true when the loop goes on; an answer to a question on the
+    /// last iteration buys one more.
+    pub fn answer(&mut self, answer: Option<&str>) -> bool {
+        let Some(text) = self.question.take() else {
+            return false;
+        };
+        self.log(&format!(
+            "ITERATION {} ANSWER: {}",
+            self.iteration,
+            answer.unwrap_or("(none)")
+        ));
+        question::record_in_scratchpad(
+            self.session.scratchpad_path.as_deref(),
+            self.iteration,
+            &text,
+            answer,
+        );
+        if let Err(e) = question::archive(&self.question_path, &text, answer) {
+            self.log(&format!("Could not archive question: {}", e));
+        }
+        self.pending_answer = Some(question::answer_note(&text, answer));
+
+        if answer.is_some() && self.iteration >= self.config.max_iterations {
+            self.config.max_iterations += 1;
+            self.log(&format!(
+                "Iteration budget raised to {} for the answer",
+                self.config.max_iterations
+            ));
+        }
+        let ended = self.out_of_iterations();
+        self.go_on(ended)
+    }
+
+    /// End the loop on an error the tab can't go on from
+    pub fn abort(&mut self, error: &str) {
+        self.question = None;
+        self.log(&format!("Session ended: {}", error));
+    }
+
+    /// Why the session ends if the budget is used up
+    fn out_of_iterations(&self) -> Option<String> {
+        (self.iteration >= self.config.max_iterations)
+            .then(|| format!("max iterations ({}) reached", self.config.max_iterations))
+    }
+
+    fn log(&mut self, message: &str) {
+        if let Some(ref mut logger) = self.logger {
+            let _ = logger.log(message);
+        }
+    }
+
+    /// True when the loop goes on; otherwise logs why the session `ended`
+    fn go_on(&mut self, ended: Option<String>) -> bool {
+        match ended {
+            Some(reason) => {
+                self.log(&format!("Session ended: {}", reason));
+                false
+            }
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt::PromptSource;
+
+    fn driver(max_iterations: u32) -> LoopDriver {
+        let config = Config {
+            max_iterations,
+            timeout_seconds: 600,
+            ..Config::default()
+        };
+        let mut driver = LoopDriver::new(
+            config,
+            TabSession {
+                harness: Harness::Claude,
+                prompt: ResolvedPrompt {
+                    path: PathBuf::from("prompt.md"),
+                    content: "Do the next task".to_string(),
+                    source: PromptSource::CliOverride,
+                },
+                instructions: "Instructions".to_string(),
+                plan_path: None,
+                scratchpad_path: None,
+            },
+        );
+        // Keep the test from writing session logs
+        driver.logger = None;
+        driver
+    }
+
+    #[test]
+    fn test_iterations_continue_until_budget_or_signal() {
+        let mut driver = driver(2);
+        let file = driver.start_iteration().unwrap();
+        let prompt = std::fs::read_to_string(file.path()).unwrap();
+        assert!(prompt.starts_with("Instructions\nDo the next task"));
+        assert!(prompt.contains("- Iteration: 1 of 2"));
+        assert!(!driver.timed_out());
+        assert!(driver.finish_iteration(&IterationResult::TaskComplete));
+
+        let file = driver.start_iteration().unwrap();
+        let prompt = std::fs::read_to_string(file.path()).unwrap();
+        assert!(prompt.contains("- Iteration: 2 of 2"));
+        assert!(prompt.contains("- Previous iteration:"));
+        assert!(!driver.finish_iteration(&IterationResult::TaskComplete));
+
+        let mut driver = self::driver(5);
+        driver.start_iteration().unwrap();
+        assert!(!driver.finish_iteration(&IterationResult::AllComplete));
+        driver.start_iteration().unwrap();
+        assert!(driver.finish_iteration(&IterationResult::Timeout));
+        assert_eq!(driver.iteration(), 2);
+    }
+
+    #[test]
+    fn test_question_pauses_until_answered() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut driver = driver(1);
+        driver.question_path = dir.path().join("main.md");
+
+        // A question signal without a question file goes on as usual
+        driver.config.max_iterations = 2;
+        driver.start_iteration().unwrap();
+        assert!(driver.finish_iteration(&IterationResult::Question));
+        assert_eq!(driver.question(), None);
+        driver.config.max_iterations = 1;
+
+        std::fs::write(&driver.question_path, "Which database?\n").unwrap();
+        assert!(!driver.finish_iteration(&IterationResult::Question));
+        assert_eq!(driver.question(), Some("Which database?"));
+
+        // An answer on the last iteration buys one more, which gets it
+        assert!(driver.answer(Some("SQLite")));
+        assert_eq!(driver.question(), None);
+        assert!(!driver.question_path.exists());
+        assert_eq!(driver.max_iterations(), 2);
+        let file = driver.start_iteration().unwrap();
+        let prompt = std::fs::read_to_string(file.path()).unwrap();
+        assert!(prompt.contains("## Answer to Your Question"));
+        assert!(prompt.contains("> SQLite"));
+
+        // Leaving it to the agent doesn't
+        std::fs::write(&driver.question_path, "Which port?").unwrap();
+        assert!(!driver.finish_iteration(&IterationResult::Question));
+        assert!(!driver.answer(None));
+        assert!(!driver.answer(None));
+    }
+}
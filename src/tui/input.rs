@@ -1,16 +1,20 @@
 //! Input handling for TUI mode
 //!
-//! Handles keyboard events: tab management and input forwarding.
+//! Handles keyboard events: bound actions (see `keymap.rs`), copy mode, the
+//! tab overview and input forwarding.
 
 use crate::error::Result;
 use crate::tui::app::App;
+use crate::tui::copy::SelectionKind;
+use crate::tui::keymap::Action;
+use crate::tui::pane::Arrangement;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 /// Result of handling a key event
 pub enum KeyAction {
     /// Continue running
     Continue,
-    /// Exit the TUI
+    /// Detach from the daemon and exit the TUI
     Exit,
 }
 
@@ -18,76 +22,182 @@ pub enum KeyAction {
 ///
 /// Returns KeyAction to indicate whether to continue or exit
 pub fn handle_key_event(app: &mut App, event: KeyEvent) -> Result<KeyAction> {
-    // F-key bindings for tab management
-    match event.code {
-        // F1-F7: Switch to tab 1-7
-        KeyCode::F(n) if (1..=7).contains(&n) => {
-            app.switch_to_tab(n);
-            return Ok(KeyAction::Continue);
-        }
+    app.notice = None;
 
-        // F8: Close active tab
-        KeyCode::F(8) => {
-            app.close_active_tab();
-            return Ok(KeyAction::Continue);
-        }
-
-        // F9: Exit TUI
-        KeyCode::F(9) => {
-            return Ok(KeyAction::Exit);
+    // The new-tab picker takes all keys while it's open
+    if let Some(ref mut picker) = app.picker {
+        match event.code {
+            KeyCode::Up | KeyCode::Char('k') => picker.up(),
+            KeyCode::Down | KeyCode::Char('j') => picker.down(),
+            KeyCode::Tab => picker.next_prompt(),
+            KeyCode::Left | KeyCode::Right => picker.next_harness(),
+            KeyCode::Enter => app.confirm_picker(),
+            KeyCode::Esc => app.picker = None,
+            _ => {}
         }
-
-        _ => {}
+        return Ok(KeyAction::Continue);
     }
 
-    // Ctrl+... keybindings
-    if event.modifiers.contains(KeyModifiers::CONTROL) {
+    // The answer prompt takes all keys while it's open
+    if let Some(ref mut input) = app.answer {
         match event.code {
-            // Ctrl+O: New tab
-            KeyCode::Char('o') => {
-                let _ = app.new_tab();
-                return Ok(KeyAction::Continue);
+            KeyCode::Char(c) if !event.modifiers.contains(KeyModifiers::CONTROL) => {
+                input.text.push(c)
             }
-
-            // Ctrl+C: Kill active tab's Claude (not exit TUI)
-            KeyCode::Char('c') => {
-                app.kill_active_tab();
-                return Ok(KeyAction::Continue);
+            KeyCode::Backspace => {
+                input.text.pop();
             }
+            KeyCode::Enter => app.confirm_answer(),
+            KeyCode::Esc => app.answer = None,
+            _ => {}
+        }
+        return Ok(KeyAction::Continue);
+    }
 
-            // Ctrl+Tab: Next tab (Note: Some terminals don't support this)
-            KeyCode::Tab => {
-                if event.modifiers.contains(KeyModifiers::SHIFT) {
-                    app.prev_tab();
-                } else {
-                    app.next_tab();
-                }
-                return Ok(KeyAction::Continue);
-            }
+    // Copy mode takes all keys while it's on
+    if app.copy.is_some() {
+        handle_copy_key(app, event);
+        return Ok(KeyAction::Continue);
+    }
 
+    // The overview takes all keys while it's shown
+    if app.overview.is_some() {
+        match event.code {
+            KeyCode::Left | KeyCode::Char('h') => app.move_overview(-1, 0),
+            KeyCode::Right | KeyCode::Char('l') => app.move_overview(1, 0),
+            KeyCode::Up | KeyCode::Char('k') => app.move_overview(0, -1),
+            KeyCode::Down | KeyCode::Char('j') => app.move_overview(0, 1),
+            KeyCode::Enter => app.confirm_overview(),
+            KeyCode::Esc | KeyCode::Char('q') => app.overview = None,
             _ => {}
         }
+        return Ok(KeyAction::Continue);
     }
 
-    // Ctrl+Shift+Tab: Previous tab
-    if event
-        .modifiers
-        .contains(KeyModifiers::CONTROL | KeyModifiers::SHIFT)
-        && event.code == KeyCode::BackTab
-    {
-        app.prev_tab();
+    // The help overlay closes on any key
+    if app.help {
+        app.help = false;
         return Ok(KeyAction::Continue);
     }
 
+    // After the prefix key: a prefixed binding, a tab number, or the
+    // prefix itself for the agent
+    let keymap = app.config().keymap.clone();
+    if app.prefix_pending {
+        app.prefix_pending = false;
+        if keymap.prefix.matches(&event) {
+            app.send_input(&key_event_to_bytes(&event));
+        } else if let Some(n) = keymap.tab(true, &event) {
+            app.switch_to_tab(n);
+        } else if let Some(action) = keymap.action(true, &event) {
+            return Ok(run_action(app, action));
+        }
+        return Ok(KeyAction::Continue);
+    }
+    if keymap.prefix.matches(&event) {
+        app.prefix_pending = true;
+        return Ok(KeyAction::Continue);
+    }
+
+    if let Some(n) = keymap.tab(false, &event) {
+        app.switch_to_tab(n);
+        return Ok(KeyAction::Continue);
+    }
+    if let Some(action) = keymap.action(false, &event) {
+        return Ok(run_action(app, action));
+    }
+
     // Forward other keys to active tab's PTY
     let bytes = key_event_to_bytes(&event);
     if !bytes.is_empty() {
-        app.send_input(&bytes)?;
+        app.send_input(&bytes);
     }
 
     Ok(KeyAction::Continue)
 }
 
+/// Carry out a bound action
+fn run_action(app: &mut App, action: Action) -> KeyAction {
+    match action {
+        Action::NewTab => app.open_picker(),
+        Action::CloseTab => app.close_active_tab(),
+        Action::NextTab => app.next_tab(),
+        Action::PrevTab => app.prev_tab(),
+        // Kills the agent only, not the TUI
+        Action::KillAgent => app.kill_active_tab(),
+        Action::Answer => app.open_answer(),
+        Action::Detach => return KeyAction::Exit,
+        Action::ScrollUp => app.scroll_up(app.page_lines()),
+        Action::ScrollDown => app.scroll_down(app.page_lines()),
+        Action::CopyMode => app.enter_copy_mode(),
+        Action::Sidebar => app.toggle_sidebar(),
+        Action::SplitColumns => app.split(Arrangement::Columns),
+        Action::SplitRows => app.split(Arrangement::Rows),
+        Action::NextLayout => app.next_layout(),
+        Action::NextPane => app.focus_pane(true),
+        Action::PrevPane => app.focus_pane(false),
+        Action::Unsplit => app.unsplit(),
+        Action::Overview => app.open_overview(),
+        Action::NextAttention => app.next_attention(),
+        Action::Help => app.help = true,
+    }
+    KeyAction::Continue
+}
+
+/// Handle a key in copy mode: vi motions, selection, search and yank
+fn handle_copy_key(app: &mut App, event: KeyEvent) {
+    let ctrl = event.modifiers.contains(KeyModifiers::CONTROL);
+    let Some(ref mut copy) = app.copy else {
+        return;
+    };
+
+    // The search prompt takes the query
+    if copy.search.is_some() {
+        match event.code {
+            KeyCode::Char(c) if !ctrl => copy.search_push(c),
+            KeyCode::Backspace => copy.search_pop(),
+            KeyCode::Enter => copy.confirm_search(),
+            KeyCode::Esc => copy.cancel_search(),
+            _ => {}
+        }
+        return;
+    }
+
+    match event.code {
+        KeyCode::Char('u') if ctrl => copy.half_page_up(),
+        KeyCode::Char('d') if ctrl => copy.half_page_down(),
+        KeyCode::Char('b') if ctrl => copy.page_up(),
+        KeyCode::Char('f') if ctrl => copy.page_down(),
+        KeyCode::Char('c') if ctrl => app.copy = None,
+        KeyCode::Char('h') | KeyCode::Left => copy.left(),
+        KeyCode::Char('j') | KeyCode::Down => copy.down(1),
+        KeyCode::Char('k') | KeyCode::Up => copy.up(1),
+        KeyCode::Char('l') | KeyCode::Right => copy.right(),
+        KeyCode::Char('0') | KeyCode::Home => copy.line_start(),
+        KeyCode::Char('^') => copy.first_non_blank(),
+        KeyCode::Char('$') | KeyCode::End => copy.line_end(),
+        KeyCode::Char('w') => copy.word_forward(),
+        KeyCode::Char('b') => copy.word_back(),
+        KeyCode::Char('g') => copy.first_line(),
+        KeyCode::Char('G') => copy.last_line(),
+        KeyCode::PageUp => copy.page_up(),
+        KeyCode::PageDown => copy.page_down(),
+        KeyCode::Char('v') => copy.toggle_selection(SelectionKind::Char),
+        KeyCode::Char('V') => copy.toggle_selection(SelectionKind::Line),
+        KeyCode::Char('/') => copy.start_search(true),
+        KeyCode::Char('?') => copy.start_search(false),
+        KeyCode::Char(c @ ('n' | 'N')) => {
+            app.notice = (!copy.next_match(c == 'N')).then(|| "No match".to_string());
+        }
+        KeyCode::Char('y') | KeyCode::Enter => app.yank(false),
+        KeyCode::Char('s') => app.yank(true),
+        // Esc drops the selection first, then leaves copy mode
+        KeyCode::Esc if copy.selection.is_some() => copy.selection = None,
+        KeyCode::Esc | KeyCode::Char('q') => app.copy = None,
+        _ => {}
+    }
+}
+
 /// Convert a key event to bytes to send to PTY
 fn key_event_to_bytes(event: &KeyEvent) -> Vec<u8> {
     let mut bytes = Vec::new();
@@ -0,0 +1,505 @@
+//! TUI keybindings (`[keymap]` in config.toml)
+//!
+//! Every action has a list of bindings. A binding is a key such as `f8` or
+//! `ctrl+o`, or the prefix key followed by a key, written `prefix c`, for
+//! terminals that don't pass function keys through. After the prefix,
+//! `1`-`9` switch to that tab and the prefix again sends it to the agent.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A key with its modifiers, written like `ctrl+shift+tab` or `f8`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+/// Key names other than single characters and `f1`-`f12`
+const NAMED_KEYS: [(&str, KeyCode); 14] = [
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("space", KeyCode::Char(' ')),
+    ("backspace", KeyCode::Backspace),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+];
+
+impl Key {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self::normalize(code, modifiers)
+    }
+
+    /// Parse `ctrl+b`, `f8`, `shift+pageup`, `?`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid key \"{}\"", text);
+        let text = text.trim();
+        // `ctrl++` ends in the key `+`
+        let (mods, name) = match text.strip_suffix("++") {
+            Some(mods) => (format!("{}+", mods), "+"),
+            None => match text.rfind('+') {
+                Some(i) => (text[..=i].to_string(), &text[i + 1..]),
+                None => (String::new(), text),
+            },
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for modifier in mods.split('+').filter(|m| !m.is_empty()) {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(invalid()),
+            };
+        }
+
+        // Single characters keep their case; names don't have one
+        let mut chars = name.chars();
+        let lower = name.to_ascii_lowercase();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match NAMED_KEYS.iter().find(|(n, _)| *n == lower) {
+                Some((_, code)) => *code,
+                None => match lower.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(invalid()),
+                },
+            },
+        };
+        Ok(Self::normalize(code, modifiers))
+    }
+
+    /// Make equal keys compare equal however the terminal reports them:
+    /// Shift+Tab is BackTab, and Shift is part of a character (`?`, `B`)
+    /// rather than a modifier; with Ctrl, letters are lowercase.
+    fn normalize(code: KeyCode, mut modifiers: KeyModifiers) -> Self {
+        let code = match code {
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => {
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            code => code,
+        };
+        if matches!(code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        modifiers &= KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT;
+        Self { code, modifiers }
+    }
+
+    /// Whether `event` is this key
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        Self::new(event.code, event.modifiers) == *self
+    }
+
+    /// Key name as the help overlay shows it, e.g. `Ctrl+O`, `F8`
+    pub fn label(&self) -> String {
+        let mut label = String::new();
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                label.push_str(name);
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => label.push_str("Space"),
+            KeyCode::Char(c) if self.modifiers.contains(KeyModifiers::CONTROL) => {
+                label.push(c.to_ascii_uppercase())
+            }
+            KeyCode::Char(c) => label.push(c),
+            KeyCode::F(n) => label.push_str(&format!("F{}", n)),
+            KeyCode::BackTab => label.push_str("Shift+Tab"),
+            KeyCode::PageUp => label.push_str("PgUp"),
+            KeyCode::PageDown => label.push_str("PgDn"),
+            code => label.push_str(&format!("{:?}", code)),
+        }
+        label
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl+"),
+            (KeyModifiers::ALT, "alt+"),
+            (KeyModifiers::SHIFT, "shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            code => {
+                let name = NAMED_KEYS
+                    .iter()
+                    .find(|(_, c)| *c == code)
+                    .map_or("?", |(n, _)| n);
+                f.write_str(name)
+            }
+        }
+    }
+}
+
+impl TryFrom<String> for Key {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, String> {
+        Self::parse(&text)
+    }
+}
+
+impl From<Key> for String {
+    fn from(key: Key) -> Self {
+        key.to_string()
+    }
+}
+
+/// A key, or the prefix key followed by a key (`prefix c`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Binding {
+    pub prefixed: bool,
+    pub key: Key,
+}
+
+impl Binding {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        match text.strip_prefix("prefix ") {
+            Some(key) => Ok(Self {
+                prefixed: true,
+                key: Key::parse(key)?,
+            }),
+            None => Ok(Self {
+                prefixed: false,
+                key: Key::parse(text)?,
+            }),
+        }
+    }
+
+    /// Binding as the help overlay shows it, e.g. `Ctrl+B c`
+    pub fn label(&self, prefix: &Key) -> String {
+        if self.prefixed {
+            format!("{} {}", prefix.label(), self.key.label())
+        } else {
+            self.key.label()
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.prefixed {
+            f.write_str("prefix ")?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+impl TryFrom<String> for Binding {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, String> {
+        Self::parse(&text)
+    }
+}
+
+impl From<Binding> for String {
+    fn from(binding: Binding) -> Self {
+        binding.to_string()
+    }
+}
+
+/// What a binding does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    NewTab,
+    CloseTab,
+    NextTab,
+    PrevTab,
+    KillAgent,
+    Answer,
+    Detach,
+    ScrollUp,
+    ScrollDown,
+    CopyMode,
+    Sidebar,
+    SplitColumns,
+    SplitRows,
+    NextLayout,
+    NextPane,
+    PrevPane,
+    Unsplit,
+    Overview,
+    NextAttention,
+    Help,
+}
+
+impl Action {
+    /// Description for the help overlay
+    pub fn describe(self) -> &'static str {
+        match self {
+            Action::NewTab => "New tab (plan/prompt picker)",
+            Action::CloseTab => "Close active tab",
+            Action::NextTab => "Next tab",
+            Action::PrevTab => "Previous tab",
+            Action::KillAgent => "Kill active tab's agent",
+            Action::Answer => "Answer active tab's question",
+            Action::Detach => "Detach (tabs keep running)",
+            Action::ScrollUp => "Scroll back a page",
+            Action::ScrollDown => "Scroll forward a page",
+            Action::CopyMode => "Copy mode",
+            Action::Sidebar => "Toggle progress sidebar",
+            Action::SplitColumns => "Show another tab side by side",
+            Action::SplitRows => "Show another tab below",
+            Action::NextLayout => "Next pane layout (columns, rows, grid)",
+            Action::NextPane => "Focus next pane",
+            Action::PrevPane => "Focus previous pane",
+            Action::Unsplit => "Show only the focused pane",
+            Action::Overview => "Overview of every tab",
+            Action::NextAttention => "Next tab needing attention",
+            Action::Help => "Show this help",
+        }
+    }
+}
+
+/// The TUI's keybindings; see the module docs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keymap {
+    /// Key that starts a prefixed binding
+    pub prefix: Key,
+    /// The n-th binding switches to tab n
+    pub switch_tab: Vec<Binding>,
+    pub new_tab: Vec<Binding>,
+    pub close_tab: Vec<Binding>,
+    pub next_tab: Vec<Binding>,
+    pub prev_tab: Vec<Binding>,
+    pub kill_agent: Vec<Binding>,
+    pub answer: Vec<Binding>,
+    pub detach: Vec<Binding>,
+    pub scroll_up: Vec<Binding>,
+    pub scroll_down: Vec<Binding>,
+    pub copy_mode: Vec<Binding>,
+    pub sidebar: Vec<Binding>,
+    pub split_columns: Vec<Binding>,
+    pub split_rows: Vec<Binding>,
+    pub next_layout: Vec<Binding>,
+    pub next_pane: Vec<Binding>,
+    pub prev_pane: Vec<Binding>,
+    pub unsplit: Vec<Binding>,
+    pub overview: Vec<Binding>,
+    pub next_attention: Vec<Binding>,
+    pub help: Vec<Binding>,
+}
+
+fn bindings(texts: &[&str]) -> Vec<Binding> {
+    texts
+        .iter()
+        .map(|text| Binding::parse(text).expect("default binding"))
+        .collect()
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            prefix: Key::new(KeyCode::Char('b'), KeyModifiers::CONTROL),
+            switch_tab: bindings(&["f1", "f2", "f3", "f4", "f5", "f6", "f7"]),
+            new_tab: bindings(&["ctrl+o", "prefix c"]),
+            close_tab: bindings(&["f8", "prefix x"]),
+            next_tab: bindings(&["ctrl+tab", "prefix n"]),
+            prev_tab: bindings(&["ctrl+shift+tab", "prefix p"]),
+            kill_agent: bindings(&["ctrl+c"]),
+            answer: bindings(&["prefix q"]),
+            detach: bindings(&["f9", "prefix d"]),
+            scroll_up: bindings(&["shift+pageup", "prefix pageup"]),
+            scroll_down: bindings(&["shift+pagedown", "prefix pagedown"]),
+            copy_mode: bindings(&["f10", "prefix ["]),
+            sidebar: bindings(&["f12", "prefix s"]),
+            split_columns: bindings(&["prefix %"]),
+            split_rows: bindings(&["prefix \""]),
+            next_layout: bindings(&["prefix space"]),
+            next_pane: bindings(&["prefix o"]),
+            prev_pane: bindings(&["prefix O"]),
+            unsplit: bindings(&["prefix z"]),
+            overview: bindings(&["prefix w"]),
+            next_attention: bindings(&["prefix a"]),
+            help: bindings(&["prefix ?"]),
+        }
+    }
+}
+
+impl Keymap {
+    /// Every action with its bindings, in help overlay order
+    pub fn actions(&self) -> [(Action, &[Binding]); 20] {
+        [
+            (Action::NewTab, &self.new_tab),
+            (Action::CloseTab, &self.close_tab),
+            (Action::NextTab, &self.next_tab),
+            (Action::PrevTab, &self.prev_tab),
+            (Action::KillAgent, &self.kill_agent),
+            (Action::Answer, &self.answer),
+            (Action::Detach, &self.detach),
+            (Action::ScrollUp, &self.scroll_up),
+            (Action::ScrollDown, &self.scroll_down),
+            (Action::CopyMode, &self.copy_mode),
+            (Action::Sidebar, &self.sidebar),
+            (Action::SplitColumns, &self.split_columns),
+            (Action::SplitRows, &self.split_rows),
+            (Action::NextLayout, &self.next_layout),
+            (Action::NextPane, &self.next_pane),
+            (Action::PrevPane, &self.prev_pane),
+            (Action::Unsplit, &self.unsplit),
+            (Action::Overview, &self.overview),
+            (Action::NextAttention, &self.next_attention),
+            (Action::Help, &self.help),
+        ]
+    }
+
+    /// The action bound to `event`, pressed after the prefix or not
+    pub fn action(&self, prefixed: bool, event: &KeyEvent) -> Option<Action> {
+        self.actions()
+            .into_iter()
+            .find(|(_, bindings)| matches(bindings, prefixed, event))
+            .map(|(action, _)| action)
+    }
+
+    /// The tab number (from 1) `event` switches to, pressed after the prefix
+    /// or not
+    pub fn tab(&self, prefixed: bool, event: &KeyEvent) -> Option<usize> {
+        if let Some(i) = self
+            .switch_tab
+            .iter()
+            .position(|b| b.prefixed == prefixed && b.key.matches(event))
+        {
+            return Some(i + 1);
+        }
+        match event.code {
+            KeyCode::Char(c @ '1'..='9') if prefixed => c.to_digit(10).map(|n| n as usize),
+            _ => None,
+        }
+    }
+}
+
+fn matches(bindings: &[Binding], prefixed: bool, event: &KeyEvent) -> bool {
+    bindings
+        .iter()
+        .any(|b| b.prefixed == prefixed && b.key.matches(event))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_parse_and_match_keys() {
+        let key = Key::parse("Ctrl+B").unwrap();
+        assert!(key.matches(&event(KeyCode::Char('b'), KeyModifiers::CONTROL)));
+        assert!(key.matches(&event(
+            KeyCode::Char('B'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT
+        )));
+        assert!(!key.matches(&event(KeyCode::Char('b'), KeyModifiers::NONE)));
+        assert_eq!(key.label(), "Ctrl+B");
+        assert_eq!(key.to_string(), "ctrl+b");
+
+        // Terminals report `?` with Shift, and Ctrl+Shift+Tab as BackTab
+        assert!(
+            Key::parse("?")
+                .unwrap()
+                .matches(&event(KeyCode::Char('?'), KeyModifiers::SHIFT))
+        );
+        let back = Key::parse("ctrl+shift+tab").unwrap();
+        assert!(back.matches(&event(
+            KeyCode::BackTab,
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT
+        )));
+        assert!(back.matches(&event(
+            KeyCode::Tab,
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT
+        )));
+        assert_eq!(Key::parse("ctrl++").unwrap().to_string(), "ctrl++");
+        assert!(
+            Key::parse("B")
+                .unwrap()
+                .matches(&event(KeyCode::Char('B'), KeyModifiers::SHIFT))
+        );
+
+        for bad in ["", "f13", "hyper+x", "ctrl+pgup"] {
+            assert!(Key::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_keymap_actions() {
+        let keymap = Keymap::default();
+        let f8 = event(KeyCode::F(8), KeyModifiers::NONE);
+        let x = event(KeyCode::Char('x'), KeyModifiers::NONE);
+        assert_eq!(keymap.action(false, &f8), Some(Action::CloseTab));
+        assert_eq!(keymap.action(false, &x), None);
+        assert_eq!(keymap.action(true, &x), Some(Action::CloseTab));
+        assert_eq!(
+            keymap.tab(false, &event(KeyCode::F(3), KeyModifiers::NONE)),
+            Some(3)
+        );
+        assert_eq!(
+            keymap.tab(true, &event(KeyCode::Char('9'), KeyModifiers::NONE)),
+            Some(9)
+        );
+        assert_eq!(
+            keymap.tab(false, &event(KeyCode::Char('9'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(keymap.new_tab[1].label(&keymap.prefix), "Ctrl+B c");
+        assert_eq!(
+            keymap.action(true, &event(KeyCode::Char('O'), KeyModifiers::SHIFT)),
+            Some(Action::PrevPane)
+        );
+        assert_eq!(
+            keymap.action(true, &event(KeyCode::Char(' '), KeyModifiers::NONE)),
+            Some(Action::NextLayout)
+        );
+    }
+
+    #[test]
+    fn test_keymap_from_toml() {
+        let keymap: Keymap = toml::from_str(
+            r#"
+            prefix = "ctrl+a"
+            close_tab = ["prefix k", "alt+w"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(keymap.prefix, Key::parse("ctrl+a").unwrap());
+        assert_eq!(keymap.close_tab[1], Binding::parse("alt+w").unwrap());
+        // Unset actions keep their defaults
+        assert_eq!(keymap.detach, Keymap::default().detach);
+
+        // Round trip, as the config travels to the daemon
+        let json = serde_json::to_string(&keymap).unwrap();
+        assert_eq!(serde_json::from_str::<Keymap>(&json).unwrap(), keymap);
+
+        let error = toml::from_str::<Keymap>("detach = [\"ctrl+pgup\"]").unwrap_err();
+        assert!(error.to_string().contains("invalid key \"ctrl+pgup\""));
+    }
+}
@@ -1,25 +1,76 @@
 //! TUI mode - Multi-tab interface for running parallel Claude PTY instances
 //!
-//! Entry point via `hydra tui [PLAN]`. Uses ratatui for rendering with crossterm backend.
+//! Entry point via `hydra tui [PLAN...]`, one tab per plan. Each tab runs the iteration loop,
+//! respawning Claude after every completed task. The tabs live in a daemon
+//! (`hydra daemon`) that `hydra tui` attaches to, so they survive detaching.
+//! Uses ratatui for rendering with crossterm backend.
 
+mod activity;
 mod app;
+mod copy;
+mod daemon;
+mod driver;
 mod input;
+mod keymap;
+mod pane;
+mod picker;
+mod protocol;
+mod sidebar;
+mod tab;
 mod ui;
 
 use crate::config::Config;
 use crate::error::{HydraError, Result};
+use crate::harness::Harness;
 use crate::prompt::ResolvedPrompt;
+pub use activity::Notify;
 use app::App;
-use crossterm::event::{self, Event};
+use crossterm::event::{self, Event, MouseEventKind};
 use crossterm::terminal::{self, disable_raw_mode, enable_raw_mode};
+pub use daemon::{ls_command, run_daemon};
+pub use driver::TabSession;
 use input::{KeyAction, handle_key_event};
+pub use keymap::Keymap;
+use protocol::Connection;
 use ratatui::Terminal;
 use ratatui::backend::CrosstermBackend;
 use std::io::{self, Write};
 use std::time::Duration;
 
-/// Run the TUI mode with the given configuration and optional resolved prompt
-pub fn run_tui(config: Config, prompt: ResolvedPrompt) -> Result<()> {
+/// Lines one mouse wheel step scrolls
+const MOUSE_SCROLL_LINES: usize = 3;
+
+/// Run the TUI mode, attaching to this directory's daemon and opening a tab
+/// per session. A daemon is started when none is running, with a tab without
+/// a plan if there are no sessions. `prompt` (not yet composed),
+/// `instructions` and `harness` are what tabs opened from the picker start
+/// from; `lint` checks the plans picked (not `--no-lint`).
+pub fn run_tui(
+    config: Config,
+    prompt: ResolvedPrompt,
+    instructions: String,
+    harness: Harness,
+    lint: bool,
+    mut sessions: Vec<TabSession>,
+) -> Result<()> {
+    let stream = match daemon::connect() {
+        Ok(stream) => stream,
+        Err(_) => {
+            if sessions.is_empty() {
+                sessions.push(TabSession::prepare(
+                    &config,
+                    harness,
+                    prompt.clone(),
+                    &instructions,
+                    None,
+                )?);
+            }
+            daemon::spawn()?
+        }
+    };
+    let connection =
+        Connection::new(stream).map_err(|e| HydraError::io("connecting to hydra daemon", e))?;
+
     // Setup terminal
     enable_raw_mode()
         .map_err(|e| HydraError::io("enabling raw mode", io::Error::other(e.to_string())))?;
@@ -40,10 +91,21 @@ pub fn run_tui(config: Config, prompt: ResolvedPrompt) -> Result<()> {
     let term_size = terminal
         .size()
         .map_err(|e| HydraError::io("getting terminal size", io::Error::other(e.to_string())))?;
-    let (rows, cols) = calculate_content_area(term_size.width, term_size.height);
-
-    // Create application state and spawn initial tab with correct dimensions
-    let mut app = App::new(config, prompt, rows, cols)?;
+    let content_size = calculate_content_area(term_size.width, term_size.height, false);
+
+    // Attach with correct dimensions and open the sessions' tabs
+    let mut app = App::new(
+        config,
+        prompt,
+        instructions,
+        harness,
+        lint,
+        connection,
+        content_size,
+    );
+    for session in sessions {
+        app.new_tab(session);
+    }
 
     // Main event loop
     let result = run_event_loop(&mut terminal, &mut app);
@@ -54,15 +116,19 @@ pub fn run_tui(config: Config, prompt: ResolvedPrompt) -> Result<()> {
     result
 }
 
-/// Calculate the content area dimensions (inside the content block borders)
-fn calculate_content_area(terminal_width: u16, terminal_height: u16) -> (u16, u16) {
+/// Calculate the content area dimensions (inside the content block borders),
+/// next to the sidebar when it's shown
+fn calculate_content_area(terminal_width: u16, terminal_height: u16, sidebar: bool) -> (u16, u16) {
     // Layout: tab bar (3 lines including borders) + content (remaining)
     let content_height = terminal_height.saturating_sub(3); // Subtract tab bar height
 
     // Content area is inside a Block with borders, so subtract 2 for top/bottom borders
     let inner_height = content_height.saturating_sub(2);
     // Subtract 2 for left/right borders
-    let inner_width = terminal_width.saturating_sub(2);
+    let mut inner_width = terminal_width.saturating_sub(2);
+    if sidebar {
+        inner_width = inner_width.saturating_sub(sidebar::sidebar_width(terminal_width));
+    }
 
     (inner_height.max(1), inner_width.max(1))
 }
@@ -82,7 +148,8 @@ fn run_event_loop(
         let term_size = terminal.size().map_err(|e| {
             HydraError::io("getting terminal size", io::Error::other(e.to_string()))
         })?;
-        let content_size = calculate_content_area(term_size.width, term_size.height);
+        let content_size =
+            calculate_content_area(term_size.width, term_size.height, app.sidebar.is_some());
 
         // Resize vt100 parsers if content area changed
         if prev_content_size != Some(content_size) {
@@ -91,6 +158,7 @@ fn run_event_loop(
         }
 
         // Render current state
+        app.refresh_sidebar();
         terminal
             .draw(|frame| ui::render(frame, app))
             .map_err(|e| HydraError::io("drawing frame", io::Error::other(e.to_string())))?;
@@ -108,26 +176,30 @@ fn run_event_loop(
                         KeyAction::Exit => return Ok(()),
                         KeyAction::Continue => {}
                     }
-
-                    // Check if all tabs are closed
-                    if app.is_empty() {
-                        return Ok(());
-                    }
                 }
                 Event::Resize(width, height) => {
                     // Terminal was resized - update content area size
-                    let new_content_size = calculate_content_area(width, height);
+                    let new_content_size =
+                        calculate_content_area(width, height, app.sidebar.is_some());
                     if prev_content_size != Some(new_content_size) {
                         app.resize_all(new_content_size.0, new_content_size.1);
                         prev_content_size = Some(new_content_size);
                     }
                 }
-                _ => {} // Ignore other events (mouse, focus, paste)
+                Event::Mouse(mouse) => match mouse.kind {
+                    MouseEventKind::ScrollUp => app.scroll_up(MOUSE_SCROLL_LINES),
+                    MouseEventKind::ScrollDown => app.scroll_down(MOUSE_SCROLL_LINES),
+                    _ => {}
+                },
+                _ => {} // Ignore other events (focus, paste)
             }
         }
 
-        // Poll all PTYs for output
-        app.poll_pty_output()?;
+        // Take in the daemon's tab updates and output
+        app.poll()?;
+        if app.all_closed() {
+            return Ok(());
+        }
     }
 }
 
@@ -0,0 +1,164 @@
+//! Split panes and the tab overview for TUI mode
+//!
+//! While split, two to four tabs are shown at once, side by side, stacked or
+//! in a grid, each with its PTY sized to its pane. The focused pane shows the
+//! active tab and takes the keyboard. The overview shows every tab as a
+//! thumbnail: its status and the last lines of its screen.
+
+use ratatui::layout::{Constraint, Layout, Rect};
+
+/// Most tabs shown at once
+pub const MAX_PANES: usize = 4;
+
+/// How panes share the content area
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arrangement {
+    /// Side by side
+    Columns,
+    /// Stacked
+    Rows,
+    /// Two rows, the top one filled first
+    Grid,
+}
+
+impl Arrangement {
+    /// The arrangement after this one, for cycling through them
+    pub fn next(self) -> Self {
+        match self {
+            Arrangement::Columns => Arrangement::Rows,
+            Arrangement::Rows => Arrangement::Grid,
+            Arrangement::Grid => Arrangement::Columns,
+        }
+    }
+}
+
+/// The tabs shown while split
+#[derive(Debug, Clone, PartialEq)]
+pub struct Panes {
+    pub arrangement: Arrangement,
+    /// Keys of the tabs shown, in pane order
+    pub keys: Vec<u64>,
+}
+
+impl Panes {
+    /// Each pane's area (borders included) within `area`, in pane order
+    pub fn areas(&self, area: Rect) -> Vec<Rect> {
+        let count = self.keys.len();
+        match self.arrangement {
+            Arrangement::Columns => split(Layout::horizontal(ratios(count)), area),
+            Arrangement::Rows => split(Layout::vertical(ratios(count)), area),
+            Arrangement::Grid => grid(count, count.div_ceil(2), area),
+        }
+    }
+}
+
+/// `count` equal shares
+fn ratios(count: usize) -> Vec<Constraint> {
+    vec![Constraint::Ratio(1, count.max(1) as u32); count]
+}
+
+fn split(layout: Layout, area: Rect) -> Vec<Rect> {
+    layout.split(area).to_vec()
+}
+
+/// `count` cells in rows of `columns`, left to right and top to bottom; a
+/// short last row is spread across the width
+pub fn grid(count: usize, columns: usize, area: Rect) -> Vec<Rect> {
+    if count == 0 {
+        return Vec::new();
+    }
+    let columns = columns.clamp(1, count);
+    let rows = count.div_ceil(columns);
+    split(Layout::vertical(ratios(rows)), area)
+        .into_iter()
+        .enumerate()
+        .flat_map(|(row, line)| {
+            let cells = columns.min(count - row * columns);
+            split(Layout::horizontal(ratios(cells)), line)
+        })
+        .collect()
+}
+
+/// Columns of the overview grid for `count` tabs: about as many as rows
+pub fn overview_columns(count: usize) -> usize {
+    let mut columns = 1;
+    while columns * columns < count {
+        columns += 1;
+    }
+    columns
+}
+
+/// The last `height` lines of a screen up to its cursor or last text,
+/// whichever is lower, cut to `width` columns, for a thumbnail
+pub fn thumbnail(screen: &vt100::Screen, height: usize, width: u16) -> Vec<String> {
+    let lines: Vec<String> = screen.rows(0, width).collect();
+    let cursor = usize::from(screen.cursor_position().0) + 1;
+    let text = lines
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(0, |last| last + 1);
+    let end = cursor.max(text).min(lines.len());
+    lines[end.saturating_sub(height)..end].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pane_areas() {
+        let area = Rect::new(0, 3, 100, 40);
+        let mut panes = Panes {
+            arrangement: Arrangement::Columns,
+            keys: vec![1, 2],
+        };
+        assert_eq!(
+            panes.areas(area),
+            [Rect::new(0, 3, 50, 40), Rect::new(50, 3, 50, 40)]
+        );
+
+        panes.arrangement = Arrangement::Rows;
+        assert_eq!(
+            panes.areas(area),
+            [Rect::new(0, 3, 100, 20), Rect::new(0, 23, 100, 20)]
+        );
+
+        // Three in a grid: two on top, one across the bottom
+        panes.arrangement = Arrangement::Grid;
+        panes.keys.push(3);
+        assert_eq!(
+            panes.areas(area),
+            [
+                Rect::new(0, 3, 50, 20),
+                Rect::new(50, 3, 50, 20),
+                Rect::new(0, 23, 100, 20)
+            ]
+        );
+        assert_eq!(Arrangement::Grid.next(), Arrangement::Columns);
+    }
+
+    #[test]
+    fn test_overview_grid() {
+        assert_eq!(overview_columns(1), 1);
+        assert_eq!(overview_columns(4), 2);
+        assert_eq!(overview_columns(5), 3);
+        let cells = grid(5, overview_columns(5), Rect::new(0, 0, 90, 20));
+        assert_eq!(cells.len(), 5);
+        assert_eq!(cells[2], Rect::new(60, 0, 30, 10));
+        assert_eq!(cells[4], Rect::new(45, 10, 45, 10));
+        assert!(grid(0, 1, Rect::new(0, 0, 10, 10)).is_empty());
+    }
+
+    #[test]
+    fn test_thumbnail_ends_at_cursor_or_text() {
+        let mut parser = vt100::Parser::new(6, 20, 0);
+        parser.process(b"one\r\ntwo\r\nthree four");
+        let screen = parser.screen();
+        assert_eq!(thumbnail(screen, 2, 5), ["two", "three"]);
+        assert_eq!(thumbnail(screen, 10, 20), ["one", "two", "three four"]);
+
+        // The cursor below the text keeps the blank lines above it
+        parser.process(b"\x1b[5;1H");
+        assert_eq!(thumbnail(parser.screen(), 3, 20), ["three four", "", ""]);
+    }
+}
@@ -0,0 +1,158 @@
+//! New-tab picker (Ctrl+O)
+//!
+//! Lists the plans in `plans/` with their progress, plus "no plan", the
+//! prompt files hydra knows about and the harnesses. The chosen plan, prompt
+//! and harness become the new tab's session, with its own scratchpad.
+
+use crate::config::Config;
+use crate::harness::Harness;
+use crate::plan::{self, Plan};
+use crate::prompt::{PromptSource, ResolvedPrompt};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A plan the picker offers
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanChoice {
+    /// None for a tab without a plan
+    pub path: Option<PathBuf>,
+    pub label: String,
+}
+
+/// Picker state while the overlay is open
+#[derive(Debug)]
+pub struct Picker {
+    pub plans: Vec<PlanChoice>,
+    /// Prompt files, the session's prompt first
+    pub prompts: Vec<ResolvedPrompt>,
+    pub selected_plan: usize,
+    pub selected_prompt: usize,
+    pub harness: Harness,
+    /// Why the last choice couldn't be opened
+    pub error: Option<String>,
+}
+
+impl Picker {
+    /// Offer the plans in `plans_dir` and the prompt files that exist,
+    /// starting with `default_prompt` and `harness`
+    pub fn load(plans_dir: &Path, default_prompt: &ResolvedPrompt, harness: Harness) -> Self {
+        let mut plans = vec![PlanChoice {
+            path: None,
+            label: "(no plan)".to_string(),
+        }];
+        plans.extend(plan::discover_plans(plans_dir).into_iter().map(|path| {
+            let label = match Plan::load(&path) {
+                Ok(p) => format!(
+                    "{}  {}/{} done",
+                    path.display(),
+                    p.checked_count(),
+                    p.checked_count() + p.unchecked_count()
+                ),
+                Err(_) => path.display().to_string(),
+            };
+            PlanChoice {
+                path: Some(path),
+                label,
+            }
+        }));
+
+        let mut prompts = vec![default_prompt.clone()];
+        for (path, source) in [
+            (Config::local_prompt_path(), PromptSource::ProjectHydra),
+            (PathBuf::from("prompt.md"), PromptSource::CurrentDir),
+            (
+                Config::global_default_prompt_path(),
+                PromptSource::GlobalDefault,
+            ),
+        ] {
+            if prompts.iter().any(|p| p.path == path) {
+                continue;
+            }
+            if let Ok(content) = fs::read_to_string(&path) {
+                prompts.push(ResolvedPrompt {
+                    path,
+                    content,
+                    source,
+                });
+            }
+        }
+
+        Self {
+            // Preselect the first plan rather than "no plan" when there is one
+            selected_plan: plans.len().min(2) - 1,
+            plans,
+            prompts,
+            selected_prompt: 0,
+            harness,
+            error: None,
+        }
+    }
+
+    pub fn up(&mut self) {
+        self.selected_plan = self.selected_plan.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.selected_plan + 1 < self.plans.len() {
+            self.selected_plan += 1;
+        }
+    }
+
+    /// Cycle through the prompt files
+    pub fn next_prompt(&mut self) {
+        self.selected_prompt = (self.selected_prompt + 1) % self.prompts.len();
+    }
+
+    /// Cycle through the harnesses
+    pub fn next_harness(&mut self) {
+        self.harness = self.harness.next();
+    }
+
+    pub fn plan(&self) -> Option<&Path> {
+        self.plans[self.selected_plan].path.as_deref()
+    }
+
+    pub fn prompt(&self) -> &ResolvedPrompt {
+        &self.prompts[self.selected_prompt]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_picker_lists_plans_with_progress() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.md"), "- [x] one\n- [ ] two\n").unwrap();
+        fs::write(dir.path().join("b.md"), "- [ ] one\n").unwrap();
+        let prompt = ResolvedPrompt {
+            path: PathBuf::from("custom-prompt.md"),
+            content: String::new(),
+            source: PromptSource::CliOverride,
+        };
+
+        let mut picker = Picker::load(dir.path(), &prompt, Harness::Claude);
+        assert_eq!(picker.plans.len(), 3);
+        assert_eq!(picker.plans[0].label, "(no plan)");
+        assert!(picker.plans[1].label.ends_with("a.md  1/2 done"));
+        assert!(picker.plans[2].label.ends_with("b.md  0/1 done"));
+        assert_eq!(picker.plan(), Some(dir.path().join("a.md").as_path()));
+        assert_eq!(picker.prompt().path, Path::new("custom-prompt.md"));
+        picker.next_harness();
+        assert_eq!(picker.harness, Harness::Pi);
+
+        picker.down();
+        picker.down();
+        assert!(picker.plan().unwrap().ends_with("b.md"));
+        picker.up();
+        picker.up();
+        picker.up();
+        assert_eq!(picker.plan(), None);
+
+        let empty = Picker::load(&dir.path().join("missing"), &prompt, Harness::Claude);
+        assert_eq!(empty.plans.len(), 1);
+        assert_eq!(empty.plan(), None);
+    }
+}
@@ -0,0 +1,299 @@
+//! Wire protocol between `hydra tui` and `hydra daemon`
+//!
+//! The daemon listens on `.hydra/daemon.sock`. Both directions carry frames
+//! of a kind byte, a big-endian `u32` length and the payload: JSON for
+//! requests and events, raw bytes for terminal data (a tab's PTY output one
+//! way, keyboard input the other), prefixed by the tab's key. A client keeps
+//! its own vt100 parser per tab and feeds it exactly what the daemon's parser
+//! was fed, starting with the replay sent on attach.
+
+use crate::config::Config;
+use crate::harness::Harness;
+use crate::tui::driver::TabSession;
+use crate::tui::tab::TabStatus;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{self, ErrorKind, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+
+const FRAME_MESSAGE: u8 = 0;
+const FRAME_DATA: u8 = 1;
+
+/// Client to daemon
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Request {
+    /// Receive every tab's screen and then its output; tabs take this
+    /// content size
+    Attach { rows: u16, cols: u16 },
+    /// The client's content area or panes changed size: tabs in `panes`
+    /// take their pane's size, the others `rows` x `cols`. Tabs follow the
+    /// client that resized last.
+    Resize {
+        rows: u16,
+        cols: u16,
+        panes: Vec<PaneSize>,
+    },
+    /// Open a tab running `session` with `config`
+    OpenTab {
+        config: Box<Config>,
+        session: Box<TabSession>,
+    },
+    /// Close a tab, killing its agent
+    CloseTab { key: u64 },
+    /// Kill a tab's agent and end its loop (Ctrl+C)
+    KillTab { key: u64 },
+    /// Answer the question a tab's loop is paused on and resume it; `None`
+    /// leaves the decision to the agent
+    Answer { key: u64, answer: Option<String> },
+    /// Reply with a `Listing` (`hydra ls`)
+    List,
+}
+
+/// Daemon to client
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Event {
+    /// The tab's screen is now this size; a replay of it follows, to be
+    /// fed to a fresh parser. Sent for every tab on attach and for a tab
+    /// whose size changes.
+    Resized { key: u64, rows: u16, cols: u16 },
+    /// Every tab, in tab bar order; sent on attach and whenever one changes
+    Tabs(Vec<TabInfo>),
+    /// The tab this client asked for is open
+    Opened { key: u64 },
+    /// A request failed
+    Error(String),
+    /// Reply to `List`
+    Listing {
+        pid: u32,
+        clients: usize,
+        tabs: Vec<TabInfo>,
+    },
+}
+
+/// The content size of a pane showing the tab with `key`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaneSize {
+    pub key: u64,
+    pub rows: u16,
+    pub cols: u16,
+}
+
+/// What a client shows about a tab besides its screen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TabInfo {
+    /// Stable for the tab's lifetime, unlike its position
+    pub key: u64,
+    pub status: TabStatus,
+    pub harness: Harness,
+    pub plan_path: Option<PathBuf>,
+    pub scratchpad_path: Option<PathBuf>,
+    pub iteration: u32,
+    pub max_iterations: u32,
+    /// Seconds into the current iteration
+    pub elapsed_seconds: u64,
+    pub timeout_seconds: u64,
+    /// Size of the tab's screen
+    pub rows: u16,
+    pub cols: u16,
+    /// Seconds since the tab last had output, while running
+    pub idle_seconds: u64,
+    /// The screen has looked like a prompt for a while
+    pub waiting: bool,
+    /// Agents that exited with an error or failed to start so far, and how
+    /// the last one did (`exited with code 1`)
+    pub failed_exits: u32,
+    pub exit_error: Option<String>,
+    /// The question the tab waits on an answer to
+    pub question: Option<String>,
+}
+
+/// A decoded frame
+#[derive(Debug)]
+pub enum Frame<T> {
+    Message(T),
+    /// Terminal data for the tab with `key`
+    Data {
+        key: u64,
+        data: Vec<u8>,
+    },
+}
+
+fn frame(kind: u8, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(payload.len() + 5);
+    bytes.push(kind);
+    bytes.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+/// Encode a request or event
+pub fn encode_message<T: Serialize>(message: &T) -> Vec<u8> {
+    // Our own types always serialize
+    let json = serde_json::to_vec(message).unwrap_or_default();
+    frame(FRAME_MESSAGE, &json)
+}
+
+/// Encode terminal data for the tab with `key`
+pub fn encode_data(key: u64, data: &[u8]) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(data.len() + 8);
+    payload.extend_from_slice(&key.to_be_bytes());
+    payload.extend_from_slice(data);
+    frame(FRAME_DATA, &payload)
+}
+
+/// Read one frame; `None` when the peer closed the connection
+pub fn read_frame<T: DeserializeOwned>(reader: &mut impl Read) -> io::Result<Option<Frame<T>>> {
+    let mut header = [0u8; 5];
+    match reader.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload)?;
+
+    match header[0] {
+        FRAME_MESSAGE => serde_json::from_slice(&payload)
+            .map(|message| Some(Frame::Message(message)))
+            .map_err(|e| io::Error::new(ErrorKind::InvalidData, e)),
+        FRAME_DATA if payload.len() >= 8 => {
+            let mut key = [0u8; 8];
+            key.copy_from_slice(&payload[..8]);
+            Ok(Some(Frame::Data {
+                key: u64::from_be_bytes(key),
+                data: payload[8..].to_vec(),
+            }))
+        }
+        kind => Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!("unexpected frame kind {}", kind),
+        )),
+    }
+}
+
+/// One end of a daemon connection. A reader thread decodes incoming frames
+/// and a writer thread sends outgoing ones, so neither side blocks on a slow
+/// peer.
+pub struct Connection<T> {
+    incoming: Receiver<Frame<T>>,
+    outgoing: Sender<Vec<u8>>,
+}
+
+impl<T: DeserializeOwned + Send + 'static> Connection<T> {
+    pub fn new(stream: UnixStream) -> io::Result<Self> {
+        let mut reader = stream.try_clone()?;
+        let mut writer = stream;
+
+        let (in_tx, incoming) = mpsc::channel();
+        thread::spawn(move || {
+            while let Ok(Some(frame)) = read_frame(&mut reader) {
+                if in_tx.send(frame).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let (outgoing, out_rx) = mpsc::channel::<Vec<u8>>();
+        thread::spawn(move || {
+            for bytes in out_rx {
+                if writer.write_all(&bytes).is_err() {
+                    break;
+                }
+            }
+            let _ = writer.shutdown(std::net::Shutdown::Both);
+        });
+
+        Ok(Self { incoming, outgoing })
+    }
+
+    /// Queue an encoded frame. Fails once the peer is gone.
+    pub fn send(&self, frame: Vec<u8>) -> io::Result<()> {
+        self.outgoing
+            .send(frame)
+            .map_err(|_| io::Error::new(ErrorKind::BrokenPipe, "connection closed"))
+    }
+
+    /// The next frame received, if any. Fails once the peer is gone and
+    /// everything it sent has been read.
+    pub fn try_recv(&self) -> io::Result<Option<Frame<T>>> {
+        match self.incoming.try_recv() {
+            Ok(frame) => Ok(Some(frame)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => {
+                Err(io::Error::new(ErrorKind::BrokenPipe, "connection closed"))
+            }
+        }
+    }
+
+    /// Wait for the next frame
+    pub fn recv(&self) -> io::Result<Frame<T>> {
+        self.incoming
+            .recv()
+            .map_err(|_| io::Error::new(ErrorKind::BrokenPipe, "connection closed"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_round_trip() {
+        let pane = PaneSize {
+            key: 3,
+            rows: 24,
+            cols: 39,
+        };
+        let mut bytes = encode_message(&Request::Resize {
+            rows: 24,
+            cols: 80,
+            panes: vec![pane.clone()],
+        });
+        bytes.extend(encode_data(7, b"\x1b[1mhi"));
+        let mut reader = bytes.as_slice();
+
+        match read_frame::<Request>(&mut reader).unwrap() {
+            Some(Frame::Message(Request::Resize {
+                rows: 24,
+                cols: 80,
+                panes,
+            })) => assert_eq!(panes, [pane]),
+            other => panic!("unexpected frame: {:?}", other),
+        }
+        match read_frame::<Request>(&mut reader).unwrap() {
+            Some(Frame::Data { key: 7, data }) => assert_eq!(data, b"\x1b[1mhi"),
+            other => panic!("unexpected frame: {:?}", other),
+        }
+        assert!(read_frame::<Request>(&mut reader).unwrap().is_none());
+
+        let mut bad = frame(9, b"");
+        assert!(read_frame::<Request>(&mut bad.as_slice()).is_err());
+        bad = frame(FRAME_MESSAGE, b"{");
+        assert!(read_frame::<Request>(&mut bad.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_connection_over_socket() {
+        let (a, b) = UnixStream::pair().unwrap();
+        let client: Connection<Event> = Connection::new(a).unwrap();
+        let daemon: Connection<Request> = Connection::new(b).unwrap();
+
+        client.send(encode_message(&Request::List)).unwrap();
+        assert!(matches!(
+            daemon.recv().unwrap(),
+            Frame::Message(Request::List)
+        ));
+        daemon.send(encode_data(1, b"out")).unwrap();
+        match client.recv().unwrap() {
+            Frame::Data { key: 1, data } => assert_eq!(data, b"out"),
+            other => panic!("unexpected frame: {:?}", other),
+        }
+
+        drop(daemon);
+        assert!(client.recv().is_err());
+    }
+}
@@ -0,0 +1,163 @@
+//! Progress sidebar (F12)
+//!
+//! Shows the active tab's iteration, elapsed time against the iteration
+//! timeout, its plan as a checklist and the last lines of its scratchpad.
+//! The plan and scratchpad are re-read when their modification time changes,
+//! checked at most every `REFRESH_INTERVAL`.
+
+use crate::plan::Plan;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Columns the sidebar takes, borders included
+pub const SIDEBAR_WIDTH: u16 = 40;
+
+/// How often the sidebar looks at the files on disk
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Sidebar width for a terminal `width` columns wide: `SIDEBAR_WIDTH`, but
+/// never more than half the terminal
+pub fn sidebar_width(width: u16) -> u16 {
+    SIDEBAR_WIDTH.min(width / 2)
+}
+
+/// A file re-read when its modification time changes
+#[derive(Debug)]
+pub struct WatchedFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    /// None while the file is missing or unreadable
+    pub content: Option<String>,
+}
+
+impl WatchedFile {
+    pub fn new(path: &Path) -> Self {
+        let mut file = Self {
+            path: path.to_path_buf(),
+            modified: None,
+            content: None,
+        };
+        file.refresh();
+        file
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Re-read the file if it changed on disk. Returns true when it did.
+    pub fn refresh(&mut self) -> bool {
+        let modified = fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+        if modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+        self.content = fs::read_to_string(&self.path).ok();
+        true
+    }
+}
+
+/// What the sidebar shows for the active tab's files
+#[derive(Debug, Default)]
+pub struct Sidebar {
+    pub plan: Option<WatchedFile>,
+    /// Parsed from `plan` whenever it's re-read
+    pub tasks: Option<Plan>,
+    pub scratchpad: Option<WatchedFile>,
+    last_refresh: Option<Instant>,
+}
+
+impl Sidebar {
+    /// Follow the active tab's plan and scratchpad, re-reading them when
+    /// they change (or when the tab switched to other files)
+    pub fn refresh(&mut self, plan: Option<&Path>, scratchpad: Option<&Path>) {
+        let switched = self.plan.as_ref().map(WatchedFile::path) != plan
+            || self.scratchpad.as_ref().map(WatchedFile::path) != scratchpad;
+        if !switched
+            && self
+                .last_refresh
+                .is_some_and(|t| t.elapsed() < REFRESH_INTERVAL)
+        {
+            return;
+        }
+        self.last_refresh = Some(Instant::now());
+
+        if switched {
+            self.plan = plan.map(WatchedFile::new);
+            self.scratchpad = scratchpad.map(WatchedFile::new);
+            self.reparse();
+            return;
+        }
+        if let Some(ref mut scratchpad) = self.scratchpad {
+            scratchpad.refresh();
+        }
+        if self.plan.as_mut().is_some_and(WatchedFile::refresh) {
+            self.reparse();
+        }
+    }
+
+    fn reparse(&mut self) {
+        self.tasks = self
+            .plan
+            .as_ref()
+            .and_then(|p| p.content.as_deref())
+            .map(Plan::parse);
+    }
+
+    /// The last `count` lines of the scratchpad
+    pub fn scratchpad_tail(&self, count: usize) -> Vec<&str> {
+        let Some(content) = self.scratchpad.as_ref().and_then(|s| s.content.as_deref()) else {
+            return Vec::new();
+        };
+        let lines: Vec<&str> = content.trim_end().lines().collect();
+        lines[lines.len().saturating_sub(count)..].to_vec()
+    }
+}
+
+/// `m:ss` for the elapsed-time line
+pub fn clock(seconds: u64) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sidebar_follows_files() {
+        let dir = TempDir::new().unwrap();
+        let plan = dir.path().join("plan.md");
+        let scratchpad = dir.path().join("scratchpad.md");
+        fs::write(&plan, "- [x] one\n- [ ] two\n").unwrap();
+        fs::write(&scratchpad, "a\nb\nc\n").unwrap();
+
+        let mut sidebar = Sidebar::default();
+        sidebar.refresh(Some(&plan), Some(&scratchpad));
+        assert_eq!(sidebar.tasks.as_ref().unwrap().checked_count(), 1);
+        assert_eq!(sidebar.scratchpad_tail(2), ["b", "c"]);
+
+        // A change on disk is picked up once the interval has passed
+        fs::write(&plan, "- [x] one\n- [x] two\n").unwrap();
+        let file = fs::File::options().write(true).open(&plan).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5))
+            .unwrap();
+        sidebar.last_refresh = Some(Instant::now() - REFRESH_INTERVAL);
+        sidebar.refresh(Some(&plan), Some(&scratchpad));
+        assert_eq!(sidebar.tasks.as_ref().unwrap().checked_count(), 2);
+
+        // Another tab's files, or none, are followed at once
+        sidebar.refresh(None, None);
+        assert!(sidebar.tasks.is_none());
+        assert!(sidebar.scratchpad_tail(5).is_empty());
+    }
+
+    #[test]
+    fn test_sidebar_width_and_clock() {
+        assert_eq!(sidebar_width(200), SIDEBAR_WIDTH);
+        assert_eq!(sidebar_width(60), 30);
+        assert_eq!(clock(0), "0:00");
+        assert_eq!(clock(125), "2:05");
+    }
+}
@@ -0,0 +1,646 @@
+//! A TUI tab as the daemon runs it
+//!
+//! Each tab runs the hydra iteration loop with the agent in a PTY, feeding
+//! the output to a vt100 parser that is the tab's screen. Everything the
+//! parser is fed is also kept for the daemon to pass on to attached clients.
+//! The tab also notes what clients show as activity: when it last had output,
+//! whether its screen looks like the agent waits for input, and agents that
+//! exited with an error.
+
+use crate::config::Config;
+use crate::error::{HydraError, Result};
+use crate::harness::Harness;
+use crate::lock::SessionLock;
+use crate::pty::{PtyManager, PtyResult};
+use crate::runner::IterationResult;
+use crate::tui::driver::{LoopDriver, TabSession};
+use crate::tui::protocol::TabInfo;
+use portable_pty::ExitStatus;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tempfile::NamedTempFile;
+
+/// How long a prompt-like screen has to sit without output before the agent
+/// counts as waiting for input
+const WAITING_IDLE: Duration = Duration::from_secs(3);
+
+/// How long output an exited agent wrote is still taken in (it may hold a
+/// stop signal) before the iteration ends
+const EXIT_DRAIN: Duration = Duration::from_millis(250);
+
+/// Screen text, in lowercase, that says an agent is waiting for an answer:
+/// confirmation prompts, numbered choices, Claude's input box
+const PROMPT_PATTERNS: [&str; 9] = [
+    "(y/n)",
+    "[y/n]",
+    "(yes/no)",
+    "press enter",
+    "do you want",
+    "would you like",
+    "❯ 1.",
+    "esc to cancel",
+    "? for shortcuts",
+];
+
+/// Status of a tab's Claude process
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TabStatus {
+    /// An iteration is running
+    Running,
+    /// The last iteration used up the iteration budget
+    MaxIterations,
+    /// Claude completed with AllTasksComplete signal
+    AllComplete,
+    /// Claude stopped to ask a question (see .hydra/questions/); the loop
+    /// resumes once it's answered
+    Question,
+    /// Claude process was stopped/killed
+    Stopped,
+}
+
+impl TabStatus {
+    /// Lowercase description, as `hydra ls` prints it
+    pub fn describe(&self) -> &'static str {
+        match self {
+            TabStatus::Running => "running",
+            TabStatus::MaxIterations => "max iterations reached",
+            TabStatus::AllComplete => "all tasks complete",
+            TabStatus::Question => "question for the operator",
+            TabStatus::Stopped => "stopped",
+        }
+    }
+}
+
+/// A single tab running the iteration loop, one PTY session per iteration
+pub struct Tab {
+    /// Identifies the tab to clients for its lifetime
+    pub key: u64,
+    /// vt100 terminal parser for interpreting escape sequences
+    pub parser: vt100::Parser,
+    /// Bytes fed to the parser since the daemon last passed them on
+    outbox: Vec<u8>,
+    /// Current status of the tab
+    pub status: TabStatus,
+    /// Iteration counter, timeout and session log for this tab
+    pub driver: LoopDriver,
+    /// Keeps other sessions off the tab's plan and scratchpad
+    _lock: SessionLock,
+    /// Combined prompt of the running iteration, kept until the next one
+    _prompt_file: Option<NamedTempFile>,
+    /// PTY manager for this tab (None after PTY closes)
+    pty_manager: Option<PtyManager>,
+    /// PTY writer for sending input
+    pty_writer: Option<Box<dyn Write + Send>>,
+    /// Receiver for PTY output from reader thread
+    pty_rx: Option<Receiver<PtyMessage>>,
+    /// Reader thread handle
+    _reader_thread: Option<JoinHandle<()>>,
+    /// Stop flag for this tab's PTY
+    stop_flag: Arc<AtomicBool>,
+    /// When the parser was last fed
+    last_output: Instant,
+    /// The agent's exit status and when it was seen, while its last output
+    /// drains
+    exited: Option<(ExitStatus, Instant)>,
+    /// Agents that exited with an error or failed to start, and how the
+    /// last one did
+    failed_exits: u32,
+    exit_error: Option<String>,
+}
+
+/// Messages from PTY reader thread
+enum PtyMessage {
+    Data(Vec<u8>),
+    Closed,
+}
+
+impl Tab {
+    /// Create a new tab with specified terminal dimensions and start its
+    /// first iteration. Fails with `AlreadyRunning` when another session or
+    /// tab works on the plan.
+    pub fn new(
+        key: u64,
+        config: &Config,
+        session: &TabSession,
+        rows: u16,
+        cols: u16,
+    ) -> Result<Self> {
+        let lock = SessionLock::acquire_for_tab(session.plan_path.as_deref())?;
+        let mut tab = Self {
+            key,
+            // Initialize vt100 parser with same dimensions
+            parser: vt100::Parser::new(rows, cols, config.tui_scrollback),
+            outbox: Vec::new(),
+            status: TabStatus::Running,
+            driver: LoopDriver::new(config.clone(), session.clone()),
+            _lock: lock,
+            _prompt_file: None,
+            pty_manager: None,
+            pty_writer: None,
+            pty_rx: None,
+            _reader_thread: None,
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            last_output: Instant::now(),
+            exited: None,
+            failed_exits: 0,
+            exit_error: None,
+        };
+        tab.spawn_iteration()?;
+        Ok(tab)
+    }
+
+    /// Start the driver's next iteration: spawn Claude in a fresh PTY with
+    /// that iteration's prompt
+    fn spawn_iteration(&mut self) -> Result<()> {
+        let prompt_file = self.driver.start_iteration()?;
+        if self.driver.iteration() > 1 {
+            // Keep the previous iteration's stop signal from being detected
+            // again, while leaving it reachable in scroll mode
+            let (rows, _) = self.parser.screen().size();
+            self.feed(&scrollback_push(rows));
+        }
+        let (rows, cols) = self.parser.screen().size();
+
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        // Use specified size so Claude sees correct terminal dimensions
+        let mut pty_manager = PtyManager::new_with_size(Arc::clone(&stop_flag), rows, cols)?;
+
+        // Spawn the tab's harness in PTY
+        pty_manager.spawn_harness(self.driver.harness(), prompt_file.path())?;
+
+        // Get PTY reader and writer
+        let (pty_reader, pty_writer) = pty_manager.take_reader_writer()?;
+
+        // Spawn reader thread
+        let (tx, rx) = mpsc::channel();
+        let reader_stop_flag = Arc::clone(&stop_flag);
+        let reader_thread = thread::spawn(move || {
+            Self::reader_thread(pty_reader, tx, reader_stop_flag);
+        });
+
+        self._prompt_file = Some(prompt_file);
+        self.pty_manager = Some(pty_manager);
+        self.pty_writer = Some(pty_writer);
+        self.pty_rx = Some(rx);
+        self._reader_thread = Some(reader_thread);
+        self.stop_flag = stop_flag;
+        self.exited = None;
+        self.status = TabStatus::Running;
+        Ok(())
+    }
+
+    /// Feed the parser, keeping the bytes for attached clients
+    fn feed(&mut self, data: &[u8]) {
+        self.parser.process(data);
+        self.outbox.extend_from_slice(data);
+        self.last_output = Instant::now();
+    }
+
+    /// Bytes fed to the parser since the last call
+    pub fn take_output(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    /// What clients show about this tab besides its screen
+    pub fn info(&self) -> TabInfo {
+        let running = self.status == TabStatus::Running;
+        let (rows, cols) = self.size();
+        let idle = if running {
+            self.last_output.elapsed()
+        } else {
+            Duration::ZERO
+        };
+        TabInfo {
+            key: self.key,
+            status: self.status.clone(),
+            harness: self.driver.harness(),
+            plan_path: self.driver.plan_path().map(Path::to_path_buf),
+            scratchpad_path: self.driver.scratchpad_path().map(Path::to_path_buf),
+            iteration: self.driver.iteration(),
+            max_iterations: self.driver.max_iterations(),
+            elapsed_seconds: if running {
+                self.driver.elapsed().as_secs()
+            } else {
+                0
+            },
+            timeout_seconds: self.driver.timeout_seconds(),
+            rows,
+            cols,
+            idle_seconds: idle.as_secs(),
+            waiting: idle >= WAITING_IDLE && looks_like_prompt(&self.parser.screen().contents()),
+            failed_exits: self.failed_exits,
+            exit_error: self.exit_error.clone(),
+            question: self.driver.question().map(str::to_string),
+        }
+    }
+
+    /// PTY reader thread - reads output and sends to main thread
+    fn reader_thread(
+        mut reader: Box<dyn Read + Send>,
+        tx: mpsc::Sender<PtyMessage>,
+        stop_flag: Arc<AtomicBool>,
+    ) {
+        let mut buf = [0u8; 4096];
+
+        loop {
+            if stop_flag.load(Ordering::SeqCst) {
+                break;
+            }
+
+            match reader.read(&mut buf) {
+                Ok(0) => {
+                    let _ = tx.send(PtyMessage::Closed);
+                    break;
+                }
+                Ok(n) => {
+                    if tx.send(PtyMessage::Data(buf[..n].to_vec())).is_err() {
+                        break;
+                    }
+                }
+                Err(_) => {
+                    let _ = tx.send(PtyMessage::Closed);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Poll for PTY output (non-blocking); ends the iteration on a stop
+    /// signal, PTY close or timeout
+    pub fn poll_output(&mut self) -> Result<()> {
+        if self.status != TabStatus::Running {
+            return Ok(());
+        }
+
+        let Some(ref rx) = self.pty_rx else {
+            return Ok(());
+        };
+
+        // Collect messages first to avoid borrow issues
+        let mut messages = Vec::new();
+        loop {
+            match rx.try_recv() {
+                Ok(msg) => messages.push(msg),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    messages.push(PtyMessage::Closed);
+                    break;
+                }
+            }
+        }
+
+        // Process messages
+        let mut result = None;
+        for msg in messages {
+            match msg {
+                PtyMessage::Data(data) => {
+                    // Feed data through vt100 parser to interpret escape sequences
+                    self.feed(&data);
+                    self.driver.record_output(&data);
+
+                    // Check for stop signals
+                    let contents = self.parser.screen().contents();
+                    if let Some(signal) = find_signal(&contents, self.driver.harness()) {
+                        result = Some(match signal {
+                            PtyResult::TaskComplete => IterationResult::TaskComplete,
+                            PtyResult::AllComplete => IterationResult::AllComplete,
+                            PtyResult::Question => IterationResult::Question,
+                            _ => IterationResult::NoSignal,
+                        });
+                        break;
+                    }
+                }
+                PtyMessage::Closed => {
+                    result = Some(IterationResult::NoSignal);
+                    break;
+                }
+            }
+        }
+
+        // The agent exiting ends the iteration once its output is in
+        if self.exited.is_none() {
+            let status = self.pty_manager.as_mut().and_then(PtyManager::try_wait);
+            self.exited = status.map(|status| (status, Instant::now()));
+        }
+        if result.is_none()
+            && self
+                .exited
+                .as_ref()
+                .is_some_and(|(_, at)| at.elapsed() >= EXIT_DRAIN)
+        {
+            result = Some(IterationResult::NoSignal);
+        }
+        if let Some((ref status, _)) = self.exited
+            && !status.success()
+            && result == Some(IterationResult::NoSignal)
+        {
+            self.failed_exits += 1;
+            self.exit_error = Some(status.to_string().to_lowercase());
+        }
+
+        if result.is_none() && self.driver.timed_out() {
+            result = Some(IterationResult::Timeout);
+        }
+        match result {
+            Some(result) => self.end_iteration(result),
+            None => Ok(()),
+        }
+    }
+
+    /// Stop the PTY and either spawn the next iteration or settle the tab's
+    /// final status
+    fn end_iteration(&mut self, result: IterationResult) -> Result<()> {
+        self.stop_pty();
+        if self.driver.finish_iteration(&result) {
+            return self.spawn_iteration();
+        }
+        self.status = match result {
+            IterationResult::AllComplete => TabStatus::AllComplete,
+            IterationResult::Question => TabStatus::Question,
+            IterationResult::Terminated => TabStatus::Stopped,
+            _ => TabStatus::MaxIterations,
+        };
+        Ok(())
+    }
+
+    /// Answer the question the tab's loop is paused on and resume it; `None`
+    /// leaves the decision to the agent
+    pub fn answer(&mut self, answer: Option<&str>) -> Result<()> {
+        if self.status != TabStatus::Question {
+            return Ok(());
+        }
+        if self.driver.answer(answer) {
+            return self.spawn_iteration();
+        }
+        self.status = TabStatus::MaxIterations;
+        Ok(())
+    }
+
+    /// End the tab's loop after an error of its own, such as an agent that
+    /// can't be spawned; the tab stays open showing it
+    pub fn fail(&mut self, error: &HydraError) {
+        self.stop_pty();
+        self.driver.abort(&error.to_string());
+        self.failed_exits += 1;
+        self.exit_error = Some(error.to_string());
+        self.status = TabStatus::Stopped;
+    }
+
+    /// Send input to the PTY
+    pub fn send_input(&mut self, data: &[u8]) -> Result<()> {
+        if let Some(ref mut writer) = self.pty_writer {
+            writer
+                .write_all(data)
+                .map_err(|e| HydraError::io("writing to PTY", e))?;
+            writer
+                .flush()
+                .map_err(|e| HydraError::io("flushing PTY", e))?;
+        }
+        Ok(())
+    }
+
+    /// Stop this tab's Claude process
+    fn stop_pty(&mut self) {
+        self.stop_flag.store(true, Ordering::SeqCst);
+        if let Some(ref pty) = self.pty_manager {
+            pty.terminate_child();
+        }
+        self.pty_writer = None;
+        self.pty_rx = None;
+    }
+
+    /// Kill this tab's Claude process and end its loop (Ctrl+C behavior)
+    pub fn kill(&mut self) {
+        self.stop_pty();
+        if self.status == TabStatus::Running {
+            self.driver.finish_iteration(&IterationResult::Terminated);
+        }
+        self.status = TabStatus::Stopped;
+    }
+
+    /// Size of the tab's screen (rows, cols)
+    pub fn size(&self) -> (u16, u16) {
+        self.parser.screen().size()
+    }
+
+    /// Resize the vt100 parser and PTY to match new terminal dimensions
+    pub fn resize(&mut self, rows: u16, cols: u16) {
+        self.parser.screen_mut().set_size(rows, cols);
+        // Also resize the PTY so Claude knows the new terminal size
+        if let Some(ref pty) = self.pty_manager {
+            let _ = pty.resize(rows, cols);
+        }
+    }
+}
+
+impl Drop for Tab {
+    fn drop(&mut self) {
+        self.stop_pty();
+    }
+}
+
+/// Whether the bottom of a screen looks like the agent asks something: a
+/// known prompt in its last lines, or a last line ending in `?`
+fn looks_like_prompt(contents: &str) -> bool {
+    let lines: Vec<&str> = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    let tail = &lines[lines.len().saturating_sub(5)..];
+    tail.last().is_some_and(|line| line.ends_with('?'))
+        || tail.iter().any(|line| {
+            let line = line.to_lowercase();
+            PROMPT_PATTERNS.iter().any(|p| line.contains(p))
+        })
+}
+
+/// Bytes that clear the screen by scrolling every row into the scrollback
+fn scrollback_push(rows: u16) -> Vec<u8> {
+    // Leave the alternate screen a killed harness may have left on, then
+    // push every row off the top from the bottom line
+    let mut bytes = format!("\x1b[?1049l\x1b[0m\x1b[{};1H", rows).into_bytes();
+    bytes.extend(b"\r\n".repeat(usize::from(rows)));
+    bytes.extend(b"\x1b[H");
+    bytes
+}
+
+/// Bytes that bring a fresh parser of the same size to `parser`'s state: the
+/// scrollback, line by line, then the screen. Sent to a client on attach.
+pub fn replay(parser: &mut vt100::Parser) -> Vec<u8> {
+    let (rows, cols) = parser.screen().size();
+    let mut bytes = Vec::new();
+    let history = walk_scrollback(parser, |screen, lines| {
+        for row in screen.rows_formatted(0, cols).take(lines) {
+            bytes.extend(row);
+            bytes.extend(b"\x1b[0m\r\n");
+        }
+    });
+    // The last lines written are still on screen (above an empty row);
+    // scroll exactly those off before drawing the screen over it
+    let on_screen = history.min(usize::from(rows) - 1);
+    bytes.extend(format!("\x1b[{};1H", rows).into_bytes());
+    bytes.extend(b"\r\n".repeat(on_screen));
+    bytes.extend(parser.screen().state_formatted());
+    bytes
+}
+
+/// Walk the parser's scrollback, oldest line first: `page` gets the screen
+/// scrolled back a page at a time, with how many of its top rows are
+/// scrollback lines not given yet. Leaves the parser at the live screen and
+/// returns the scrollback's length.
+pub fn walk_scrollback(
+    parser: &mut vt100::Parser,
+    mut page: impl FnMut(&vt100::Screen, usize),
+) -> usize {
+    let rows = usize::from(parser.screen().size().0).max(1);
+    parser.screen_mut().set_scrollback(usize::MAX);
+    let history = parser.screen().scrollback();
+
+    // At offset `back` the top row is the line `back` from the end
+    let mut back = history;
+    while back > 0 {
+        parser.screen_mut().set_scrollback(back);
+        page(parser.screen(), back.min(rows));
+        back = back.saturating_sub(rows);
+    }
+    parser.screen_mut().set_scrollback(0);
+    history
+}
+
+/// Find a stop signal in the plain text of a tab's screen. For harnesses
+/// that echo the prompt, the signal has to stand on a line of its own (as the
+/// instructions ask), so a signal quoted in the echoed prompt doesn't count.
+fn find_signal(contents: &str, harness: Harness) -> Option<PtyResult> {
+    const SIGNALS: [(&str, PtyResult); 3] = [
+        ("###ALL_TASKS_COMPLETE###", PtyResult::AllComplete),
+        ("###TASK_COMPLETE###", PtyResult::TaskComplete),
+        (crate::question::QUESTION_SIGNAL, PtyResult::Question),
+    ];
+
+    let found = |signal: &str| {
+        if harness.echoes_prompt() {
+            // Ignore the TUI's margins and box borders around the line
+            contents.lines().any(|line| {
+                line.trim_matches(|c: char| c.is_whitespace() || "│┃|".contains(c)) == signal
+            })
+        } else {
+            contents.contains(signal)
+        }
+    };
+    SIGNALS
+        .into_iter()
+        .find(|(signal, _)| found(signal))
+        .map(|(_, result)| result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_signal() {
+        let screen = "Working...\n###TASK_COMPLETE###\n";
+        assert_eq!(
+            find_signal(screen, Harness::Claude),
+            Some(PtyResult::TaskComplete)
+        );
+        assert_eq!(
+            find_signal(screen, Harness::Pi),
+            Some(PtyResult::TaskComplete)
+        );
+        assert_eq!(
+            find_signal(" │ ###ALL_TASKS_COMPLETE### │", Harness::Pi),
+            Some(PtyResult::AllComplete)
+        );
+
+        // A signal quoted inside the echoed prompt
+        let echoed = "> When done, print ###TASK_COMPLETE### on its own line";
+        assert_eq!(
+            find_signal(echoed, Harness::Claude),
+            Some(PtyResult::TaskComplete)
+        );
+        assert_eq!(find_signal(echoed, Harness::Pi), None);
+    }
+
+    #[test]
+    fn test_looks_like_prompt() {
+        assert!(looks_like_prompt(
+            "Edit src/main.rs\n\n Do you want to make this edit?\n ❯ 1. Yes\n   2. No\n\n"
+        ));
+        assert!(looks_like_prompt("Overwrite the file? (y/N)\n"));
+        assert!(looks_like_prompt("Which plan should I start with?\n\n"));
+        assert!(!looks_like_prompt(
+            "Reading src/main.rs\nRunning cargo test\n"
+        ));
+        assert!(!looks_like_prompt(""));
+        // Only the bottom of the screen counts
+        assert!(!looks_like_prompt(
+            "Do you want to proceed?\n1\n2\n3\n4\n5\nRunning cargo test\n"
+        ));
+    }
+
+    #[test]
+    fn test_scrollback_push() {
+        let mut parser = vt100::Parser::new(3, 20, 100);
+        parser.process(b"one\r\n###TASK_COMPLETE###");
+        parser.process(&scrollback_push(3));
+        assert_eq!(parser.screen().contents().trim(), "");
+
+        // The old screen is the newest scrollback page
+        parser.screen_mut().set_scrollback(3);
+        let contents = parser.screen().contents();
+        assert!(contents.starts_with("one\n###TASK_COMPLETE###"));
+    }
+
+    #[test]
+    fn test_walk_scrollback_pages() {
+        let mut parser = vt100::Parser::new(3, 20, 100);
+        let text: Vec<String> = (0..13).map(|i| format!("line {}", i)).collect();
+        parser.process(text.join("\r\n").as_bytes());
+        parser.screen_mut().set_scrollback(4);
+
+        // Four pages, the last one short
+        let mut lines = Vec::new();
+        let mut pages = 0;
+        let history = walk_scrollback(&mut parser, |screen, count| {
+            pages += 1;
+            lines.extend(screen.rows(0, 20).take(count));
+        });
+        assert_eq!(history, 10);
+        assert_eq!(pages, 4);
+        assert_eq!(lines, text[..10]);
+        assert_eq!(parser.screen().scrollback(), 0);
+    }
+
+    #[test]
+    fn test_replay_restores_screen_and_scrollback() {
+        let mut parser = vt100::Parser::new(3, 20, 100);
+        parser.process(b"first\r\nsecond\r\n\x1b[31mred\x1b[0m\r\nfourth\r\nfif");
+
+        let mut restored = vt100::Parser::new(3, 20, 100);
+        restored.process(&replay(&mut parser));
+        assert_eq!(restored.screen().contents(), parser.screen().contents());
+        assert_eq!(restored.screen().cursor_position(), (2, 3));
+        assert_eq!(
+            restored.screen().cell(0, 0).unwrap().fgcolor(),
+            vt100::Color::Idx(1)
+        );
+
+        restored.screen_mut().set_scrollback(usize::MAX);
+        assert_eq!(restored.screen().scrollback(), 2);
+        assert!(
+            restored
+                .screen()
+                .contents()
+                .starts_with("first\nsecond\nred")
+        );
+        assert_eq!(parser.screen().scrollback(), 0);
+    }
+}
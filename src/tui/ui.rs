@@ -2,13 +2,23 @@
 //!
 //! Uses ratatui to render tab bar and content area.
 
-use crate::tui::app::{App, TabStatus};
+use crate::config::Config;
+use crate::plan;
+use crate::tui::activity::Attention;
+use crate::tui::app::{AnswerInput, App, TabView};
+use crate::tui::copy::CopyMode;
+use crate::tui::keymap::{Binding, Keymap};
+use crate::tui::pane;
+use crate::tui::picker::Picker;
+use crate::tui::sidebar::{self, Sidebar};
+use crate::tui::tab::TabStatus;
 use ratatui::Frame;
 use ratatui::buffer::Buffer;
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Tabs, Widget};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Tabs, Widget, Wrap};
+use std::ops::Range;
 
 /// Render the entire TUI
 pub fn render(frame: &mut Frame, app: &App) {
@@ -16,7 +26,28 @@ pub fn render(frame: &mut Frame, app: &App) {
     let chunks = Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).split(frame.area());
 
     render_tab_bar(frame, app, chunks[0]);
-    render_content(frame, app, chunks[1]);
+    // The sidebar takes the right of the content row when shown
+    if let Some(ref sidebar) = app.sidebar {
+        let width = sidebar::sidebar_width(frame.area().width);
+        let columns =
+            Layout::horizontal([Constraint::Min(0), Constraint::Length(width)]).split(chunks[1]);
+        render_content(frame, app, columns[0]);
+        render_sidebar(frame, app, sidebar, columns[1]);
+    } else {
+        render_content(frame, app, chunks[1]);
+    }
+    if let Some(selected) = app.overview {
+        render_overview(frame, app, selected, chunks[1]);
+    }
+    if let Some(ref picker) = app.picker {
+        render_picker(frame, picker, chunks[1]);
+    }
+    if let Some(ref input) = app.answer {
+        render_answer(frame, input, chunks[1]);
+    }
+    if app.help {
+        render_help(frame, &app.config().keymap, chunks[1]);
+    }
 }
 
 /// Render the tab bar
@@ -26,10 +57,11 @@ fn render_tab_bar(frame: &mut Frame, app: &App, area: Rect) {
         .iter()
         .enumerate()
         .map(|(i, tab)| {
-            let status_indicator = match tab.status {
+            let status_indicator = match tab.info.status {
                 TabStatus::Running => "",
-                TabStatus::TaskComplete => " [done]",
+                TabStatus::MaxIterations => " [max]",
                 TabStatus::AllComplete => " [ALL]",
+                TabStatus::Question => " [?]",
                 TabStatus::Stopped => " [X]",
             };
 
@@ -41,20 +73,88 @@ fn render_tab_bar(frame: &mut Frame, app: &App, area: Rect) {
                 Style::default().fg(Color::Gray)
             };
 
-            Line::from(Span::styled(
-                format!("{}{}", tab.id, status_indicator),
+            let plan = tab
+                .info
+                .plan_path
+                .as_deref()
+                .map(|p| format!(" {}", plan::plan_name(p)))
+                .unwrap_or_default();
+
+            let title = Span::styled(
+                format!(
+                    "{}{} ({}) iteration {}/{}{}",
+                    tab.id,
+                    plan,
+                    tab.info.harness,
+                    tab.info.iteration,
+                    tab.info.max_iterations,
+                    status_indicator
+                ),
                 style,
-            ))
+            );
+            match app.attention(i) {
+                Some(attention) => Line::from(vec![
+                    Span::styled(
+                        format!("{} ", attention.badge()),
+                        attention_style(attention),
+                    ),
+                    title,
+                ]),
+                None => Line::from(title),
+            }
         })
         .collect();
 
-    let tabs = Tabs::new(titles)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(" hydra tui - Ctrl+Q to quit "),
-        )
-        .select(app.active_tab_index)
+    // Only the tabs around the active one fit; the others are counted at
+    // either end
+    let widths: Vec<usize> = titles.iter().map(Line::width).collect();
+    let shown = visible_tabs(
+        &widths,
+        app.active_tab_index,
+        area.width.saturating_sub(2).into(),
+    );
+    let hidden = Style::default().fg(Color::DarkGray);
+    let mut selected = app.active_tab_index - shown.start;
+    let mut items: Vec<Line> = Vec::with_capacity(shown.len() + 2);
+    if shown.start > 0 {
+        items.push(Line::from(Span::styled(
+            format!("«{}", shown.start),
+            hidden,
+        )));
+        selected += 1;
+    }
+    let after = titles.len() - shown.end;
+    items.extend(titles.into_iter().skip(shown.start).take(shown.len()));
+    if after > 0 {
+        items.push(Line::from(Span::styled(format!("{}»", after), hidden)));
+    }
+
+    let keymap = &app.config().keymap;
+    let title = if app.prefix_pending {
+        Line::from(Span::styled(
+            format!(" {} - waiting for a key ", keymap.prefix.label()),
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ))
+    } else {
+        let hint = |bindings: &[Binding], what: &str| {
+            bindings
+                .first()
+                .map(|b| format!(", {} {}", b.label(&keymap.prefix), what))
+                .unwrap_or_default()
+        };
+        Line::from(format!(
+            " hydra tui{}{} ",
+            hint(&keymap.detach, "to detach"),
+            hint(&keymap.help, "for help")
+        ))
+    };
+
+    let tabs = Tabs::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .select(selected)
         .highlight_style(
             Style::default()
                 .fg(Color::Yellow)
@@ -65,6 +165,49 @@ fn render_tab_bar(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(tabs, area);
 }
 
+/// Colour of an attention badge
+fn attention_style(attention: Attention) -> Style {
+    let color = match attention {
+        Attention::Error => Color::Red,
+        Attention::Waiting => Color::Magenta,
+        Attention::Idle => Color::Blue,
+        Attention::Output => Color::Cyan,
+    };
+    Style::default().fg(color).add_modifier(Modifier::BOLD)
+}
+
+/// Cells the tab bar puts around a title (padding) and between titles
+const TAB_PADDING: usize = 2;
+const TAB_DIVIDER: usize = 3;
+/// Room kept for a `«N` / `N»` count of hidden tabs, divider included
+const TAB_MORE: usize = 8;
+
+/// The titles (by width) that fit in `available` columns, a window around
+/// `active` reaching as far left as it can, then filled to the right
+fn visible_tabs(widths: &[usize], active: usize, available: usize) -> Range<usize> {
+    let width = |range: Range<usize>| -> usize {
+        widths[range.clone()]
+            .iter()
+            .map(|w| w + TAB_PADDING)
+            .sum::<usize>()
+            + TAB_DIVIDER * range.len().saturating_sub(1)
+    };
+    if widths.is_empty() || width(0..widths.len()) <= available {
+        return 0..widths.len();
+    }
+
+    let room = available.saturating_sub(2 * TAB_MORE);
+    let mut start = active;
+    while start > 0 && width(start - 1..active + 1) <= room {
+        start -= 1;
+    }
+    let mut end = active + 1;
+    while end < widths.len() && width(start..end + 1) <= room {
+        end += 1;
+    }
+    start..end
+}
+
 /// Convert vt100::Color to ratatui::Color
 fn convert_color(color: vt100::Color) -> Color {
     match color {
@@ -74,7 +217,8 @@ fn convert_color(color: vt100::Color) -> Color {
     }
 }
 
-/// Widget that renders a vt100 screen to a ratatui buffer
+/// Widget that renders a vt100 screen to a ratatui buffer. Cells are read at
+/// the screen's scrollback offset, so a scrolled-back tab shows its history.
 struct Vt100Widget<'a> {
     screen: &'a vt100::Screen,
 }
@@ -129,24 +273,142 @@ impl Widget for Vt100Widget<'_> {
     }
 }
 
-/// Render the content area (active tab's output)
+/// Widget that renders copy mode's snapshot: search matches highlighted,
+/// the selection reversed and the cursor on top
+struct CopyWidget<'a> {
+    copy: &'a CopyMode,
+}
+
+impl Widget for CopyWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let copy = self.copy;
+        for row in 0..area.height {
+            let line = copy.top + usize::from(row);
+            if line >= copy.len() {
+                break;
+            }
+            let chars = copy.line(line);
+            let highlights = copy.highlights(line);
+            for col in 0..area.width {
+                let index = usize::from(col);
+                let mut style = Style::default();
+                if highlights.iter().any(|h| h.contains(&index)) {
+                    style = style.fg(Color::Black).bg(Color::Yellow);
+                }
+                if copy.is_selected(line, index) {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                if (line, index) == copy.cursor {
+                    style = Style::default().fg(Color::Black).bg(Color::Cyan);
+                }
+                if let Some(cell) = buf.cell_mut((area.x + col, area.y + row)) {
+                    cell.set_char(chars.get(index).copied().unwrap_or(' '))
+                        .set_style(style);
+                }
+            }
+        }
+    }
+}
+
+/// Title line for copy mode: the position, or the search prompt
+fn copy_mode_title(copy: &CopyMode) -> Line<'static> {
+    match copy.search {
+        Some(ref search) => {
+            let style = if copy.search_failed() {
+                Style::default().fg(Color::White).bg(Color::Red)
+            } else {
+                Style::default().fg(Color::Black).bg(Color::Yellow)
+            };
+            Line::from(Span::styled(
+                format!(
+                    " {}{} ",
+                    if search.forward { '/' } else { '?' },
+                    search.query
+                ),
+                style,
+            ))
+        }
+        None => Line::from(Span::styled(
+            format!(
+                " COPY {}/{} - v/V select, y copy, s save, / search, q quit ",
+                copy.cursor.0 + 1,
+                copy.len()
+            ),
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+    }
+    .right_aligned()
+}
+
+/// Render the content area: the active tab's output, or each pane's tab
+/// while split
 fn render_content(frame: &mut Frame, app: &App, area: Rect) {
-    let status_text = if let Some(tab) = app.active_tab() {
-        match tab.status {
-            TabStatus::Running => " Running ",
-            TabStatus::TaskComplete => " Task Complete ",
-            TabStatus::AllComplete => " All Tasks Complete ",
-            TabStatus::Stopped => " Stopped ",
+    match app.panes {
+        Some(ref panes) => {
+            for (key, pane) in panes.keys.iter().zip(panes.areas(area)) {
+                let tab = app.tabs.iter().find(|t| t.info.key == *key);
+                render_tab_view(frame, app, tab, pane, true);
+            }
         }
-    } else {
-        ""
-    };
+        None => render_tab_view(frame, app, app.active_tab(), area, false),
+    }
+}
 
-    let block = Block::default().borders(Borders::ALL).title(format!(
-        " Tab {} {}",
-        app.active_tab().map(|t| t.id).unwrap_or(0),
+/// Render a tab's output in `area`. Copy mode and notices go to the active
+/// tab; while `split`, its border is highlighted.
+fn render_tab_view(frame: &mut Frame, app: &App, tab: Option<&TabView>, area: Rect, split: bool) {
+    let focused = tab.is_some_and(|t| t.id == app.active_tab_index + 1);
+    let keymap = &app.config().keymap;
+    let status_text = match tab.map(|t| &t.info.status) {
+        Some(TabStatus::Running) => " Running ".to_string(),
+        Some(TabStatus::MaxIterations) => " Max Iterations Reached ".to_string(),
+        Some(TabStatus::AllComplete) => " All Tasks Complete ".to_string(),
+        Some(TabStatus::Question) => match keymap.answer.first() {
+            Some(binding) => format!(" Question - {} to answer ", binding.label(&keymap.prefix)),
+            None => " Question (see .hydra/questions/) ".to_string(),
+        },
+        Some(TabStatus::Stopped) => " Stopped ".to_string(),
+        None => String::new(),
+    };
+    let plan = tab
+        .and_then(|t| t.info.plan_path.as_deref())
+        .map(|p| format!("- {} ", p.display()))
+        .unwrap_or_default();
+    let mut block = Block::default().borders(Borders::ALL).title(format!(
+        " Tab {} {}{}",
+        tab.map(|t| t.id).unwrap_or(0),
+        plan,
         status_text
     ));
+    if split && focused {
+        block = block.border_style(Style::default().fg(Color::Yellow));
+    }
+    // Copy mode, or not following live output: say where the view is
+    let copy = app.copy.as_ref().filter(|_| focused);
+    if let Some(copy) = copy {
+        block = block.title(copy_mode_title(copy));
+    } else if let Some(offset) = tab.map(|t| t.scroll_offset()).filter(|o| *o > 0) {
+        block = block.title(
+            Line::from(Span::styled(
+                format!(
+                    " SCROLLED {} line(s) back - Shift+PgDn or type to follow ",
+                    offset
+                ),
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ))
+            .right_aligned(),
+        );
+    }
+
+    if let Some(ref notice) = app.notice.as_ref().filter(|_| focused || tab.is_none()) {
+        block = block.title_bottom(format!(" {} ", notice));
+    }
 
     // Calculate inner area (inside the borders)
     let inner_area = block.inner(area);
@@ -154,7 +416,9 @@ fn render_content(frame: &mut Frame, app: &App, area: Rect) {
     // Render the block border
     frame.render_widget(block, area);
 
-    if let Some(tab) = app.active_tab() {
+    if let Some(copy) = copy {
+        frame.render_widget(CopyWidget { copy }, inner_area);
+    } else if let Some(tab) = tab {
         // Render vt100 screen contents directly to the inner area
         let widget = Vt100Widget {
             screen: tab.parser.screen(),
@@ -162,7 +426,7 @@ fn render_content(frame: &mut Frame, app: &App, area: Rect) {
         frame.render_widget(widget, inner_area);
     } else {
         // No active tab - show help message
-        let help_text = "No active tab. Press Ctrl+T to create one.";
+        let help_text = "No active tab. Press Ctrl+O to open one.";
         if inner_area.width > 0 && inner_area.height > 0 {
             let buf = frame.buffer_mut();
             for (i, ch) in help_text.chars().enumerate() {
@@ -176,3 +440,348 @@ fn render_content(frame: &mut Frame, app: &App, area: Rect) {
         }
     }
 }
+
+/// Render the overview over the content area: a thumbnail per tab with its
+/// status and the last lines of its screen, the selected one highlighted
+fn render_overview(frame: &mut Frame, app: &App, selected: usize, area: Rect) {
+    frame.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Overview - arrows select, Enter to switch, Esc to close ");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let cells = pane::grid(
+        app.tabs.len(),
+        pane::overview_columns(app.tabs.len()),
+        inner,
+    );
+    for ((i, tab), cell) in app.tabs.iter().enumerate().zip(cells) {
+        let info = &tab.info;
+        let plan = info
+            .plan_path
+            .as_deref()
+            .map(|p| format!(" {}", plan::plan_name(p)))
+            .unwrap_or_default();
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {}{} ({}) ", tab.id, plan, info.harness));
+        if i == selected {
+            block = block
+                .border_style(Style::default().fg(Color::Yellow))
+                .title_style(Style::default().add_modifier(Modifier::BOLD));
+        }
+
+        let mut status = format!(
+            "iteration {}/{} - {}",
+            info.iteration,
+            info.max_iterations,
+            info.status.describe()
+        );
+        if info.status == TabStatus::Running {
+            status.push_str(&format!(" {}", sidebar::clock(info.elapsed_seconds)));
+        }
+        let inner = block.inner(cell);
+        let mut status = vec![Span::styled(status, Style::default().fg(Color::Cyan))];
+        if let Some(attention) = app.attention(i) {
+            status.push(Span::styled(
+                format!(" {} {}", attention.badge(), attention.describe(info)),
+                attention_style(attention),
+            ));
+        }
+        let mut lines = vec![Line::from(status)];
+        lines.extend(
+            pane::thumbnail(
+                tab.parser.screen(),
+                usize::from(inner.height.saturating_sub(1)),
+                inner.width,
+            )
+            .into_iter()
+            .map(Line::from),
+        );
+        frame.render_widget(Paragraph::new(lines).block(block), cell);
+    }
+}
+
+/// Render the progress sidebar for the active tab: iteration and elapsed
+/// time, the plan checklist and the scratchpad's last lines
+fn render_sidebar(frame: &mut Frame, app: &App, sidebar: &Sidebar, area: Rect) {
+    let Some(tab) = app.active_tab() else {
+        frame.render_widget(Block::default().borders(Borders::ALL), area);
+        return;
+    };
+    let info = &tab.info;
+    let scratchpad_height = area.height / 3;
+    let rows = Layout::vertical([
+        Constraint::Length(4),
+        Constraint::Min(3),
+        Constraint::Length(scratchpad_height),
+    ])
+    .split(area);
+
+    // Iteration and elapsed time, yellow past 80% of the timeout
+    let mut status = vec![Line::from(format!(
+        "Iteration {}/{}",
+        info.iteration, info.max_iterations
+    ))];
+    if info.status == TabStatus::Running {
+        let elapsed = info.elapsed_seconds;
+        let timeout = info.timeout_seconds;
+        let style = if elapsed * 5 >= timeout * 4 {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        status.push(Line::from(Span::styled(
+            format!(
+                "Elapsed {} / {}",
+                sidebar::clock(elapsed),
+                sidebar::clock(timeout)
+            ),
+            style,
+        )));
+    } else {
+        status.push(Line::from("Not running"));
+    }
+    frame.render_widget(
+        Paragraph::new(status).block(Block::default().borders(Borders::ALL).title(" Progress ")),
+        rows[0],
+    );
+
+    // The plan checklist, scrolled to keep the next task in view
+    let (title, lines) = match (info.plan_path.as_deref(), sidebar.tasks.as_ref()) {
+        (None, _) => (" No plan ".to_string(), Vec::new()),
+        (Some(_), None) => (
+            " Plan ".to_string(),
+            vec![Line::from(Span::styled(
+                "plan file not readable",
+                Style::default().fg(Color::Red),
+            ))],
+        ),
+        (Some(_), Some(plan)) => {
+            let next = plan.tasks.iter().position(|t| !t.checked);
+            let visible = usize::from(rows[1].height.saturating_sub(2));
+            let first = next
+                .map(|n| n.saturating_sub(visible / 3))
+                .unwrap_or(0)
+                .min(plan.tasks.len().saturating_sub(visible));
+            let lines = plan
+                .tasks
+                .iter()
+                .enumerate()
+                .skip(first)
+                .take(visible)
+                .map(|(i, task)| {
+                    let style = if Some(i) == next {
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD)
+                    } else if task.checked {
+                        Style::default().fg(Color::DarkGray)
+                    } else {
+                        Style::default()
+                    };
+                    Line::from(Span::styled(
+                        format!(
+                            "{}[{}] {}",
+                            " ".repeat(task.indent),
+                            if task.checked { 'x' } else { ' ' },
+                            task.text
+                        ),
+                        style,
+                    ))
+                })
+                .collect();
+            (
+                format!(" Plan {}/{} done ", plan.checked_count(), plan.tasks.len()),
+                lines,
+            )
+        }
+    };
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title)),
+        rows[1],
+    );
+
+    let tail: Vec<Line> = sidebar
+        .scratchpad_tail(usize::from(scratchpad_height.saturating_sub(2)))
+        .into_iter()
+        .map(|line| Line::from(line.to_string()))
+        .collect();
+    frame.render_widget(
+        Paragraph::new(tail).block(Block::default().borders(Borders::ALL).title(" Scratchpad ")),
+        rows[2],
+    );
+}
+
+/// Render the new-tab picker centered over the content area
+fn render_picker(frame: &mut Frame, picker: &Picker, area: Rect) {
+    let width = area.width.saturating_sub(4).min(80);
+    let height = (picker.plans.len() as u16 + 7).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    // Keep the selection in view when there are more plans than rows
+    let visible = (height as usize).saturating_sub(7).max(1);
+    let first = (picker.selected_plan + 1).saturating_sub(visible);
+    let mut lines: Vec<Line> = picker
+        .plans
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(visible)
+        .map(|(i, choice)| {
+            if i == picker.selected_plan {
+                Line::from(Span::styled(
+                    format!("> {}", choice.label),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ))
+            } else {
+                Line::from(format!("  {}", choice.label))
+            }
+        })
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(format!(
+        "Harness: {} (Left/Right for another)",
+        picker.harness
+    )));
+    lines.push(Line::from(format!(
+        "Prompt: {}{}",
+        picker.prompt().path.display(),
+        if picker.prompts.len() > 1 {
+            " (Tab for another)"
+        } else {
+            ""
+        }
+    )));
+    if let Some(ref error) = picker.error {
+        lines.push(Line::from(Span::styled(
+            error.clone(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" New tab - Enter to open, Esc to cancel ");
+    frame.render_widget(Clear, popup);
+    frame.render_widget(Paragraph::new(lines).block(block), popup);
+}
+
+/// Render the answer prompt centered over the content area: the question,
+/// then the answer as typed
+fn render_answer(frame: &mut Frame, input: &AnswerInput, area: Rect) {
+    let width = area.width.saturating_sub(4).min(80);
+    let inner = usize::from(width.saturating_sub(2)).max(1);
+    let mut lines: Vec<Line> = input
+        .question
+        .lines()
+        .map(|line| Line::from(line.to_string()))
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        format!("> {}_", input.text),
+        Style::default().fg(Color::Yellow),
+    )));
+    lines.push(Line::from("(empty answer: the agent decides)"));
+
+    // Wrapped lines take more rows
+    let rows: usize = lines.iter().map(|l| l.width().max(1).div_ceil(inner)).sum();
+    let height = (rows as u16).saturating_add(2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Question from the agent - Enter to answer, Esc to cancel ");
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(block),
+        popup,
+    );
+}
+
+/// Render the keybinding help overlay: every action with its bindings
+fn render_help(frame: &mut Frame, keymap: &Keymap, area: Rect) {
+    let label = |bindings: &[Binding]| {
+        let labels: Vec<String> = bindings.iter().map(|b| b.label(&keymap.prefix)).collect();
+        if labels.is_empty() {
+            "(unbound)".to_string()
+        } else {
+            labels.join(", ")
+        }
+    };
+    let key_style = Style::default().fg(Color::Yellow);
+    let row = |keys: String, what: &str| {
+        Line::from(vec![
+            Span::styled(format!("{:<28}", keys), key_style),
+            Span::raw(what.to_string()),
+        ])
+    };
+
+    let mut lines = vec![row(label(&keymap.switch_tab), "Switch to tab 1, 2, ...")];
+    lines.push(row(
+        format!("{} 1-9", keymap.prefix.label()),
+        "Switch to tab 1-9",
+    ));
+    for (action, bindings) in keymap.actions() {
+        lines.push(row(label(bindings), action.describe()));
+    }
+    lines.push(row(
+        format!("{} {}", keymap.prefix.label(), keymap.prefix.label()),
+        "Send the prefix key to the agent",
+    ));
+    lines.push(Line::from(""));
+    lines.push(Line::from(format!(
+        "Change these in the [keymap] section of {}",
+        Config::global_config_path().display()
+    )));
+
+    let width = area.width.saturating_sub(4).min(80);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Keys - any key to close ");
+    frame.render_widget(Clear, popup);
+    frame.render_widget(Paragraph::new(lines).block(block), popup);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visible_tabs() {
+        // Each title takes its width + 2, plus 3 between titles
+        assert_eq!(visible_tabs(&[10, 10, 10], 2, 42), 0..3);
+        assert_eq!(visible_tabs(&[], 0, 10), 0..0);
+
+        // 6 tabs of 12 cells each in 60 columns: 44 left for titles after
+        // the counts, so 3 tabs fit
+        let widths = [10; 6];
+        assert_eq!(visible_tabs(&widths, 0, 60), 0..3);
+        assert_eq!(visible_tabs(&widths, 3, 60), 1..4);
+        assert_eq!(visible_tabs(&widths, 5, 60), 3..6);
+
+        // The active tab is shown even when it doesn't fit
+        assert_eq!(visible_tabs(&[100, 100], 1, 50), 1..2);
+    }
+}